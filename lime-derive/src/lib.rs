@@ -0,0 +1,260 @@
+//! `#[derive(RemoteStruct)]` for `lime::remote::structs`, see there for the attributes.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Expr, Fields, Ident, Type, Visibility, parse_macro_input};
+
+#[proc_macro_derive(RemoteStruct, attributes(remote))]
+pub fn derive_remote_struct(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	expand(&input)
+		.unwrap_or_else(|e| e.to_compile_error())
+		.into()
+}
+
+struct Field {
+	ident: Ident,
+	vis: Visibility,
+	ty: Type,
+	offset: Option<Expr>,
+	padding: Option<Expr>,
+	pointer: bool,
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Field> {
+	let mut parsed = Field {
+		ident: field.ident.clone().expect("named field"),
+		vis: field.vis.clone(),
+		ty: field.ty.clone(),
+		offset: None,
+		padding: None,
+		pointer: false,
+	};
+
+	for attr in field.attrs.iter().filter(|a| a.path().is_ident("remote")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("offset") {
+				parsed.offset = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("padding") {
+				parsed.padding = Some(meta.value()?.parse()?);
+			} else if meta.path.is_ident("pointer") {
+				parsed.pointer = true;
+			} else {
+				return Err(meta.error("expected `offset`, `padding` or `pointer`"));
+			}
+			Ok(())
+		})?;
+	}
+
+	if parsed.offset.is_some() && parsed.padding.is_some() {
+		return Err(syn::Error::new_spanned(
+			field,
+			"`padding` only applies to fields without an `offset`",
+		));
+	}
+	Ok(parsed)
+}
+
+fn struct_size(input: &DeriveInput) -> syn::Result<Option<Expr>> {
+	let mut size = None;
+	for attr in input.attrs.iter().filter(|a| a.path().is_ident("remote")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("size") {
+				size = Some(meta.value()?.parse()?);
+				Ok(())
+			} else {
+				Err(meta.error("expected `size`"))
+			}
+		})?;
+	}
+	Ok(size)
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+	let name = &input.ident;
+	if !input.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(
+			&input.generics,
+			"RemoteStruct cannot be derived for generic structs",
+		));
+	}
+	let fields = match &input.data {
+		Data::Struct(data) => match &data.fields {
+			Fields::Named(fields) => &fields.named,
+			_ => {
+				return Err(syn::Error::new_spanned(
+					input,
+					"RemoteStruct needs named fields",
+				));
+			}
+		},
+		_ => {
+			return Err(syn::Error::new_spanned(
+				input,
+				"RemoteStruct can only be derived for structs",
+			));
+		}
+	};
+	let fields = fields
+		.iter()
+		.map(parse_field)
+		.collect::<syn::Result<Vec<_>>>()?;
+
+	let lime = quote!(::lime);
+	let remote_field = quote!(#lime::remote::structs::RemoteField);
+	let error = quote!(#lime::errors::Error);
+
+	// Offsets and ends are consts, so field sizes can come from the field types.
+	let mut consts = Vec::new();
+	let mut offsets = Vec::new();
+	let mut ends = Vec::new();
+	for (i, field) in fields.iter().enumerate() {
+		let offset = format_ident!("OFFSET_{}", i);
+		let end = format_ident!("END_{}", i);
+		let value = match (&field.offset, ends.last()) {
+			(Some(expr), _) => quote!(#expr),
+			(None, previous) => {
+				let previous = previous.map_or(quote!(0), |end| quote!(#end));
+				let padding = field
+					.padding
+					.as_ref()
+					.map_or(quote!(0), |expr| quote!(#expr));
+				quote!(#previous + #padding)
+			}
+		};
+		let ty = &field.ty;
+		let size = if field.pointer {
+			quote!(<u64 as #remote_field>::SIZE)
+		} else {
+			quote!(<#ty as #remote_field>::SIZE)
+		};
+		consts.push(quote! {
+			const #offset: usize = #value;
+			const #end: usize = #offset + #size;
+		});
+		offsets.push(offset);
+		ends.push(end);
+	}
+
+	let fields_end = if ends.is_empty() {
+		quote!(0)
+	} else {
+		quote! {{
+			let mut end = 0;
+			#(if #ends > end { end = #ends; })*
+			end
+		}}
+	};
+	let size = match struct_size(input)? {
+		Some(size) => {
+			let message = format!("the fields of {} do not fit in its size", name);
+			quote! {
+				const SIZE: usize = #size;
+				const _: () = assert!(#fields_end <= SIZE, #message);
+			}
+		}
+		None => quote!(const SIZE: usize = #fields_end;),
+	};
+
+	let idents: Vec<&Ident> = fields.iter().map(|f| &f.ident).collect();
+	let read_fields = fields
+		.iter()
+		.zip(offsets.iter().zip(&ends))
+		.map(|(field, (offset, end))| {
+			let ty = &field.ty;
+			if field.pointer {
+				quote! {
+					<#ty as #lime::remote::structs::PointerTarget>::read_target(
+						process,
+						<u64 as #remote_field>::decode(&bytes[#offset..#end])?,
+					)?
+				}
+			} else {
+				quote!(<#ty as #remote_field>::decode(&bytes[#offset..#end])?)
+			}
+		});
+
+	let writers = fields
+		.iter()
+		.zip(&offsets)
+		.filter(|(f, _)| !f.pointer)
+		.map(|(field, offset)| {
+			let Field { ident, vis, ty, .. } = field;
+			let writer = format_ident!("write_{}", ident);
+			let doc = format!(
+				"Writes `{}` of the `{}` at `addr`, leaving the other fields alone.",
+				ident, name
+			);
+			quote! {
+				#[doc = #doc]
+				#vis fn #writer<
+					P: #lime::traits::ReadProcessMemory + #lime::traits::WriteProcessMemory + ?Sized,
+				>(
+					process: &mut P,
+					addr: u64,
+					value: &#ty,
+				) -> ::core::result::Result<(), #error> {
+					#remote_field::write_remote(value, process, addr + #offset as u64)
+				}
+			}
+		});
+
+	// Without pointers the struct is plain data, which can be nested and written whole.
+	let body = if fields.iter().any(|f| f.pointer) {
+		quote! {
+			impl #lime::remote::structs::RemoteStruct for #name {
+				const SIZE: usize = SIZE;
+
+				fn from_remote_bytes<P: #lime::traits::ReadProcessMemory + ?Sized>(
+					bytes: &[u8],
+					process: &mut P,
+				) -> ::core::result::Result<Self, #error> {
+					let bytes = &bytes[..SIZE];
+					Ok(Self { #(#idents: #read_fields,)* })
+				}
+			}
+		}
+	} else {
+		let encode = fields
+			.iter()
+			.zip(offsets.iter().zip(&ends))
+			.map(|(field, (offset, end))| {
+				let ident = &field.ident;
+				quote!(#remote_field::encode(&self.#ident, &mut bytes[#offset..#end]);)
+			});
+		quote! {
+			impl #remote_field for #name {
+				const SIZE: usize = SIZE;
+
+				fn decode(bytes: &[u8]) -> ::core::result::Result<Self, #error> {
+					if bytes.len() != SIZE {
+						return Err(#error::new(
+							#lime::errors::ErrorKind::BadDataType,
+							format!("expected {} bytes, got {}", SIZE, bytes.len()),
+						));
+					}
+					Ok(Self { #(#idents: #read_fields,)* })
+				}
+
+				fn encode(&self, bytes: &mut [u8]) {
+					#(#encode)*
+				}
+			}
+		}
+	};
+
+	Ok(quote! {
+		const _: () = {
+			#(#consts)*
+			#size
+
+			#body
+
+			#[allow(dead_code)]
+			impl #name {
+				#(#writers)*
+			}
+		};
+	})
+}
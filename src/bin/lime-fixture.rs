@@ -0,0 +1,118 @@
+//! Target process for lime's tests.
+//!
+//! Places known values on the heap, the main thread's stack and in anonymous mappings,
+//! prints where they are and then waits on stdin:
+//!
+//! ```text
+//! heap 0x55d0c1000010
+//! ...
+//! ready
+//! ```
+//!
+//! Every area starts with a 16-byte marker (see `marker`) followed by a `u64` value. Sending
+//! `values` prints the current value of each area, any other line or EOF exits.
+
+use std::{
+	hint::black_box,
+	io::{BufRead, Write},
+};
+
+const HEAP_VALUE: u64 = 0x1111_2222_3333_4444;
+const STACK_VALUE: u64 = 0x5555_6666_7777_8888;
+const ANON_VALUE: u64 = 0x9999_aaaa_bbbb_cccc;
+const READONLY_VALUE: u64 = 0xdddd_eeee_ffff_0000;
+
+/// `LIM` + tag, the pid, then the inverted tag and pid. Built at runtime so the bytes never
+/// appear in the binary itself.
+fn marker(tag: u8, pid: u32) -> [u8; 16] {
+	let mut m = [0u8; 16];
+	m[0..3].copy_from_slice(b"LIM");
+	m[3] = tag;
+	m[4..8].copy_from_slice(&pid.to_le_bytes());
+	m[8] = !tag;
+	m[12..16].copy_from_slice(&(!pid).to_le_bytes());
+	m
+}
+
+fn mmap(len: usize, prot: i32) -> *mut u8 {
+	let ptr = unsafe {
+		libc::mmap(
+			std::ptr::null_mut(),
+			len,
+			prot,
+			libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+			-1,
+			0,
+		)
+	};
+	assert_ne!(ptr, libc::MAP_FAILED, "mmap failed");
+	ptr as *mut u8
+}
+
+/// Writes the marker and value at `area` and returns the address of the value.
+unsafe fn fill(area: *mut u8, tag: u8, pid: u32, value: u64) -> *mut u64 {
+	unsafe {
+		std::ptr::copy_nonoverlapping(marker(tag, pid).as_ptr(), area, 16);
+		let value_ptr = area.add(16) as *mut u64;
+		value_ptr.write_unaligned(value);
+		value_ptr
+	}
+}
+
+fn main() {
+	let pid = std::process::id();
+	let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+
+	// u64 elements keep the values aligned for the volatile reads below.
+	let mut heap = vec![0u64; 512].into_boxed_slice();
+	let heap_value = unsafe { fill(heap.as_mut_ptr() as *mut u8, b'H', pid, HEAP_VALUE) };
+
+	let mut stack = black_box([0u64; 8]);
+	let stack_value = unsafe { fill(stack.as_mut_ptr() as *mut u8, b'S', pid, STACK_VALUE) };
+
+	let anon = mmap(page, libc::PROT_READ | libc::PROT_WRITE);
+	let anon_value = unsafe { fill(anon, b'A', pid, ANON_VALUE) };
+
+	let readonly = mmap(page, libc::PROT_READ | libc::PROT_WRITE);
+	let readonly_value = unsafe { fill(readonly, b'R', pid, READONLY_VALUE) };
+	unsafe { libc::mprotect(readonly as *mut libc::c_void, page, libc::PROT_READ) };
+
+	// A writable page directly followed by an inaccessible one.
+	let guarded = mmap(page * 2, libc::PROT_READ | libc::PROT_WRITE);
+	let guard = unsafe { guarded.add(page) };
+	unsafe { libc::mprotect(guard as *mut libc::c_void, page, libc::PROT_NONE) };
+
+	let mut out = std::io::stdout().lock();
+	writeln!(out, "heap {:p}", heap_value).unwrap();
+	writeln!(out, "stack {:p}", stack_value).unwrap();
+	writeln!(out, "anon {:p}", anon_value).unwrap();
+	writeln!(out, "readonly {:p}", readonly_value).unwrap();
+	writeln!(out, "guarded {:p}", guarded).unwrap();
+	writeln!(out, "guard {:p}", guard).unwrap();
+	writeln!(out, "ready").unwrap();
+	out.flush().unwrap();
+
+	for line in std::io::stdin().lock().lines() {
+		let Ok(line) = line else {
+			break;
+		};
+		if line.trim() != "values" {
+			break;
+		}
+
+		for (name, ptr) in [
+			("heap", heap_value),
+			("stack", stack_value),
+			("anon", anon_value),
+			("readonly", readonly_value),
+		] {
+			let value = unsafe { std::ptr::read_volatile(ptr) };
+			writeln!(out, "{} {:#x}", name, value).unwrap();
+		}
+		writeln!(out, "ready").unwrap();
+		out.flush().unwrap();
+	}
+
+	black_box(&heap);
+	black_box(&stack);
+}
@@ -0,0 +1,273 @@
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+use lime::{scan_mode::ScanMode, traits::ScanTarget};
+
+use super::output::OutputFormat;
+
+/// Read, write and scan the memory of running processes.
+///
+/// Without a command, starts an interactive shell. Exit status is 0 on success, 1 if a scan
+/// found nothing and 2 on errors.
+#[derive(Parser, Debug)]
+#[command(name = "lime", version)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+	/// Print processes, regions and matches as a JSON array
+	#[arg(long, global = true, conflicts_with = "ndjson")]
+	pub json: bool,
+	/// Print processes, regions and matches as one JSON object per line
+	#[arg(long, global = true)]
+	pub ndjson: bool,
+}
+
+impl Cli {
+	pub fn output_format(&self) -> OutputFormat {
+		if self.json {
+			OutputFormat::Json
+		} else if self.ndjson {
+			OutputFormat::Ndjson
+		} else {
+			OutputFormat::Text
+		}
+	}
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+	/// List running processes
+	Ps {
+		/// Only list processes whose name contains this
+		filter: Option<String>,
+	},
+	/// Print the memory map of a process
+	Maps {
+		pid: u32,
+		/// Append Rss, Private_Dirty and Swap from smaps, in KiB
+		#[arg(long)]
+		smaps: bool,
+	},
+	/// Scan memory for a value or byte pattern
+	Scan(ScanArgs),
+	/// Read a value, a string or a hex dump
+	Read(ReadArgs),
+	/// Write a value, a string or bytes
+	Write(WriteArgs),
+	/// Write a snapshot of a process' memory to a file
+	#[cfg(any(feature = "ptrace", feature = "offline"))]
+	Dump(DumpArgs),
+	/// Start an interactive shell that keeps results, watches and history between commands
+	Repl(AttachArgs),
+	/// Show processes, live results, watches and memory in a terminal UI
+	#[cfg(feature = "tui")]
+	Tui(AttachArgs),
+	/// Browse and edit a process' memory in the terminal UI
+	#[cfg(feature = "tui")]
+	Hex(HexArgs),
+	/// List the entries of a cheat table, resolved in a process, or convert it
+	Table(TableArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ProcessArgs {
+	/// Process id
+	#[arg(short, long, required_unless_present = "name", conflicts_with = "name")]
+	pub pid: Option<u32>,
+	/// Name of the process, has to match exactly one running process
+	#[arg(short, long)]
+	pub name: Option<String>,
+	/// Wait for a process matching --name to start
+	#[arg(long, requires = "name")]
+	pub wait: bool,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct AttachArgs {
+	/// Attach to this process id on start
+	#[arg(short, long, conflicts_with = "name")]
+	pub pid: Option<u32>,
+	/// Attach to the process with this name on start
+	#[arg(short, long)]
+	pub name: Option<String>,
+	/// Wait for a process matching --name to start
+	#[arg(long, requires = "name")]
+	pub wait: bool,
+}
+
+impl AttachArgs {
+	/// The process to attach to on start, if any.
+	pub fn process(&self) -> Option<ProcessArgs> {
+		(self.pid.is_some() || self.name.is_some()).then(|| ProcessArgs {
+			pid: self.pid,
+			name: self.name.clone(),
+			wait: self.wait,
+		})
+	}
+}
+
+#[cfg(feature = "tui")]
+#[derive(Args, Debug)]
+pub struct HexArgs {
+	#[command(flatten)]
+	pub process: ProcessArgs,
+	/// Address to start at, also <module>[+<offset>]; defaults to the first readable region
+	pub address: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TableArgs {
+	/// A .toml or .json table, or a Cheat Engine .ct file to import
+	pub file: std::path::PathBuf,
+	#[command(flatten)]
+	pub process: AttachArgs,
+	/// Save the table to this .toml or .json file instead of listing it
+	#[arg(short, long)]
+	pub output: Option<std::path::PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+	#[command(flatten)]
+	pub process: ProcessArgs,
+	/// How --value is interpreted
+	#[arg(short = 't', long = "type", value_parser = parse_scan_mode, default_value = "pattern")]
+	pub mode: ScanMode,
+	/// Value to look for, e.g. `100`, `0x64`, `1.5`, `"name"` or `48 8B ?? 05`
+	#[arg(short, long, allow_hyphen_values = true)]
+	pub value: String,
+	/// heap-stack, anon, anon-other, module:<name>, range:<start>-<end> or stack:<tid>
+	#[arg(long, default_value = "heap-stack")]
+	pub target: TargetArg,
+	/// Skip regions without resident pages
+	#[arg(long)]
+	pub resident: bool,
+	/// Only read pages that are in RAM or swapped out, according to the pagemap
+	#[arg(long)]
+	pub touched: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReadArgs {
+	#[command(flatten)]
+	pub process: ProcessArgs,
+	#[arg(value_parser = parse_address)]
+	pub address: u64,
+	/// Decode the memory as this type instead of printing a hex dump
+	#[arg(short = 't', long = "type", value_parser = parse_scan_mode)]
+	pub mode: Option<ScanMode>,
+	/// Bytes to dump, or the maximum length of a string
+	#[arg(short, long, default_value_t = 64)]
+	pub len: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct WriteArgs {
+	#[command(flatten)]
+	pub process: ProcessArgs,
+	#[arg(value_parser = parse_address)]
+	pub address: u64,
+	/// How --value is interpreted, patterns must not contain wildcards
+	#[arg(short = 't', long = "type", value_parser = parse_scan_mode)]
+	pub mode: ScanMode,
+	#[arg(short, long, allow_hyphen_values = true)]
+	pub value: String,
+}
+
+#[cfg(any(feature = "ptrace", feature = "offline"))]
+#[derive(Args, Debug)]
+pub struct DumpArgs {
+	#[command(flatten)]
+	pub process: ProcessArgs,
+	#[arg(short, long)]
+	pub output: std::path::PathBuf,
+	#[arg(short, long, value_enum, default_value_t = DumpFormat::default())]
+	pub format: DumpFormat,
+}
+
+#[cfg(any(feature = "ptrace", feature = "offline"))]
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DumpFormat {
+	/// ELF core file, loadable with gdb
+	#[cfg(feature = "ptrace")]
+	#[default]
+	Core,
+	/// LiME image with a `.maps` sidecar
+	#[cfg(feature = "offline")]
+	#[cfg_attr(not(feature = "ptrace"), default)]
+	Lime,
+}
+
+/// Owned form of `ScanTarget`, as parsed from `--target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetArg {
+	HeapAndStack,
+	Anonymous,
+	AnonymousNonHeapAndStack,
+	Module(String),
+	Range(u64, u64),
+	ThreadStack(u32),
+}
+
+impl TargetArg {
+	pub fn scan_target(&self) -> ScanTarget<'_> {
+		match self {
+			TargetArg::HeapAndStack => ScanTarget::HeapAndStack,
+			TargetArg::Anonymous => ScanTarget::Anonymous,
+			TargetArg::AnonymousNonHeapAndStack => ScanTarget::AnonymousNonHeapAndStack,
+			TargetArg::Module(name) => ScanTarget::Module(name),
+			TargetArg::Range(start, end) => ScanTarget::Range(*start, *end),
+			TargetArg::ThreadStack(tid) => ScanTarget::ThreadStack(*tid),
+		}
+	}
+}
+
+impl FromStr for TargetArg {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (kind, arg) = match s.split_once(':') {
+			Some((kind, arg)) => (kind, Some(arg)),
+			None => (s, None),
+		};
+
+		match (kind, arg) {
+			("heap-stack", None) => Ok(TargetArg::HeapAndStack),
+			("anon", None) => Ok(TargetArg::Anonymous),
+			("anon-other", None) => Ok(TargetArg::AnonymousNonHeapAndStack),
+			("module", Some(name)) if !name.is_empty() => Ok(TargetArg::Module(name.to_string())),
+			("range", Some(range)) => {
+				let (start, end) = range
+					.split_once('-')
+					.ok_or_else(|| format!("expected range:<start>-<end>, got {}", s))?;
+				let (start, end) = (parse_address(start)?, parse_address(end)?);
+				if start >= end {
+					return Err(format!("empty range {}", range));
+				}
+				Ok(TargetArg::Range(start, end))
+			}
+			("stack", Some(tid)) => tid
+				.parse()
+				.map(TargetArg::ThreadStack)
+				.map_err(|e| format!("invalid tid {}: {}", tid, e)),
+			_ => Err(format!("unknown scan target {}", s)),
+		}
+	}
+}
+
+pub fn parse_scan_mode(s: &str) -> Result<ScanMode, String> {
+	ScanMode::from_name(s).ok_or_else(|| {
+		let names: Vec<_> = ScanMode::ALL.iter().map(|m| m.name()).collect();
+		format!("expected one of {}", names.join(", "))
+	})
+}
+
+/// `0x`-prefixed hex or decimal.
+pub fn parse_address(s: &str) -> Result<u64, String> {
+	let s = s.trim();
+	match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+		Some(hex) => u64::from_str_radix(hex, 16),
+		None => s.parse(),
+	}
+	.map_err(|e| format!("invalid address {}: {}", s, e))
+}
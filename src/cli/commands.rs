@@ -0,0 +1,397 @@
+use std::{process::ExitCode, time::Instant};
+
+#[cfg(feature = "ptrace")]
+use lime::dump::elfcore::dump_process_core;
+#[cfg(feature = "offline")]
+use lime::dump::lime::dump_process_lime;
+use lime::{
+	Process,
+	errors::{Error, ErrorKind},
+	maps::ProcMemoryMaps,
+	process::{
+		query::{ProcessQuery, list_processes},
+		watch::wait_for_process,
+	},
+	scan_mode::ScanMode,
+	table::cheat_table::CheatTable,
+	traits::{ReadProcessMemory, RegionFilter, ScanTarget, WriteProcessMemory},
+};
+
+#[cfg(any(feature = "ptrace", feature = "offline"))]
+use super::args::{DumpArgs, DumpFormat};
+#[cfg(feature = "tui")]
+use super::tui;
+use super::{
+	args::{Command, ProcessArgs, ReadArgs, ScanArgs, TableArgs, TargetArg, WriteArgs},
+	output::{
+		OutputFormat, describe_addr, print_hexdump, print_match_text, print_records,
+		read_value_string,
+	},
+	records::{MatchRecord, ProcessRecord, RegionRecord, TableEntryRecord},
+	repl,
+};
+
+/// Exit status of a scan without matches, so scripts can tell it apart from errors.
+const EXIT_NO_MATCHES: u8 = 1;
+
+pub fn run(command: Command, format: OutputFormat) -> Result<ExitCode, Error> {
+	match command {
+		Command::Ps { filter } => ps(filter.as_deref(), format),
+		Command::Maps { pid, smaps } => maps(pid, smaps, format),
+		Command::Scan(args) => scan(args, format),
+		Command::Read(args) => read(args),
+		Command::Write(args) => write(args),
+		#[cfg(any(feature = "ptrace", feature = "offline"))]
+		Command::Dump(args) => dump(args),
+		Command::Repl(args) => repl::shell::run(args.process().as_ref()),
+		#[cfg(feature = "tui")]
+		Command::Tui(args) => {
+			tui::app::run(args.process().as_ref(), tui::app::Pane::Processes, None)
+		}
+		#[cfg(feature = "tui")]
+		Command::Hex(args) => tui::app::run(
+			Some(&args.process),
+			tui::app::Pane::Memory,
+			args.address.as_deref(),
+		),
+		Command::Table(args) => table(args, format),
+	}
+}
+
+/// The pid selected by `--pid`, or the single process matching `--name`.
+pub fn resolve_pid(args: &ProcessArgs) -> Result<u32, Error> {
+	if let Some(pid) = args.pid {
+		return Ok(pid);
+	}
+
+	let Some(name) = args.name.as_deref() else {
+		return Err(Error::new(ErrorKind::InvalidPid, "no process selected"));
+	};
+	let query = ProcessQuery::new().name_contains(name);
+
+	if args.wait {
+		eprintln!("Waiting for {}...", name);
+		return wait_for_process(&query, None).map(|info| info.pid);
+	}
+
+	let own_pid = std::process::id();
+	let matches: Vec<_> = query
+		.run()?
+		.into_iter()
+		.filter(|p| p.pid != own_pid)
+		.collect();
+	match matches.as_slice() {
+		[info] => Ok(info.pid),
+		[] => Err(Error::new(
+			ErrorKind::InvalidPid,
+			format!("no process matches {}", name),
+		)),
+		_ => Err(Error::new(
+			ErrorKind::InvalidPid,
+			format!(
+				"{} processes match {}, pick one with --pid: {}",
+				matches.len(),
+				name,
+				matches
+					.iter()
+					.map(|p| p.pid.to_string())
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+		)),
+	}
+}
+
+fn open(args: &ProcessArgs) -> Result<Process, Error> {
+	Process::open(resolve_pid(args)?)
+}
+
+fn ps(filter: Option<&str>, format: OutputFormat) -> Result<ExitCode, Error> {
+	let query = match filter {
+		Some(name) => ProcessQuery::new().name_contains(name),
+		None => ProcessQuery::new(),
+	};
+	let processes: Vec<_> = list_processes()?
+		.into_iter()
+		.filter(|p| query.matches(p))
+		.collect();
+
+	if format == OutputFormat::Text {
+		println!(
+			"{:>7} {:>7} {:>6}  {:<16} CMDLINE",
+			"PID", "PPID", "UID", "NAME"
+		);
+	}
+	print_records(format, &processes, ProcessRecord::from, |info| {
+		let uid = info
+			.uid
+			.map(|u| u.to_string())
+			.unwrap_or_else(|| "?".to_string());
+		println!(
+			"{:>7} {:>7} {:>6}  {:<16} {}",
+			info.pid,
+			info.ppid,
+			uid,
+			info.full_name(),
+			info.cmdline_string()
+		);
+	});
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn maps(pid: u32, smaps: bool, format: OutputFormat) -> Result<ExitCode, Error> {
+	let maps = if smaps {
+		ProcMemoryMaps::new_with_smaps(pid)?
+	} else {
+		ProcMemoryMaps::new(pid)?
+	};
+
+	print_records(
+		format,
+		maps.get_regions(),
+		RegionRecord::from,
+		|region| match &region.smaps {
+			Some(s) => println!(
+				"{} rss={} dirty={} swap={}",
+				region.to_maps_line(),
+				s.rss / 1024,
+				s.private_dirty / 1024,
+				s.swap / 1024
+			),
+			None => println!("{}", region.to_maps_line()),
+		},
+	);
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn scan(args: ScanArgs, format: OutputFormat) -> Result<ExitCode, Error> {
+	let pattern = args.mode.to_pattern(&args.value).map_err(|e| {
+		Error::new(
+			ErrorKind::BadDataType,
+			format!("{} as {}: {}", args.value, args.mode.name(), e),
+		)
+	})?;
+	let n_bytes = pattern.split_whitespace().count();
+
+	let mut process = open(&args.process)?;
+	eprintln!(
+		"Scanning pid {} with the {} backend for [{}] {}",
+		process.pid(),
+		process.backend().name(),
+		args.mode.name(),
+		pattern
+	);
+
+	let matches = scan_process(
+		&mut process,
+		&pattern,
+		&args.target,
+		args.resident,
+		args.touched,
+	)?;
+
+	let records: Vec<_> = matches
+		.iter()
+		.map(|addr| MatchRecord::read(&mut process, *addr, args.mode, n_bytes))
+		.collect();
+
+	if format == OutputFormat::Text {
+		if records.is_empty() {
+			eprintln!("No matches found.");
+		} else {
+			println!("{} match(es)", records.len());
+		}
+	}
+	print_records(format, &records, |r| r, |r| print_match_text(r, args.mode));
+
+	Ok(if records.is_empty() {
+		ExitCode::from(EXIT_NO_MATCHES)
+	} else {
+		ExitCode::SUCCESS
+	})
+}
+
+/// Scans `target` of `process` for `pattern`, reporting the time taken on stderr.
+pub fn scan_process(
+	process: &mut Process,
+	pattern: &str,
+	target: &TargetArg,
+	resident: bool,
+	touched: bool,
+) -> Result<Vec<u64>, Error> {
+	let inner = target.scan_target();
+	let target = if resident {
+		ScanTarget::Filtered(&inner, RegionFilter::Resident)
+	} else {
+		inner
+	};
+
+	let start = Instant::now();
+	let matches = if touched {
+		let scan = process.scan_touched_for_pattern_in(pattern, target)?;
+		eprintln!(
+			"Inspected {} KiB of {} KiB touched memory",
+			scan.inspected_bytes() / 1024,
+			scan.selected_bytes() / 1024
+		);
+		scan.matches
+	} else {
+		match process.try_scan_for_pattern_in(pattern, target) {
+			Ok(matches) => matches,
+			Err(e) if e.kind() == ErrorKind::PatternNotFound => Vec::new(),
+			Err(e) => return Err(e),
+		}
+	};
+	eprintln!("Search took: {}micros", start.elapsed().as_micros());
+
+	Ok(matches)
+}
+
+fn read(args: ReadArgs) -> Result<ExitCode, Error> {
+	let mut process = open(&args.process)?;
+
+	match args.mode {
+		None | Some(ScanMode::Pattern) => {
+			let mut buf = vec![0u8; args.len];
+			let n = process.read_bytes(args.address, &mut buf)?;
+			if n == 0 && !buf.is_empty() {
+				return Err(Error::new(ErrorKind::ReadFailed, "nothing readable")
+					.with_addr(args.address)
+					.with_size(args.len));
+			}
+			print_hexdump(args.address, &buf[..n]);
+		}
+		Some(mode) => {
+			if let Some(size) = mode.size() {
+				process.get_maps().can_read(args.address, size)?;
+			}
+			let value = read_value_string(&mut process, args.address, mode, args.len);
+			println!(
+				"{:#x}  {}  {}",
+				args.address,
+				describe_addr(&process, args.address),
+				value
+			);
+		}
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn write(args: WriteArgs) -> Result<ExitCode, Error> {
+	let bytes = args.mode.to_bytes(&args.value).map_err(|e| {
+		Error::new(
+			ErrorKind::BadDataType,
+			format!("{} as {}: {}", args.value, args.mode.name(), e),
+		)
+	})?;
+	if bytes.is_empty() {
+		return Err(Error::new(ErrorKind::BadDataType, "nothing to write"));
+	}
+
+	let mut process = open(&args.process)?;
+	process.write_bytes(args.address, &bytes)?;
+	eprintln!("Wrote {} byte(s) at {:#x}", bytes.len(), args.address);
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn table(args: TableArgs, format: OutputFormat) -> Result<ExitCode, Error> {
+	let import = CheatTable::import(&args.file)?;
+	for skipped in &import.skipped {
+		eprintln!("Skipped {}", skipped);
+	}
+	let table = import.table;
+
+	if let Some(output) = &args.output {
+		table.save(output)?;
+		eprintln!(
+			"Wrote {} entries and {} scripts to {}",
+			table.entries.len(),
+			table.scripts.len(),
+			output.display()
+		);
+		return Ok(ExitCode::SUCCESS);
+	}
+
+	let records: Vec<TableEntryRecord> = match args.process.process() {
+		Some(process_args) => {
+			let mut process = open(&process_args)?;
+			let maps = process.get_maps().clone();
+			let addrs = table.resolve_all(&mut process, &maps);
+			table
+				.entries
+				.iter()
+				.zip(addrs)
+				.map(|(entry, addr)| TableEntryRecord::read(&mut process, entry, addr))
+				.collect()
+		}
+		None => table.entries.iter().map(TableEntryRecord::from).collect(),
+	};
+
+	print_records(
+		format,
+		&records,
+		|record| record,
+		|record| {
+			let resolved = match (record.resolved, &record.value, &record.error) {
+				(Some(addr), value, _) => format!(
+					"  = {:#x}  {}",
+					addr,
+					value.as_deref().unwrap_or("?")
+				),
+				(None, _, Some(error)) => format!("  error: {}", error),
+				(None, _, None) => String::new(),
+			};
+			println!(
+				"{:<24} {:<7} {}{}{}",
+				record.description,
+				record.value_type,
+				record.address,
+				if record.frozen { " [frozen]" } else { "" },
+				resolved
+			);
+		},
+	);
+	for script in &table.scripts {
+		eprintln!("Script {} is not run by lime", script.description);
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(any(feature = "ptrace", feature = "offline"))]
+fn dump(args: DumpArgs) -> Result<ExitCode, Error> {
+	let pid = resolve_pid(&args.process)?;
+
+	match args.format {
+		#[cfg(feature = "ptrace")]
+		DumpFormat::Core => {
+			let stats = dump_process_core(pid, &args.output)?;
+			eprintln!(
+				"Wrote {} regions and {} threads, {} KiB ({} KiB zero filled, {} regions skipped) to {}",
+				stats.regions,
+				stats.threads,
+				stats.bytes_written / 1024,
+				stats.bytes_zero_filled / 1024,
+				stats.regions_skipped,
+				args.output.display()
+			);
+		}
+		#[cfg(feature = "offline")]
+		DumpFormat::Lime => {
+			let stats = dump_process_lime(pid, &args.output)?;
+			eprintln!(
+				"Wrote {} ranges, {} KiB ({} KiB zero filled) to {}",
+				stats.ranges,
+				stats.bytes_written / 1024,
+				stats.bytes_zero_filled / 1024,
+				args.output.display()
+			);
+		}
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
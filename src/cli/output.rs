@@ -0,0 +1,131 @@
+use lime::{Process, scan_mode::ScanMode, traits::ReadProcessMemory};
+use serde::Serialize;
+
+use super::records::MatchRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+	Text,
+	/// One JSON array per command.
+	Json,
+	/// One JSON object per line.
+	Ndjson,
+}
+
+/// Prints the record of every item as JSON or NDJSON, or calls `text` for each item.
+pub fn print_records<'a, T, R: Serialize>(
+	format: OutputFormat,
+	items: &'a [T],
+	record: impl Fn(&'a T) -> R,
+	text: impl Fn(&T),
+) {
+	match format {
+		OutputFormat::Text => items.iter().for_each(text),
+		OutputFormat::Json => {
+			let records: Vec<R> = items.iter().map(record).collect();
+			println!(
+				"{}",
+				serde_json::to_string_pretty(&records).expect("records serialize")
+			);
+		}
+		OutputFormat::Ndjson => {
+			for item in items {
+				println!(
+					"{}",
+					serde_json::to_string(&record(item)).expect("records serialize")
+				);
+			}
+		}
+	}
+}
+
+/// `<module> +<offset>` of `addr`, `anon +<offset>` for unnamed regions.
+pub fn describe_addr(process: &Process, addr: u64) -> String {
+	process
+		.get_maps()
+		.find_region_by_addr(addr)
+		.map(|r| {
+			let name = r.pathname.as_deref().unwrap_or("anon");
+			format!("{} +{:#x}", name, addr - r.start)
+		})
+		.unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Address, region, the matched bytes in brackets with some context around them, and the
+/// decoded value.
+pub fn print_match_text(record: &MatchRecord, scan_mode: ScanMode) {
+	let region = match (&record.region, record.region_start) {
+		(_, None) => "unknown".to_string(),
+		(name, Some(start)) => format!(
+			"{} +{:#x}",
+			name.as_deref().unwrap_or("anon"),
+			record.address - start
+		),
+	};
+
+	let match_start = (record.address - record.context_start) as usize;
+	let match_end = match_start + record.bytes.len();
+	let hex = record
+		.context
+		.iter()
+		.enumerate()
+		.map(|(i, b)| {
+			if (match_start..match_end).contains(&i) {
+				format!("[{:02x}]", b)
+			} else {
+				format!("{:02x}", b)
+			}
+		})
+		.collect::<Vec<_>>()
+		.join(" ");
+
+	// Numbers are fully contained in the match, strings may be longer.
+	let value = match scan_mode.size() {
+		Some(_) => scan_mode.format_value(&record.bytes),
+		None => record.value.clone().unwrap_or_default(),
+	};
+
+	if value.is_empty() {
+		println!("  {:#x}  {}  {}", record.address, region, hex);
+	} else {
+		println!(
+			"  {:#x}  {}  {}  \"{}\"",
+			record.address, region, hex, value
+		);
+	}
+}
+
+/// Reads and formats the value at `addr`. Strings are read up to `max_len` bytes.
+pub fn read_value_string(
+	process: &mut Process,
+	addr: u64,
+	scan_mode: ScanMode,
+	max_len: usize,
+) -> String {
+	let mut buf = vec![0u8; scan_mode.size().unwrap_or(max_len)];
+	let read = process.read_bytes(addr, &mut buf).unwrap_or(0);
+	scan_mode.format_value(&buf[..read])
+}
+
+/// Classic 16 bytes per line hex dump with an ASCII column.
+pub fn print_hexdump(addr: u64, bytes: &[u8]) {
+	for (i, line) in bytes.chunks(16).enumerate() {
+		let hex = line
+			.iter()
+			.map(|b| format!("{:02x}", b))
+			.collect::<Vec<_>>()
+			.join(" ");
+		let ascii: String = line
+			.iter()
+			.map(|b| {
+				if b.is_ascii_graphic() || *b == b' ' {
+					*b as char
+				} else {
+					'.'
+				}
+			})
+			.collect();
+
+		println!("{:#014x}  {:<47}  |{}|", addr + i as u64 * 16, hex, ascii);
+	}
+}
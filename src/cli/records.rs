@@ -0,0 +1,208 @@
+//! What the JSON output is made of. Addresses are `0x`-prefixed hex strings and decoded values
+//! are strings, so 64-bit numbers survive JSON parsers that only have doubles.
+
+use lime::{
+	Process, errors::Error, maps::ProcMemoryRegion, process::query::ProcessInfo,
+	scan_mode::ScanMode, table::cheat_table::TableEntry, traits::ReadProcessMemory,
+};
+use serde::{Serialize, Serializer};
+
+const CONTEXT_BEFORE: u64 = 8;
+const CONTEXT_AFTER: u64 = 24;
+/// Longest string decoded for a string match.
+pub const MAX_STRING_LEN: usize = 256;
+
+fn hex<S: Serializer>(v: &u64, s: S) -> Result<S::Ok, S::Error> {
+	s.serialize_str(&format!("{:#x}", v))
+}
+
+fn hex_opt<S: Serializer>(v: &Option<u64>, s: S) -> Result<S::Ok, S::Error> {
+	match v {
+		Some(v) => hex(v, s),
+		None => s.serialize_none(),
+	}
+}
+
+fn hex_bytes<S: Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+	s.serialize_str(&v.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+#[derive(Serialize, Debug)]
+pub struct MatchRecord {
+	#[serde(serialize_with = "hex")]
+	pub address: u64,
+	/// Pathname of the region, `None` for anonymous memory.
+	pub region: Option<String>,
+	#[serde(serialize_with = "hex_opt")]
+	pub region_start: Option<u64>,
+	/// Offset from the probable load base of the module the region belongs to.
+	#[serde(serialize_with = "hex_opt")]
+	pub module_offset: Option<u64>,
+	pub value: Option<String>,
+	#[serde(serialize_with = "hex_bytes")]
+	pub bytes: Vec<u8>,
+	#[serde(serialize_with = "hex")]
+	pub context_start: u64,
+	#[serde(serialize_with = "hex_bytes")]
+	pub context: Vec<u8>,
+}
+
+impl MatchRecord {
+	/// Reads the `n_bytes` long match at `addr` and some bytes around it.
+	pub fn read(process: &mut Process, addr: u64, scan_mode: ScanMode, n_bytes: usize) -> Self {
+		let region = process.get_maps().find_region_by_addr(addr);
+		let region_start = region.map(|r| r.start);
+		let region_end = region.map_or(u64::MAX, |r| r.end);
+		let pathname = region.and_then(|r| r.pathname.clone());
+		let module_offset = pathname
+			.as_deref()
+			.filter(|p| p.starts_with('/'))
+			.and_then(|p| process.get_maps().get_module_probable_load_base(p))
+			.and_then(|base| addr.checked_sub(base));
+
+		let mut bytes = vec![0u8; n_bytes];
+		let read = process.read_bytes(addr, &mut bytes).unwrap_or(0);
+		bytes.truncate(read);
+
+		// The context stays inside the region, so a neighbouring unmapped page does not make
+		// the whole read fail.
+		let context_start = addr
+			.saturating_sub(CONTEXT_BEFORE)
+			.max(region_start.unwrap_or(0));
+		let context_end = addr
+			.saturating_add(n_bytes as u64 + CONTEXT_AFTER)
+			.min(region_end)
+			.max(addr);
+		let mut context = vec![0u8; (context_end - context_start) as usize];
+		let read = process.read_bytes(context_start, &mut context).unwrap_or(0);
+		context.truncate(read);
+
+		let mut value_bytes = vec![0u8; scan_mode.size().unwrap_or(MAX_STRING_LEN)];
+		let read = process.read_bytes(addr, &mut value_bytes).unwrap_or(0);
+		let value = scan_mode.decode_value(&value_bytes[..read]);
+
+		Self {
+			address: addr,
+			region: pathname,
+			region_start,
+			module_offset,
+			value,
+			bytes,
+			context_start,
+			context,
+		}
+	}
+}
+
+#[derive(Serialize, Debug)]
+pub struct RegionRecord {
+	#[serde(serialize_with = "hex")]
+	pub start: u64,
+	#[serde(serialize_with = "hex")]
+	pub end: u64,
+	pub perm: String,
+	#[serde(serialize_with = "hex")]
+	pub offset: u64,
+	pub dev: String,
+	pub inode: u64,
+	pub pathname: Option<String>,
+	/// Sizes in bytes, only with `--smaps`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rss: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub private_dirty: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub swap: Option<u64>,
+}
+
+impl From<&ProcMemoryRegion> for RegionRecord {
+	fn from(r: &ProcMemoryRegion) -> Self {
+		Self {
+			start: r.start,
+			end: r.end,
+			perm: r.perm.clone(),
+			offset: r.offset,
+			dev: r.dev.clone(),
+			inode: r.inode,
+			pathname: r.pathname.clone(),
+			rss: r.smaps.as_ref().map(|s| s.rss),
+			private_dirty: r.smaps.as_ref().map(|s| s.private_dirty),
+			swap: r.smaps.as_ref().map(|s| s.swap),
+		}
+	}
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProcessRecord {
+	pub pid: u32,
+	pub ppid: u32,
+	pub uid: Option<u32>,
+	/// `comm`, truncated by the kernel to 15 bytes.
+	pub comm: String,
+	pub name: String,
+	pub cmdline: Vec<String>,
+	pub exe: Option<String>,
+}
+
+impl From<&ProcessInfo> for ProcessRecord {
+	fn from(info: &ProcessInfo) -> Self {
+		Self {
+			pid: info.pid,
+			ppid: info.ppid,
+			uid: info.uid,
+			comm: info.name.clone(),
+			name: info.full_name(),
+			cmdline: info.cmdline.clone(),
+			exe: info.exe.as_ref().map(|p| p.to_string_lossy().into_owned()),
+		}
+	}
+}
+
+#[derive(Serialize, Debug)]
+pub struct TableEntryRecord {
+	pub description: String,
+	/// The address expression as written in the table.
+	pub address: String,
+	#[serde(rename = "type")]
+	pub value_type: &'static str,
+	pub frozen: bool,
+	/// Address and current value in the process, when one is given.
+	#[serde(serialize_with = "hex_opt", skip_serializing_if = "Option::is_none")]
+	pub resolved: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<String>,
+	/// Why the address could not be resolved.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+impl From<&TableEntry> for TableEntryRecord {
+	fn from(entry: &TableEntry) -> Self {
+		Self {
+			description: entry.description.clone(),
+			address: entry.address.to_string(),
+			value_type: entry.value_type.name(),
+			frozen: entry.frozen,
+			resolved: None,
+			value: None,
+			error: None,
+		}
+	}
+}
+
+impl TableEntryRecord {
+	/// The entry with the value at `addr`, its resolved address.
+	pub fn read(process: &mut Process, entry: &TableEntry, addr: Result<u64, Error>) -> Self {
+		let mut record = Self::from(entry);
+		match addr {
+			Ok(addr) => {
+				let mut bytes = vec![0u8; entry.size()];
+				let read = process.read_bytes(addr, &mut bytes).unwrap_or(0);
+				record.resolved = Some(addr);
+				record.value = entry.value_type.decode_value(&bytes[..read]);
+			}
+			Err(e) => record.error = Some(e.to_string()),
+		}
+		record
+	}
+}
@@ -0,0 +1,74 @@
+use lime::scan_mode::ScanMode;
+use rustyline::{
+	Context, Helper, completion::Completer, highlight::Highlighter, hint::Hinter,
+	validate::Validator,
+};
+
+use super::shell::COMMANDS;
+
+const TARGETS: [&str; 5] = ["heap-stack", "anon", "anon-other", "module:", "range:"];
+const NEXT_FILTERS: [&str; 4] = ["changed", "unchanged", "increased", "decreased"];
+
+/// Completes command names, then types, scan targets and module names.
+#[derive(Default)]
+pub struct ReplHelper {
+	/// File names of the attached process' modules, updated after every command.
+	pub modules: Vec<String>,
+}
+
+impl ReplHelper {
+	fn candidates(&self, first_word: bool, word: &str) -> Vec<String> {
+		if first_word {
+			return matching(COMMANDS.iter().map(|(name, _)| name.to_string()), word);
+		}
+
+		if let Some(module) = word.strip_prefix("module:") {
+			return matching(self.modules.iter().cloned(), module)
+				.into_iter()
+				.map(|m| format!("module:{}", m))
+				.collect();
+		}
+
+		let words = ScanMode::ALL
+			.iter()
+			.map(|m| m.name().to_string())
+			.chain(TARGETS.iter().map(|t| t.to_string()))
+			.chain(NEXT_FILTERS.iter().map(|f| f.to_string()))
+			.chain(self.modules.iter().cloned());
+		matching(words, word)
+	}
+}
+
+fn matching(words: impl Iterator<Item = String>, prefix: &str) -> Vec<String> {
+	let mut words: Vec<String> = words.filter(|w| w.starts_with(prefix)).collect();
+	words.sort();
+	words.dedup();
+	words
+}
+
+impl Completer for ReplHelper {
+	type Candidate = String;
+
+	fn complete(
+		&self,
+		line: &str,
+		pos: usize,
+		_ctx: &Context<'_>,
+	) -> rustyline::Result<(usize, Vec<String>)> {
+		let before = &line[..pos];
+		let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+		let first_word = before[..start].trim().is_empty();
+
+		Ok((start, self.candidates(first_word, &before[start..])))
+	}
+}
+
+impl Hinter for ReplHelper {
+	type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,73 @@
+use std::{
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread::JoinHandle,
+	time::Duration,
+};
+
+use lime::{Backend, Process, errors::Error, traits::WriteProcessMemory};
+
+const FREEZE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Addresses and the bytes kept there.
+type Entries = Arc<Mutex<Vec<(u64, Vec<u8>)>>>;
+
+/// Keeps writing values to a process from a background thread until dropped.
+///
+/// The thread has its own handle, opened with `process_vm` or `procmem`; ptrace would fight
+/// with the session's handle over the tracee.
+pub struct Freezer {
+	entries: Entries,
+	stop: Arc<AtomicBool>,
+	handle: Option<JoinHandle<()>>,
+}
+
+impl Freezer {
+	pub fn start(pid: u32) -> Result<Self, Error> {
+		let mut process = Process::open_with(pid, Backend::ProcVm)
+			.or_else(|_| Process::open_with(pid, Backend::ProcMem))?;
+
+		let entries: Entries = Arc::default();
+		let stop = Arc::new(AtomicBool::new(false));
+
+		let handle = {
+			let entries = entries.clone();
+			let stop = stop.clone();
+			std::thread::spawn(move || {
+				while !stop.load(Ordering::Relaxed) {
+					let mut failed = false;
+					for (addr, bytes) in entries.lock().unwrap().iter() {
+						failed |= process.write_bytes(*addr, bytes).is_err();
+					}
+					// Writes are checked against our copy of the maps, which may be stale.
+					if failed {
+						let _ = process.refresh_maps();
+					}
+					std::thread::sleep(FREEZE_INTERVAL);
+				}
+			})
+		};
+
+		Ok(Self {
+			entries,
+			stop,
+			handle: Some(handle),
+		})
+	}
+
+	/// Replaces everything that is kept frozen.
+	pub fn set(&self, entries: Vec<(u64, Vec<u8>)>) {
+		*self.entries.lock().unwrap() = entries;
+	}
+}
+
+impl Drop for Freezer {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
@@ -0,0 +1,706 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use lime::{
+	Process,
+	errors::{Error, ErrorKind},
+	process::{
+		query::{ProcessInfo, ProcessQuery},
+		watch::ProcessWatcher,
+	},
+	procmem::softdirty::SoftDirtyTracker,
+	scan_mode::ScanMode,
+	table::{
+		address::AddressExpr,
+		cheat_table::{CheatTable, TableEntry, TableScript},
+	},
+	traits::{ReadProcessMemory, WriteProcessMemory},
+};
+
+use super::freeze::Freezer;
+use crate::cli::{
+	args::TargetArg,
+	commands::scan_process,
+	output::describe_addr,
+	records::MAX_STRING_LEN,
+};
+
+pub struct ScanResult {
+	pub addr: u64,
+	/// The bytes seen by the last `scan` or `next`.
+	pub last: Vec<u8>,
+}
+
+/// How `next` narrows down the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NextFilter {
+	Equals(Vec<u8>),
+	Changed,
+	Unchanged,
+	Increased,
+	Decreased,
+}
+
+impl NextFilter {
+	/// The length to read, which is the filter value's for `Equals`.
+	pub fn read_len(&self, last: &[u8]) -> usize {
+		match self {
+			NextFilter::Equals(value) => value.len(),
+			_ => last.len(),
+		}
+	}
+
+	/// Whether a result whose value went from `last` to `current` stays.
+	pub fn keep(&self, mode: ScanMode, current: &[u8], last: &[u8]) -> bool {
+		match self {
+			NextFilter::Equals(value) => current == value.as_slice(),
+			NextFilter::Changed => current != last,
+			NextFilter::Unchanged => current == last,
+			NextFilter::Increased => mode.compare(current, last) == Some(Ordering::Greater),
+			NextFilter::Decreased => mode.compare(current, last) == Some(Ordering::Less),
+		}
+	}
+
+	/// `changed`, `unchanged`, `increased`, `decreased` or a value of type `mode`.
+	pub fn parse(s: &str, mode: ScanMode) -> Result<Self, Error> {
+		Ok(match s {
+			"changed" => NextFilter::Changed,
+			"unchanged" => NextFilter::Unchanged,
+			"increased" => NextFilter::Increased,
+			"decreased" => NextFilter::Decreased,
+			value => NextFilter::Equals(mode.to_bytes(value).map_err(|e| {
+				Error::new(
+					ErrorKind::BadDataType,
+					format!("{} as {}: {}", value, mode.name(), e),
+				)
+			})?),
+		})
+	}
+
+	/// Results with values that cannot be ordered only support the other filters.
+	pub fn check(&self, mode: ScanMode) -> Result<(), Error> {
+		if matches!(self, NextFilter::Increased | NextFilter::Decreased) && mode.size().is_none() {
+			return Err(Error::new(
+				ErrorKind::BadDataType,
+				format!("{} values cannot be ordered", mode.name()),
+			));
+		}
+		Ok(())
+	}
+}
+
+pub struct WatchEntry {
+	pub addr: u64,
+	pub mode: ScanMode,
+	pub label: String,
+	/// The bytes written over and over while frozen.
+	pub frozen: Option<Vec<u8>>,
+	/// The table address `addr` was resolved from, saved instead of `addr`.
+	pub expr: Option<AddressExpr>,
+}
+
+/// What `follow_restart` did.
+pub struct Reattached {
+	pub pid: u32,
+	/// Watches that could not be moved to the new process, with the reason.
+	pub failed: Vec<String>,
+}
+
+/// Notices when the attached program is started again.
+struct Restarts {
+	watcher: ProcessWatcher,
+	/// Pid and start time of the attached process.
+	attached: (u32, u64),
+}
+
+impl Restarts {
+	fn watch(pid: u32) -> Result<Self, Error> {
+		let info = ProcessInfo::from_pid(pid)?;
+		let query = ProcessQuery::new().name_exact(&info.full_name());
+
+		Ok(Self {
+			watcher: ProcessWatcher::ignore_existing(query)?,
+			attached: (info.pid, info.start_time),
+		})
+	}
+}
+
+/// What `undo` reverted.
+pub enum Undone {
+	Write { addr: u64, len: usize },
+	Results(usize),
+}
+
+enum UndoStep {
+	Write {
+		addr: u64,
+		previous: Vec<u8>,
+	},
+	Results {
+		mode: ScanMode,
+		results: Vec<ScanResult>,
+	},
+}
+
+/// Everything the REPL and the TUI keep between commands.
+#[derive(Default)]
+pub struct Session {
+	process: Option<Process>,
+	/// Type of the current results, also the default for `write` and `watch`.
+	mode: Option<ScanMode>,
+	results: Vec<ScanResult>,
+	/// Soft-dirty bits reset when the values in `results` were read, so `next` only reads
+	/// the pages written since. `None` where the kernel or the permissions do not allow it.
+	dirty: Option<SoftDirtyTracker>,
+	watches: Vec<WatchEntry>,
+	undo: Vec<UndoStep>,
+	freezer: Option<Freezer>,
+	/// Scripts of the loaded table, kept to be saved again.
+	scripts: Vec<TableScript>,
+	/// `None` if the program name cannot be read.
+	restarts: Option<Restarts>,
+}
+
+/// The attached process. A free function so the other fields can be borrowed alongside.
+fn attached(process: &mut Option<Process>) -> Result<&mut Process, Error> {
+	process
+		.as_mut()
+		.ok_or_else(|| Error::new(ErrorKind::InvalidPid, "not attached, use attach <pid|name>"))
+}
+
+/// Clears the soft-dirty bits of `pid`, starting the tracker if there is none yet.
+fn reset_dirty(dirty: &mut Option<SoftDirtyTracker>, pid: u32) {
+	if let Some(tracker) = dirty
+		&& tracker.reset().is_ok()
+	{
+		return;
+	}
+	*dirty = SoftDirtyTracker::start(pid).ok();
+}
+
+impl Session {
+	pub fn pid(&self) -> Option<u32> {
+		self.process.as_ref().map(|p| p.pid())
+	}
+
+	/// File names of the mapped modules, for completion.
+	pub fn module_names(&self) -> Vec<String> {
+		let Some(process) = &self.process else {
+			return Vec::new();
+		};
+
+		let mut names: Vec<String> = process
+			.get_maps()
+			.get_regions()
+			.iter()
+			.filter_map(|r| r.pathname.as_deref())
+			.filter(|p| p.starts_with('/'))
+			.filter_map(|p| p.rsplit('/').next())
+			.map(|n| n.to_string())
+			.collect();
+		names.sort();
+		names.dedup();
+		names
+	}
+
+	/// The type of the last scan, used when no type is given.
+	pub fn mode(&self) -> Result<ScanMode, Error> {
+		self.mode.ok_or_else(|| {
+			Error::new(
+				ErrorKind::BadDataType,
+				"no type given and nothing scanned yet",
+			)
+		})
+	}
+
+	/// The attached process.
+	pub fn process(&self) -> Option<&Process> {
+		self.process.as_ref()
+	}
+
+	pub fn results(&self) -> &[ScanResult] {
+		&self.results
+	}
+
+	pub fn watches(&self) -> &[WatchEntry] {
+		&self.watches
+	}
+
+	/// Attaches to `pid`, dropping everything that belonged to the previous process.
+	pub fn attach(&mut self, pid: u32) -> Result<(), Error> {
+		let process = Process::open(pid)?;
+		self.detach();
+		self.process = Some(process);
+		self.restarts = Restarts::watch(pid).ok();
+		Ok(())
+	}
+
+	pub fn detach(&mut self) {
+		self.freezer = None;
+		self.process = None;
+		self.mode = None;
+		self.results.clear();
+		self.dirty = None;
+		self.watches.clear();
+		self.undo.clear();
+		self.scripts.clear();
+		self.restarts = None;
+	}
+
+	/// Moves the session to a new instance of the attached program once the attached process
+	/// has exited, e.g. after a game was restarted. Results are dropped. Watches follow: table
+	/// entries through their address, other addresses inside modules relative to the module,
+	/// anything else stays where it was. Returns `None` while the attached process is running
+	/// or no new instance has started yet.
+	pub fn follow_restart(&mut self) -> Result<Option<Reattached>, Error> {
+		let Some(restarts) = &mut self.restarts else {
+			return Ok(None);
+		};
+		restarts.watcher.poll()?;
+
+		if restarts
+			.watcher
+			.running()
+			.any(|info| (info.pid, info.start_time) == restarts.attached)
+		{
+			return Ok(None);
+		}
+		let Some(next) = restarts
+			.watcher
+			.running()
+			.max_by_key(|info| info.start_time)
+			.cloned()
+		else {
+			return Ok(None);
+		};
+
+		let process = Process::open(next.pid)?;
+
+		// Taken from the maps of the exited process, which are still cached.
+		let exprs: Vec<AddressExpr> = self
+			.watches
+			.iter()
+			.map(|watch| {
+				watch
+					.expr
+					.clone()
+					.unwrap_or_else(|| self.module_expr(watch.addr))
+			})
+			.collect();
+
+		self.freezer = None;
+		self.process = Some(process);
+		self.mode = None;
+		self.results.clear();
+		self.dirty = None;
+		self.undo.clear();
+
+		let process = attached(&mut self.process)?;
+		let maps = process.get_maps().clone();
+		let mut failed = Vec::new();
+		for (index, (watch, expr)) in self.watches.iter_mut().zip(exprs).enumerate() {
+			match expr.resolve(process, &maps) {
+				Ok(addr) => watch.addr = addr,
+				Err(e) => failed.push(format!("watch {}: {}", index, e)),
+			}
+		}
+
+		if self.watches.iter().any(|w| w.frozen.is_some()) {
+			match Freezer::start(next.pid) {
+				Ok(freezer) => {
+					self.freezer = Some(freezer);
+					self.update_freezer();
+				}
+				Err(e) => failed.push(format!("freezing: {}", e)),
+			}
+		}
+
+		if let Some(restarts) = &mut self.restarts {
+			restarts.attached = (next.pid, next.start_time);
+		}
+		Ok(Some(Reattached {
+			pid: next.pid,
+			failed,
+		}))
+	}
+
+	/// `#<n>` for a result, or an address as tables write them: a plain address,
+	/// `<module>[+<offset>]` or a pointer path like `[[libgame.so+0x10]+8]`.
+	pub fn resolve_addr(&mut self, expr: &str) -> Result<u64, Error> {
+		if let Some(index) = expr.strip_prefix('#') {
+			return index
+				.parse::<usize>()
+				.ok()
+				.and_then(|i| self.results.get(i))
+				.map(|r| r.addr)
+				.ok_or_else(|| {
+					Error::new(ErrorKind::InvalidAddress, format!("no result {}", expr))
+				});
+		}
+
+		let expr: AddressExpr = expr.parse()?;
+		if let AddressExpr::Address(addr) = expr {
+			return Ok(addr);
+		}
+		let process = attached(&mut self.process)?;
+		let maps = process.get_maps().clone();
+		expr.resolve(process, &maps)
+	}
+
+	/// Region name and offset of `addr`, as printed next to results.
+	pub fn describe(&self, addr: u64) -> String {
+		match &self.process {
+			Some(process) => describe_addr(process, addr),
+			None => "unknown".to_string(),
+		}
+	}
+
+	/// Up to `len` bytes at `addr`, fewer if the end is not readable.
+	pub fn read_bytes(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, Error> {
+		let mut buf = vec![0u8; len];
+		let n = attached(&mut self.process)?.read_bytes(addr, &mut buf)?;
+		buf.truncate(n);
+		Ok(buf)
+	}
+
+	/// Replaces the results with the matches of `value`, returning how many there are.
+	pub fn scan(
+		&mut self,
+		mode: ScanMode,
+		value: &str,
+		target: &TargetArg,
+		resident: bool,
+		touched: bool,
+	) -> Result<usize, Error> {
+		let pattern = mode.to_pattern(value).map_err(|e| {
+			Error::new(
+				ErrorKind::BadDataType,
+				format!("{} as {}: {}", value, mode.name(), e),
+			)
+		})?;
+		let n_bytes = pattern.split_whitespace().count();
+
+		let process = attached(&mut self.process)?;
+		process.refresh_maps()?;
+		let matches = scan_process(process, &pattern, target, resident, touched)?;
+		reset_dirty(&mut self.dirty, process.pid());
+
+		let results = matches
+			.into_iter()
+			.map(|addr| {
+				let mut last = vec![0u8; n_bytes];
+				let n = process.read_bytes(addr, &mut last).unwrap_or(0);
+				last.truncate(n);
+				ScanResult { addr, last }
+			})
+			.collect();
+
+		self.replace_results(mode, results);
+		Ok(self.results.len())
+	}
+
+	/// Keeps the results whose current value passes `filter`, returning how many are left.
+	pub fn next(&mut self, filter: &NextFilter) -> Result<usize, Error> {
+		let mode = self.mode()?;
+		filter.check(mode)?;
+
+		let process = attached(&mut self.process)?;
+
+		// Values on pages nobody wrote to since the last pass are still the ones in `last`.
+		// The bits are reset before reading, so a write racing with the reads shows up in
+		// the next pass instead of being lost.
+		let dirty = self.dirty.as_ref().and_then(|tracker| {
+			let addresses: Vec<u64> = self.results.iter().map(|r| r.addr).collect();
+			let size = self
+				.results
+				.iter()
+				.map(|r| filter.read_len(&r.last))
+				.max()
+				.unwrap_or(0);
+			tracker
+				.dirty_addresses(&addresses, size)
+				.ok()
+				.map(HashSet::<u64>::from_iter)
+		});
+		reset_dirty(&mut self.dirty, process.pid());
+
+		let mut results = Vec::new();
+		for result in &self.results {
+			let len = filter.read_len(&result.last);
+			let clean = dirty
+				.as_ref()
+				.is_some_and(|dirty| !dirty.contains(&result.addr));
+			let current = if clean && result.last.len() >= len {
+				result.last[..len].to_vec()
+			} else {
+				let mut current = vec![0u8; len];
+				if process.read_bytes(result.addr, &mut current).unwrap_or(0) < len {
+					continue;
+				}
+				current
+			};
+
+			if filter.keep(mode, &current, &result.last) {
+				results.push(ScanResult {
+					addr: result.addr,
+					last: current,
+				});
+			}
+		}
+
+		self.replace_results(mode, results);
+		Ok(self.results.len())
+	}
+
+	fn replace_results(&mut self, mode: ScanMode, results: Vec<ScanResult>) {
+		let previous = std::mem::replace(&mut self.results, results);
+		if let Some(previous_mode) = self.mode.replace(mode) {
+			self.undo.push(UndoStep::Results {
+				mode: previous_mode,
+				results: previous,
+			});
+		}
+	}
+
+	/// Writes `value` and remembers what was there for `undo`, returning the bytes written.
+	pub fn write(
+		&mut self,
+		addr: u64,
+		value: &str,
+		mode: Option<ScanMode>,
+	) -> Result<usize, Error> {
+		let mode = match mode {
+			Some(mode) => mode,
+			None => self.mode()?,
+		};
+		let bytes = mode.to_bytes(value).map_err(|e| {
+			Error::new(
+				ErrorKind::BadDataType,
+				format!("{} as {}: {}", value, mode.name(), e),
+			)
+		})?;
+
+		self.write_bytes(addr, &bytes)?;
+		Ok(bytes.len())
+	}
+
+	/// Writes `bytes` and remembers what was there for `undo`.
+	pub fn write_bytes(&mut self, addr: u64, bytes: &[u8]) -> Result<(), Error> {
+		if bytes.is_empty() {
+			return Err(Error::new(ErrorKind::BadDataType, "nothing to write"));
+		}
+
+		let process = attached(&mut self.process)?;
+		let mut previous = vec![0u8; bytes.len()];
+		if process.read_bytes(addr, &mut previous)? < bytes.len() {
+			return Err(Error::new(ErrorKind::ReadFailed, "not readable")
+				.with_addr(addr)
+				.with_size(bytes.len()));
+		}
+		process.write_bytes(addr, bytes)?;
+
+		self.undo.push(UndoStep::Write { addr, previous });
+		Ok(())
+	}
+
+	/// Reverts the last write or result narrowing.
+	pub fn undo(&mut self) -> Result<Undone, Error> {
+		let step = self
+			.undo
+			.pop()
+			.ok_or_else(|| Error::new(ErrorKind::Unsupported, "nothing to undo"))?;
+
+		match step {
+			UndoStep::Write { addr, previous } => {
+				attached(&mut self.process)?.write_bytes(addr, &previous)?;
+				Ok(Undone::Write {
+					addr,
+					len: previous.len(),
+				})
+			}
+			UndoStep::Results { mode, results } => {
+				// The restored values are older than the last reset of the soft-dirty bits.
+				self.dirty = None;
+				self.mode = Some(mode);
+				self.results = results;
+				Ok(Undone::Results(self.results.len()))
+			}
+		}
+	}
+
+	/// Adds a watch, returning its index.
+	pub fn watch(
+		&mut self,
+		addr: u64,
+		mode: Option<ScanMode>,
+		label: String,
+	) -> Result<usize, Error> {
+		let mode = match mode {
+			Some(mode) => mode,
+			None => self.mode()?,
+		};
+		attached(&mut self.process)?;
+
+		self.watches.push(WatchEntry {
+			addr,
+			mode,
+			label,
+			frozen: None,
+			expr: None,
+		});
+		Ok(self.watches.len() - 1)
+	}
+
+	pub fn unwatch(&mut self, index: usize) -> Result<(), Error> {
+		self.watch_entry(index)?;
+		self.watches.remove(index);
+		self.update_freezer();
+		Ok(())
+	}
+
+	/// The current value of a watch, at most `MAX_STRING_LEN` bytes for strings.
+	pub fn watch_value(&mut self, index: usize) -> Result<Vec<u8>, Error> {
+		let watch = self.watch_entry(index)?;
+		let (addr, len) = (watch.addr, watch.mode.size().unwrap_or(MAX_STRING_LEN));
+		self.read_bytes(addr, len)
+	}
+
+	/// Keeps the watch at `value`, or at its current value.
+	pub fn freeze(&mut self, index: usize, value: Option<&str>) -> Result<(), Error> {
+		let watch = self.watch_entry(index)?;
+		let (addr, mode) = (watch.addr, watch.mode);
+
+		let bytes = match value {
+			Some(value) => mode.to_bytes(value).map_err(|e| {
+				Error::new(
+					ErrorKind::BadDataType,
+					format!("{} as {}: {}", value, mode.name(), e),
+				)
+			})?,
+			None => {
+				let size = mode.size().ok_or_else(|| {
+					Error::new(
+						ErrorKind::BadDataType,
+						format!("give a value to freeze {} watches", mode.name()),
+					)
+				})?;
+				let mut current = vec![0u8; size];
+				if attached(&mut self.process)?.read_bytes(addr, &mut current)? < size {
+					return Err(Error::new(ErrorKind::ReadFailed, "not readable").with_addr(addr));
+				}
+				current
+			}
+		};
+
+		if self.freezer.is_none() {
+			let pid = attached(&mut self.process)?.pid();
+			self.freezer = Some(Freezer::start(pid)?);
+		}
+
+		self.watches[index].frozen = Some(bytes);
+		self.update_freezer();
+		Ok(())
+	}
+
+	pub fn unfreeze(&mut self, index: usize) -> Result<(), Error> {
+		self.watch_entry(index)?;
+		self.watches[index].frozen = None;
+		self.update_freezer();
+		Ok(())
+	}
+
+	/// The watches as a table. Addresses inside modules are saved relative to the module so
+	/// they still work after it is loaded somewhere else.
+	pub fn table(&self) -> CheatTable {
+		let entries = self
+			.watches
+			.iter()
+			.map(|watch| TableEntry {
+				description: watch.label.clone(),
+				address: watch
+					.expr
+					.clone()
+					.unwrap_or_else(|| self.module_expr(watch.addr)),
+				value_type: watch.mode,
+				length: None,
+				frozen: watch.frozen.is_some(),
+				value: watch.frozen.as_deref().map(|bytes| match watch.mode {
+					ScanMode::Pattern => bytes
+						.iter()
+						.map(|b| format!("{:02x}", b))
+						.collect::<Vec<_>>()
+						.join(" "),
+					mode => mode.decode_value(bytes).unwrap_or_default(),
+				}),
+			})
+			.collect();
+
+		CheatTable {
+			entries,
+			scripts: self.scripts.clone(),
+		}
+	}
+
+	/// Adds every entry of `table` as a watch, frozen if the entry is. Entries that cannot be
+	/// resolved or frozen are reported and skipped, the rest is still loaded.
+	pub fn load_table(&mut self, table: CheatTable) -> Result<Vec<String>, Error> {
+		let process = attached(&mut self.process)?;
+		let maps = process.get_maps().clone();
+		let addrs = table.resolve_all(process, &maps);
+
+		let mut failed = Vec::new();
+		for (entry, addr) in table.entries.into_iter().zip(addrs) {
+			let addr = match addr {
+				Ok(addr) => addr,
+				Err(e) => {
+					failed.push(format!("{}: {}", entry.description, e));
+					continue;
+				}
+			};
+
+			let index = self.watch(addr, Some(entry.value_type), entry.description)?;
+			self.watches[index].expr = Some(entry.address);
+			if entry.frozen
+				&& let Err(e) = self.freeze(index, entry.value.as_deref())
+			{
+				failed.push(format!("{}: {}", self.watches[index].label, e));
+			}
+		}
+		self.scripts.extend(table.scripts);
+		Ok(failed)
+	}
+
+	/// `<module>+<offset>` for addresses in a mapped file, the plain address otherwise.
+	fn module_expr(&self, addr: u64) -> AddressExpr {
+		self.process
+			.as_ref()
+			.and_then(|process| {
+				let maps = process.get_maps();
+				let path = maps
+					.find_region_by_addr(addr)?
+					.pathname
+					.as_deref()
+					.filter(|p| p.starts_with('/'))?;
+				let base = maps.get_module_probable_load_base(path)?;
+				let name = path.rsplit('/').next()?;
+				Some(AddressExpr::Module(name.to_string()).offset((addr - base) as i64))
+			})
+			.unwrap_or(AddressExpr::Address(addr))
+	}
+
+	fn watch_entry(&self, index: usize) -> Result<&WatchEntry, Error> {
+		self.watches
+			.get(index)
+			.ok_or_else(|| Error::new(ErrorKind::InvalidAddress, format!("no watch {}", index)))
+	}
+
+	fn update_freezer(&self) {
+		if let Some(freezer) = &self.freezer {
+			freezer.set(
+				self.watches
+					.iter()
+					.filter_map(|w| w.frozen.clone().map(|bytes| (w.addr, bytes)))
+					.collect(),
+			);
+		}
+	}
+}
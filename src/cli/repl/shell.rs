@@ -0,0 +1,480 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use lime::{
+	errors::{Error, ErrorKind},
+	scan_mode::ScanMode,
+	table::cheat_table::CheatTable,
+};
+use rustyline::{Editor, error::ReadlineError, history::DefaultHistory};
+
+use super::{
+	complete::ReplHelper,
+	session::{NextFilter, Session, Undone},
+};
+use crate::cli::{
+	args::{ProcessArgs, TargetArg, parse_scan_mode},
+	commands::resolve_pid,
+	output::print_hexdump,
+};
+
+/// Results listed by `list` when no count is given.
+const DEFAULT_LIST_COUNT: usize = 20;
+/// Bytes shown by `read` without a type or length.
+const DEFAULT_READ_LEN: usize = 64;
+const HISTORY_FILE: &str = ".lime_history";
+
+/// Every command with its usage, for `help` and completion.
+pub const COMMANDS: [(&str, &str); 18] = [
+	("attach", "attach <pid|name>"),
+	("detach", "detach"),
+	(
+		"scan",
+		"scan <type> <value> [target] [--resident] [--touched]",
+	),
+	("next", "next <value|changed|unchanged|increased|decreased>"),
+	("list", "list [count]"),
+	("read", "read <addr> [type|len]"),
+	("write", "write <addr> <value> [type]"),
+	("watch", "watch [<addr> [type] [label]]"),
+	("unwatch", "unwatch <n>"),
+	("freeze", "freeze <watch> [value]"),
+	("unfreeze", "unfreeze <watch>"),
+	("undo", "undo"),
+	("save", "save <file.toml|file.json>"),
+	("load", "load <file.toml|file.json|file.ct>"),
+	("history", "history [count]"),
+	("help", "help"),
+	("quit", "quit"),
+	("exit", "exit"),
+];
+
+const HELP_FOOTER: &str = "\
+Addresses are numbers, #<n> for the n-th result, <module>[+<offset>] or pointer paths like
+[[<module>+<offset>]+<offset>].
+Targets are heap-stack, anon, anon-other, module:<name>, range:<start>-<end> or stack:<tid>.
+Tables saved with save keep the watches, load adds the entries of a table or Cheat Engine .ct file.
+When the attached program is restarted, the session attaches to the new process and moves the watches.";
+
+fn history_path() -> Option<PathBuf> {
+	std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Runs the REPL until `quit` or end of input, attached to `process` if given.
+pub fn run(process: Option<&ProcessArgs>) -> Result<ExitCode, Error> {
+	let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new().map_err(|e| {
+		Error::new(
+			ErrorKind::Io,
+			format!("cannot start the line editor: {}", e),
+		)
+	})?;
+	editor.set_helper(Some(ReplHelper::default()));
+
+	let history = history_path();
+	if let Some(path) = &history {
+		let _ = editor.load_history(path);
+	}
+
+	let mut session = Session::default();
+	if let Some(args) = process {
+		attach(&mut session, resolve_pid(args)?)?;
+	}
+
+	loop {
+		if let Some(helper) = editor.helper_mut() {
+			helper.modules = session.module_names();
+		}
+
+		let prompt = match session.pid() {
+			Some(pid) => format!("lime({})> ", pid),
+			None => "lime> ".to_string(),
+		};
+		let line = match editor.readline(&prompt) {
+			Ok(line) => line,
+			Err(ReadlineError::Interrupted) => continue,
+			Err(ReadlineError::Eof) => break,
+			Err(e) => return Err(Error::new(ErrorKind::Io, e.to_string())),
+		};
+
+		let line = line.trim();
+		if line.is_empty() {
+			continue;
+		}
+		let _ = editor.add_history_entry(line);
+		follow_restart(&mut session);
+
+		let args = match split_args(line) {
+			Ok(args) => args,
+			Err(e) => {
+				eprintln!("error: {}", e);
+				continue;
+			}
+		};
+
+		match args[0].as_str() {
+			"quit" | "exit" => break,
+			"history" => {
+				let count = args.get(1).and_then(|c| c.parse().ok()).unwrap_or(20);
+				let entries: Vec<_> = editor.history().iter().collect();
+				for (i, entry) in entries
+					.iter()
+					.enumerate()
+					.skip(entries.len().saturating_sub(count))
+				{
+					println!("{:>5}  {}", i, entry);
+				}
+			}
+			_ => {
+				if let Err(e) = dispatch(&mut session, &args) {
+					eprintln!("error: {}", e);
+				}
+			}
+		}
+	}
+
+	if let Some(path) = &history {
+		let _ = editor.save_history(path);
+	}
+	Ok(ExitCode::SUCCESS)
+}
+
+fn usage(command: &str) -> Error {
+	let usage = COMMANDS
+		.iter()
+		.find(|(name, _)| *name == command)
+		.map_or(command, |(_, usage)| usage);
+	Error::new(ErrorKind::Parse, format!("usage: {}", usage))
+}
+
+fn parse_index(arg: Option<&String>, command: &str) -> Result<usize, Error> {
+	arg.and_then(|a| a.parse().ok())
+		.ok_or_else(|| usage(command))
+}
+
+fn parse_mode(arg: &str) -> Result<ScanMode, Error> {
+	parse_scan_mode(arg).map_err(|e| Error::new(ErrorKind::BadDataType, format!("{}: {}", arg, e)))
+}
+
+fn dispatch(session: &mut Session, args: &[String]) -> Result<(), Error> {
+	let command = args[0].as_str();
+	let arg = |i: usize| args.get(i).map(String::as_str);
+
+	match command {
+		"help" => {
+			for (_, usage) in COMMANDS {
+				println!("  {}", usage);
+			}
+			println!("{}", HELP_FOOTER);
+		}
+		"attach" => {
+			let target = arg(1).ok_or_else(|| usage(command))?;
+			let pid = match target.parse::<u32>() {
+				Ok(pid) => pid,
+				Err(_) => resolve_pid(&ProcessArgs {
+					pid: None,
+					name: Some(target.to_string()),
+					wait: false,
+				})?,
+			};
+			attach(session, pid)?;
+		}
+		"detach" => session.detach(),
+		"scan" => {
+			let scan = ScanCommand::parse(&args[1..])?;
+			let count = session.scan(
+				scan.mode,
+				&scan.value,
+				&scan.target,
+				scan.resident,
+				scan.touched,
+			)?;
+			println!("{} result(s)", count);
+		}
+		"next" => {
+			let filter = NextFilter::parse(arg(1).ok_or_else(|| usage(command))?, session.mode()?)?;
+			println!("{} result(s)", session.next(&filter)?);
+		}
+		"list" => {
+			let count = match arg(1) {
+				Some(c) => Some(c.parse().map_err(|_| usage(command))?),
+				None => None,
+			};
+			list(session, count.unwrap_or(DEFAULT_LIST_COUNT))?;
+		}
+		"read" => {
+			let addr = session.resolve_addr(arg(1).ok_or_else(|| usage(command))?)?;
+			match arg(2) {
+				None => read(session, addr, None, DEFAULT_READ_LEN)?,
+				Some(len) if len.parse::<usize>().is_ok() => {
+					read(session, addr, None, len.parse().unwrap())?
+				}
+				Some(mode) => read(session, addr, Some(parse_mode(mode)?), DEFAULT_READ_LEN)?,
+			}
+		}
+		"write" => {
+			let (Some(addr), Some(value)) = (arg(1), arg(2)) else {
+				return Err(usage(command));
+			};
+			let addr = session.resolve_addr(addr)?;
+			let mode = arg(3).map(parse_mode).transpose()?;
+			let len = session.write(addr, value, mode)?;
+			println!("Wrote {} byte(s) at {:#x}", len, addr);
+		}
+		"watch" => {
+			let Some(addr) = arg(1) else {
+				return show_watches(session);
+			};
+			let addr = session.resolve_addr(addr)?;
+
+			let (mode, label_start) = match arg(2).map(ScanMode::from_name) {
+				Some(Some(mode)) => (Some(mode), 3),
+				_ => (None, 2),
+			};
+			let label = args.get(label_start..).unwrap_or_default().join(" ");
+			println!("Watch {} added", session.watch(addr, mode, label)?);
+		}
+		"unwatch" => session.unwatch(parse_index(args.get(1), command)?)?,
+		"freeze" => session.freeze(parse_index(args.get(1), command)?, arg(2))?,
+		"unfreeze" => session.unfreeze(parse_index(args.get(1), command)?)?,
+		"undo" => match session.undo()? {
+			Undone::Write { addr, len } => println!("Restored {} byte(s) at {:#x}", len, addr),
+			Undone::Results(count) => println!("{} result(s)", count),
+		},
+		"save" => {
+			let path = arg(1).ok_or_else(|| usage(command))?;
+			let table = session.table();
+			table.save(path)?;
+			println!("Saved {} entries to {}", table.entries.len(), path);
+		}
+		"load" => load(session, arg(1).ok_or_else(|| usage(command))?)?,
+		_ => {
+			return Err(Error::new(
+				ErrorKind::Parse,
+				format!("unknown command {}, try help", command),
+			));
+		}
+	}
+
+	Ok(())
+}
+
+/// The arguments of `scan`, shared with the TUI's scan prompt.
+pub struct ScanCommand {
+	pub mode: ScanMode,
+	pub value: String,
+	pub target: TargetArg,
+	pub resident: bool,
+	pub touched: bool,
+}
+
+impl ScanCommand {
+	/// Parses `<type> <value> [target] [--resident] [--touched]`.
+	pub fn parse(args: &[String]) -> Result<Self, Error> {
+		let [mode, value, options @ ..] = args else {
+			return Err(usage("scan"));
+		};
+
+		let mut scan = ScanCommand {
+			mode: parse_mode(mode)?,
+			value: value.to_string(),
+			target: TargetArg::HeapAndStack,
+			resident: false,
+			touched: false,
+		};
+		for option in options {
+			match option.as_str() {
+				"--resident" => scan.resident = true,
+				"--touched" => scan.touched = true,
+				spec => {
+					scan.target = spec
+						.parse()
+						.map_err(|e: String| Error::new(ErrorKind::Parse, e))?;
+				}
+			}
+		}
+		Ok(scan)
+	}
+}
+
+fn attach(session: &mut Session, pid: u32) -> Result<(), Error> {
+	session.attach(pid)?;
+	if let Some(process) = session.process() {
+		println!(
+			"Attached to {} with the {} backend",
+			pid,
+			process.backend().name()
+		);
+	}
+	Ok(())
+}
+
+/// Moves to a restarted instance of the attached program before running the next command.
+fn follow_restart(session: &mut Session) {
+	match session.follow_restart() {
+		Ok(Some(reattached)) => {
+			println!("Process restarted, attached to {}", reattached.pid);
+			for failure in reattached.failed {
+				eprintln!("error: {}", failure);
+			}
+		}
+		Ok(None) => {}
+		Err(e) => eprintln!("error: {}", e),
+	}
+}
+
+/// Prints the first `count` results with their current value, and the old one if it changed.
+fn list(session: &mut Session, count: usize) -> Result<(), Error> {
+	let mode = session.mode()?;
+	let shown: Vec<(u64, Vec<u8>)> = session
+		.results()
+		.iter()
+		.take(count)
+		.map(|r| (r.addr, r.last.clone()))
+		.collect();
+
+	for (i, (addr, last)) in shown.iter().enumerate() {
+		let current = session.read_bytes(*addr, last.len()).unwrap_or_default();
+		let now = mode.format_value(&current);
+		let then = mode.format_value(last);
+
+		let value = if now == then {
+			now
+		} else {
+			format!("{} (was {})", now, then)
+		};
+		println!(
+			"#{:<4} {:#x}  {}  {}",
+			i,
+			addr,
+			session.describe(*addr),
+			value
+		);
+	}
+
+	let total = session.results().len();
+	if total > count {
+		println!("... {} more", total - count);
+	}
+	Ok(())
+}
+
+/// Prints the value at `addr` as `mode`, or a hex dump of `len` bytes.
+fn read(session: &mut Session, addr: u64, mode: Option<ScanMode>, len: usize) -> Result<(), Error> {
+	match mode {
+		None | Some(ScanMode::Pattern) => {
+			let bytes = session.read_bytes(addr, len)?;
+			if bytes.is_empty() && len > 0 {
+				return Err(Error::new(ErrorKind::ReadFailed, "nothing readable").with_addr(addr));
+			}
+			print_hexdump(addr, &bytes);
+		}
+		Some(mode) => {
+			let bytes = session.read_bytes(addr, mode.size().unwrap_or(len))?;
+			if bytes.len() < mode.size().unwrap_or(1) {
+				return Err(Error::new(ErrorKind::ReadFailed, "not readable").with_addr(addr));
+			}
+			println!(
+				"{:#x}  {}  {}",
+				addr,
+				session.describe(addr),
+				mode.format_value(&bytes)
+			);
+		}
+	}
+	Ok(())
+}
+
+/// Adds the entries of a table as watches, reporting the ones that could not be added.
+fn load(session: &mut Session, path: &str) -> Result<(), Error> {
+	let import = CheatTable::import(path)?;
+	let (entries, scripts) = (import.table.entries.len(), import.table.scripts.len());
+	for skipped in &import.skipped {
+		eprintln!("skipped {}", skipped);
+	}
+
+	let before = session.watches().len();
+	for failure in session.load_table(import.table)? {
+		eprintln!("error: {}", failure);
+	}
+	println!(
+		"Added {} of {} entries as watches",
+		session.watches().len() - before,
+		entries
+	);
+	if scripts > 0 {
+		println!("{} script(s) kept with the table, lime does not run them", scripts);
+	}
+	Ok(())
+}
+
+fn show_watches(session: &mut Session) -> Result<(), Error> {
+	for i in 0..session.watches().len() {
+		let value = session.watch_value(i).unwrap_or_default();
+		let watch = &session.watches()[i];
+		println!(
+			"{:<3} {:#x}  {}  {:<7} {}{}  {}",
+			i,
+			watch.addr,
+			session.describe(watch.addr),
+			watch.mode.name(),
+			watch.mode.format_value(&value),
+			if watch.frozen.is_some() {
+				" [frozen]"
+			} else {
+				""
+			},
+			watch.label
+		);
+	}
+	Ok(())
+}
+
+/// Splits on whitespace, keeping double quoted parts together so strings and patterns with
+/// spaces can be given as one argument.
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+	let mut args = Vec::new();
+	let mut current = String::new();
+	let mut in_arg = false;
+	let mut quoted = false;
+
+	for c in line.chars() {
+		match c {
+			'"' => {
+				quoted = !quoted;
+				in_arg = true;
+			}
+			c if c.is_whitespace() && !quoted => {
+				if in_arg {
+					args.push(std::mem::take(&mut current));
+					in_arg = false;
+				}
+			}
+			c => {
+				current.push(c);
+				in_arg = true;
+			}
+		}
+	}
+
+	if quoted {
+		return Err("unterminated quote".to_string());
+	}
+	if in_arg {
+		args.push(current);
+	}
+	Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn split_args_keeps_quoted_parts() {
+		assert_eq!(
+			split_args(r#"scan pattern "48 8B ?? 05"  anon"#).unwrap(),
+			["scan", "pattern", "48 8B ?? 05", "anon"]
+		);
+		assert_eq!(split_args(r#"write #0 """#).unwrap(), ["write", "#0", ""]);
+		assert!(split_args(r#"scan string "open"#).is_err());
+	}
+}
@@ -0,0 +1,534 @@
+use std::{
+	ops::Range,
+	process::ExitCode,
+	time::{Duration, Instant},
+};
+
+use lime::{
+	errors::{Error, ErrorKind},
+	process::query::{ProcessInfo, ProcessQuery, list_processes},
+	scan_mode::ScanMode,
+};
+use ratatui::{
+	DefaultTerminal,
+	crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+};
+
+use super::{
+	hex::{self, HexView},
+	ui,
+};
+use crate::cli::{
+	args::ProcessArgs,
+	commands::resolve_pid,
+	repl::{
+		session::{NextFilter, Session, Undone},
+		shell::{ScanCommand, split_args},
+	},
+};
+
+/// How often values are read again while no key is pressed.
+const TICK: Duration = Duration::from_millis(250);
+const PROCESS_REFRESH: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+	Processes,
+	Results,
+	Watches,
+	Memory,
+}
+
+impl Pane {
+	const ORDER: [Pane; 4] = [Pane::Processes, Pane::Results, Pane::Watches, Pane::Memory];
+
+	fn cycle(self, forward: bool) -> Self {
+		let i = Self::ORDER.iter().position(|p| *p == self).unwrap();
+		let n = Self::ORDER.len();
+		Self::ORDER[if forward {
+			(i + 1) % n
+		} else {
+			(i + n - 1) % n
+		}]
+	}
+}
+
+/// What the line at the bottom is asking for.
+#[derive(Debug, Clone, Copy)]
+pub enum Prompt {
+	Scan,
+	Next,
+	Write { addr: u64, mode: ScanMode },
+	WriteTyped { addr: u64 },
+	Goto,
+	Filter,
+}
+
+impl Prompt {
+	pub fn label(&self) -> &'static str {
+		match self {
+			Prompt::Scan => "scan <type> <value> [target] [--resident] [--touched]",
+			Prompt::Next => "next <value|changed|unchanged|increased|decreased>",
+			Prompt::Write { .. } => "write",
+			Prompt::WriteTyped { .. } => "write <type> <value>",
+			Prompt::Goto => "go to <addr|#n|module+offset>",
+			Prompt::Filter => "filter processes",
+		}
+	}
+}
+
+/// Selection in a table, and the first row shown so the selection stays visible.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+	pub selected: usize,
+	pub offset: usize,
+}
+
+impl Cursor {
+	fn up(&mut self, n: usize) {
+		self.selected = self.selected.saturating_sub(n);
+	}
+
+	fn down(&mut self, n: usize, len: usize) {
+		self.selected = (self.selected + n).min(len.saturating_sub(1));
+	}
+
+	/// Rows to draw in `height` lines out of `len`, scrolled as little as needed to show the
+	/// selection.
+	pub fn window(&mut self, height: usize, len: usize) -> Range<usize> {
+		self.selected = self.selected.min(len.saturating_sub(1));
+		if self.selected < self.offset {
+			self.offset = self.selected;
+		} else if height > 0 && self.selected >= self.offset + height {
+			self.offset = self.selected + 1 - height;
+		}
+		self.offset = self.offset.min(len.saturating_sub(height));
+
+		self.offset..(self.offset + height).min(len)
+	}
+}
+
+/// Everything on screen. Scanning, watches and freezing are the REPL's session.
+pub struct App {
+	pub session: Session,
+	pub focus: Pane,
+	pub processes: Vec<ProcessInfo>,
+	pub process_filter: String,
+	pub process_cursor: Cursor,
+	pub result_cursor: Cursor,
+	pub watch_cursor: Cursor,
+	pub hex: HexView,
+	/// Rows of the memory pane at the last draw, for paging.
+	pub memory_rows: usize,
+	pub prompt: Option<(Prompt, String)>,
+	pub status: String,
+	processes_updated: Option<Instant>,
+	quit: bool,
+}
+
+/// Runs the TUI until `q`, attached to `process` if given, with `focus` selected and the memory
+/// pane at `addr`.
+pub fn run(
+	process: Option<&ProcessArgs>,
+	focus: Pane,
+	addr: Option<&str>,
+) -> Result<ExitCode, Error> {
+	let mut app = App::new();
+	if let Some(args) = process {
+		app.attach(resolve_pid(args)?)?;
+	}
+	if let Some(addr) = addr {
+		app.hex.goto(app.session.resolve_addr(addr)?);
+	}
+	app.focus = focus;
+
+	let mut terminal = ratatui::init();
+	let result = app.run(&mut terminal);
+	ratatui::restore();
+
+	result.map(|()| ExitCode::SUCCESS)
+}
+
+impl App {
+	fn new() -> Self {
+		Self {
+			session: Session::default(),
+			focus: Pane::Processes,
+			processes: Vec::new(),
+			process_filter: String::new(),
+			process_cursor: Cursor::default(),
+			result_cursor: Cursor::default(),
+			watch_cursor: Cursor::default(),
+			hex: HexView::default(),
+			memory_rows: 0,
+			prompt: None,
+			status: "Tab switches panes, Enter attaches, s scans, ? shows all keys".to_string(),
+			processes_updated: None,
+			quit: false,
+		}
+	}
+
+	fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Error> {
+		while !self.quit {
+			if self
+				.processes_updated
+				.is_none_or(|t| t.elapsed() >= PROCESS_REFRESH)
+			{
+				self.refresh_processes();
+				self.follow_restart();
+			}
+
+			terminal.draw(|frame| ui::draw(frame, self))?;
+
+			if event::poll(TICK)?
+				&& let Event::Key(key) = event::read()?
+				&& key.kind == KeyEventKind::Press
+			{
+				self.handle_key(key);
+			}
+		}
+		Ok(())
+	}
+
+	fn refresh_processes(&mut self) {
+		let query = ProcessQuery::new().name_contains(&self.process_filter);
+		let own_pid = std::process::id();
+
+		match list_processes() {
+			Ok(processes) => {
+				self.processes = processes
+					.into_iter()
+					.filter(|p| p.pid != own_pid && query.matches(p))
+					.collect();
+			}
+			Err(e) => self.status = format!("error: {}", e),
+		}
+		self.processes_updated = Some(Instant::now());
+	}
+
+	fn follow_restart(&mut self) {
+		match self.session.follow_restart() {
+			Ok(Some(reattached)) => {
+				self.result_cursor = Cursor::default();
+				self.status = match reattached.failed.first() {
+					Some(failure) => format!(
+						"Process restarted, attached to {}, error: {}",
+						reattached.pid, failure
+					),
+					None => format!("Process restarted, attached to {}", reattached.pid),
+				};
+			}
+			Ok(None) => {}
+			Err(e) => self.status = format!("error: {}", e),
+		}
+	}
+
+	fn attach(&mut self, pid: u32) -> Result<(), Error> {
+		self.session.attach(pid)?;
+		self.result_cursor = Cursor::default();
+		self.watch_cursor = Cursor::default();
+		self.hex = HexView::default();
+		if let Some(process) = self.session.process() {
+			if let Some(region) = process
+				.get_maps()
+				.get_regions()
+				.iter()
+				.find(|r| r.is_readable())
+			{
+				self.hex.goto(region.start);
+			}
+			self.status = format!(
+				"Attached to {} with the {} backend",
+				pid,
+				process.backend().name()
+			);
+		}
+		Ok(())
+	}
+
+	fn handle_key(&mut self, key: KeyEvent) {
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			self.quit = true;
+			return;
+		}
+
+		let result = if self.prompt.is_some() {
+			self.handle_prompt_key(key)
+		} else if self.focus == Pane::Memory && self.hex.editing {
+			self.handle_edit_key(key)
+		} else {
+			self.handle_command_key(key)
+		};
+		if let Err(e) = result {
+			self.status = format!("error: {}", e);
+		}
+	}
+
+	fn handle_prompt_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+		let Some((prompt, input)) = &mut self.prompt else {
+			return Ok(());
+		};
+
+		match key.code {
+			KeyCode::Esc => self.prompt = None,
+			KeyCode::Backspace => {
+				input.pop();
+			}
+			KeyCode::Char(c) => input.push(c),
+			KeyCode::Enter => {
+				let (prompt, input) = (*prompt, std::mem::take(input));
+				self.prompt = None;
+				self.submit(prompt, input.trim())?;
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	fn submit(&mut self, prompt: Prompt, input: &str) -> Result<(), Error> {
+		match prompt {
+			Prompt::Scan => {
+				let args = split_args(input).map_err(|e| Error::new(ErrorKind::Parse, e))?;
+				let scan = ScanCommand::parse(&args)?;
+				let count = self.session.scan(
+					scan.mode,
+					&scan.value,
+					&scan.target,
+					scan.resident,
+					scan.touched,
+				)?;
+				self.result_cursor = Cursor::default();
+				self.focus = Pane::Results;
+				self.status = format!("{} result(s)", count);
+			}
+			Prompt::Next => {
+				let filter = NextFilter::parse(input, self.session.mode()?)?;
+				let count = self.session.next(&filter)?;
+				self.result_cursor = Cursor::default();
+				self.status = format!("{} result(s)", count);
+			}
+			Prompt::Write { addr, mode } => {
+				let len = self.session.write(addr, input, Some(mode))?;
+				self.status = format!("Wrote {} byte(s) at {:#x}", len, addr);
+			}
+			Prompt::WriteTyped { addr } => {
+				let (mode, value) = input.split_once(' ').unwrap_or((input, ""));
+				let mode = ScanMode::from_name(mode).ok_or_else(|| {
+					Error::new(ErrorKind::BadDataType, format!("unknown type {}", mode))
+				})?;
+				let len = self.session.write(addr, value.trim(), Some(mode))?;
+				self.status = format!("Wrote {} byte(s) at {:#x}", len, addr);
+			}
+			Prompt::Goto => {
+				let addr = self.session.resolve_addr(input)?;
+				self.show_memory(addr);
+			}
+			Prompt::Filter => {
+				self.process_filter = input.to_string();
+				self.process_cursor = Cursor::default();
+				self.refresh_processes();
+			}
+		}
+		Ok(())
+	}
+
+	fn show_memory(&mut self, addr: u64) {
+		self.hex.goto(addr);
+		self.focus = Pane::Memory;
+	}
+
+	fn open_prompt(&mut self, prompt: Prompt, input: String) {
+		self.prompt = Some((prompt, input));
+	}
+
+	/// Address and type of the selected result or watch.
+	fn selected_value(&self) -> Option<(u64, ScanMode)> {
+		match self.focus {
+			Pane::Results => {
+				let result = self.session.results().get(self.result_cursor.selected)?;
+				Some((result.addr, self.session.mode().ok()?))
+			}
+			Pane::Watches => {
+				let watch = self.session.watches().get(self.watch_cursor.selected)?;
+				Some((watch.addr, watch.mode))
+			}
+			_ => None,
+		}
+	}
+
+	fn handle_command_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+		let page = self.memory_rows.max(1);
+		match key.code {
+			KeyCode::Char('q') => self.quit = true,
+			KeyCode::Tab => self.focus = self.focus.cycle(true),
+			KeyCode::BackTab => self.focus = self.focus.cycle(false),
+			KeyCode::Char('?') => {
+				self.status = "s scan  n next  u undo  g go to  / filter  w write  a watch  \
+					f freeze  d unwatch  m memory  e edit  [ ] regions  Enter attach  q quit"
+					.to_string();
+			}
+			KeyCode::Char('s') => self.open_prompt(Prompt::Scan, String::new()),
+			KeyCode::Char('n') => self.open_prompt(Prompt::Next, String::new()),
+			KeyCode::Char('g') => self.open_prompt(Prompt::Goto, String::new()),
+			KeyCode::Char('/') => {
+				self.focus = Pane::Processes;
+				self.open_prompt(Prompt::Filter, self.process_filter.clone());
+			}
+			KeyCode::Char('u') => {
+				self.status = match self.session.undo()? {
+					Undone::Write { addr, len } => {
+						format!("Restored {} byte(s) at {:#x}", len, addr)
+					}
+					Undone::Results(count) => format!("{} result(s)", count),
+				};
+			}
+			KeyCode::Char('w') if self.focus == Pane::Memory => {
+				let addr = self.hex.cursor;
+				self.open_prompt(Prompt::WriteTyped { addr }, String::new());
+			}
+			KeyCode::Char('w') => {
+				if let Some((addr, mode)) = self.selected_value() {
+					self.open_prompt(Prompt::Write { addr, mode }, String::new());
+				}
+			}
+			KeyCode::Char('m') => {
+				if let Some((addr, _)) = self.selected_value() {
+					self.show_memory(addr);
+				}
+			}
+			KeyCode::Up => self.move_cursor(|c, _| c.up(1), -(hex::ROW_LEN as i64)),
+			KeyCode::Down => self.move_cursor(|c, len| c.down(1, len), hex::ROW_LEN as i64),
+			KeyCode::PageUp => {
+				self.move_cursor(|c, _| c.up(page), -(page as i64 * hex::ROW_LEN as i64))
+			}
+			KeyCode::PageDown => self.move_cursor(
+				|c, len| c.down(page, len),
+				page as i64 * hex::ROW_LEN as i64,
+			),
+			_ => return self.handle_pane_key(key),
+		}
+		Ok(())
+	}
+
+	/// Moves the selection of a table, or the memory cursor by `memory` bytes.
+	fn move_cursor(&mut self, cursor: impl Fn(&mut Cursor, usize), memory: i64) {
+		match self.focus {
+			Pane::Processes => cursor(&mut self.process_cursor, self.processes.len()),
+			Pane::Results => cursor(&mut self.result_cursor, self.session.results().len()),
+			Pane::Watches => cursor(&mut self.watch_cursor, self.session.watches().len()),
+			Pane::Memory => self.hex.move_by(memory),
+		}
+	}
+
+	fn handle_pane_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+		match (self.focus, key.code) {
+			(Pane::Processes, KeyCode::Enter) => {
+				if let Some(pid) = self
+					.processes
+					.get(self.process_cursor.selected)
+					.map(|p| p.pid)
+				{
+					self.attach(pid)?;
+				}
+			}
+			(Pane::Results, KeyCode::Enter | KeyCode::Char('a')) => {
+				if let Some((addr, mode)) = self.selected_value() {
+					let index = self.session.watch(addr, Some(mode), String::new())?;
+					self.status = format!("Watch {} added", index);
+				}
+			}
+			(Pane::Watches, KeyCode::Char('f') | KeyCode::Char(' ')) => {
+				let index = self.watch_cursor.selected;
+				match self.session.watches().get(index) {
+					Some(watch) if watch.frozen.is_some() => self.session.unfreeze(index)?,
+					Some(_) => self.session.freeze(index, None)?,
+					None => {}
+				}
+			}
+			(Pane::Watches, KeyCode::Char('d') | KeyCode::Delete) => {
+				self.session.unwatch(self.watch_cursor.selected)?;
+			}
+			(Pane::Watches, KeyCode::Enter) => {
+				if let Some((addr, _)) = self.selected_value() {
+					self.show_memory(addr);
+				}
+			}
+			(Pane::Memory, KeyCode::Left) => self.hex.move_by(-1),
+			(Pane::Memory, KeyCode::Right) => self.hex.move_by(1),
+			(Pane::Memory, KeyCode::Home) => self.hex.goto(hex::row_start(self.hex.cursor)),
+			(Pane::Memory, KeyCode::End) => self
+				.hex
+				.goto(hex::row_start(self.hex.cursor) + hex::ROW_LEN - 1),
+			(Pane::Memory, KeyCode::Char('[')) => {
+				if let Some(process) = self.session.process() {
+					self.hex.previous_region(process.get_maps().get_regions());
+				}
+			}
+			(Pane::Memory, KeyCode::Char(']')) => {
+				if let Some(process) = self.session.process() {
+					self.hex.next_region(process.get_maps().get_regions());
+				}
+			}
+			(Pane::Memory, KeyCode::Char('e') | KeyCode::Enter) => {
+				self.hex.editing = true;
+				self.status = "Editing: type hex digits, Esc to stop, u to undo".to_string();
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	/// Overwrites bytes at the memory cursor, one hex digit at a time.
+	fn handle_edit_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+		match key.code {
+			KeyCode::Esc | KeyCode::Enter => {
+				self.hex.editing = false;
+				self.hex.pending = None;
+				self.status.clear();
+			}
+			KeyCode::Left | KeyCode::Backspace => self.hex.move_by(-1),
+			KeyCode::Right => self.hex.move_by(1),
+			KeyCode::Up => self.hex.move_by(-(hex::ROW_LEN as i64)),
+			KeyCode::Down => self.hex.move_by(hex::ROW_LEN as i64),
+			KeyCode::Char('u') => return self.handle_command_key(key),
+			KeyCode::Char(c) => {
+				let Some(digit) = c.to_digit(16) else {
+					return Ok(());
+				};
+				if let Some(byte) = self.hex.type_nibble(digit as u8) {
+					let addr = self.hex.cursor;
+					self.session.write_bytes(addr, &[byte])?;
+					self.status = format!("Wrote {:02x} at {:#x}", byte, addr);
+					self.hex.move_by(1);
+				}
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cursor_window_follows_the_selection() {
+		let mut cursor = Cursor::default();
+		assert_eq!(cursor.window(5, 3), 0..3);
+
+		cursor.down(7, 20);
+		assert_eq!(cursor.window(5, 20), 3..8);
+		cursor.up(1);
+		assert_eq!(cursor.window(5, 20), 3..8);
+		cursor.up(5);
+		assert_eq!(cursor.window(5, 20), 1..6);
+
+		cursor.down(100, 20);
+		assert_eq!(cursor.selected, 19);
+		assert_eq!(cursor.window(5, 20), 15..20);
+		// The list shrank, e.g. after next.
+		assert_eq!(cursor.window(5, 2), 0..2);
+		assert_eq!(cursor.selected, 1);
+	}
+}
@@ -0,0 +1,355 @@
+use lime::{maps::ProcMemoryRegion, scan_mode::ScanMode};
+use ratatui::{
+	Frame,
+	layout::{Constraint, Layout, Rect},
+	style::{Color, Modifier, Style},
+	text::{Line, Span},
+	widgets::{Block, Borders, Paragraph},
+};
+
+use super::app::App;
+use crate::cli::records::MAX_STRING_LEN;
+
+pub const ROW_LEN: u64 = 16;
+/// Characters of a decoded string shown next to the dump.
+const STRING_PREVIEW: usize = 24;
+
+const CURSOR: Style = Style::new().add_modifier(Modifier::REVERSED);
+const EDITING: Style = Style::new().fg(Color::Black).bg(Color::Yellow);
+const BOUNDARY: Style = Style::new().fg(Color::DarkGray);
+
+pub fn row_start(addr: u64) -> u64 {
+	addr - addr % ROW_LEN
+}
+
+/// A line of the dump: the start of a region, or of an unmapped gap, or 16 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexLine {
+	Boundary(Option<usize>),
+	Row(u64),
+}
+
+fn region_index(regions: &[ProcMemoryRegion], addr: u64) -> Option<usize> {
+	regions.iter().position(|r| r.contains(addr))
+}
+
+/// The `height` lines shown from `top`, with a boundary line wherever the region changes.
+///
+/// Regions are page aligned, so a row never spans two of them.
+pub fn layout(regions: &[ProcMemoryRegion], top: u64, height: usize) -> Vec<HexLine> {
+	let mut lines = Vec::with_capacity(height);
+	let mut previous = None;
+	let mut addr = top;
+
+	while lines.len() < height {
+		let region = region_index(regions, addr);
+		let starts_region = region.is_some_and(|i| regions[i].start == addr);
+		if (previous.is_some() && previous != Some(region)) || starts_region {
+			lines.push(HexLine::Boundary(region));
+			if lines.len() == height {
+				break;
+			}
+		}
+		lines.push(HexLine::Row(addr));
+		previous = Some(region);
+
+		let Some(next) = addr.checked_add(ROW_LEN) else {
+			break;
+		};
+		addr = next;
+	}
+	lines
+}
+
+/// Cursor, scroll position and edit state of the memory pane.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HexView {
+	/// Address of the first row shown.
+	pub top: u64,
+	pub cursor: u64,
+	/// Typed keys overwrite bytes instead of moving around.
+	pub editing: bool,
+	/// High nibble typed for the byte under the cursor.
+	pub pending: Option<u8>,
+}
+
+impl HexView {
+	pub fn goto(&mut self, addr: u64) {
+		self.cursor = addr;
+		self.top = row_start(addr);
+		self.pending = None;
+	}
+
+	pub fn move_by(&mut self, delta: i64) {
+		self.cursor = self.cursor.saturating_add_signed(delta);
+		self.pending = None;
+	}
+
+	/// Moves to the start of the region after the cursor.
+	pub fn next_region(&mut self, regions: &[ProcMemoryRegion]) {
+		if let Some(region) = regions.iter().find(|r| r.start > self.cursor) {
+			self.goto(region.start);
+		}
+	}
+
+	/// Moves to the start of the cursor's region, or of the one before if already there.
+	pub fn previous_region(&mut self, regions: &[ProcMemoryRegion]) {
+		if let Some(region) = regions.iter().rev().find(|r| r.start < self.cursor) {
+			self.goto(region.start);
+		}
+	}
+
+	/// Scrolls as little as needed for the cursor's row to be among the lines shown.
+	pub fn scroll_into_view(
+		&mut self,
+		regions: &[ProcMemoryRegion],
+		height: usize,
+	) -> Vec<HexLine> {
+		let row = row_start(self.cursor);
+		if row < self.top {
+			self.top = row;
+		}
+
+		loop {
+			let lines = layout(regions, self.top, height);
+			let last = lines.iter().rev().find_map(|l| match l {
+				HexLine::Row(addr) => Some(*addr),
+				HexLine::Boundary(_) => None,
+			});
+			match last {
+				// Boundary lines scrolling out make room, so step a row at a time unless far off.
+				Some(last) if row > last => {
+					let behind = row - last;
+					self.top += if behind / ROW_LEN > height as u64 {
+						behind
+					} else {
+						ROW_LEN
+					};
+				}
+				_ => return lines,
+			}
+		}
+	}
+
+	/// Takes a typed hex digit, returning the byte once both nibbles are in.
+	pub fn type_nibble(&mut self, digit: u8) -> Option<u8> {
+		match self.pending.take() {
+			Some(high) => Some(high << 4 | digit),
+			None => {
+				self.pending = Some(digit);
+				None
+			}
+		}
+	}
+}
+
+fn boundary_text(regions: &[ProcMemoryRegion], region: Option<usize>) -> String {
+	match region {
+		Some(i) => {
+			let r = &regions[i];
+			format!(
+				"── {:#x}-{:#x} {} {} ",
+				r.start,
+				r.end,
+				r.perm,
+				r.pathname.as_deref().unwrap_or("anon")
+			)
+		}
+		None => "── unmapped ".to_string(),
+	}
+}
+
+fn row_line(view: &HexView, addr: u64, bytes: &[u8]) -> Line<'static> {
+	let cursor_style = if view.editing { EDITING } else { CURSOR };
+	let mut spans = vec![Span::raw(format!("{:016x}  ", addr))];
+	let mut ascii = Vec::new();
+
+	for i in 0..ROW_LEN {
+		let at_cursor = addr + i == view.cursor;
+		let text = match bytes.get(i as usize) {
+			Some(b) if at_cursor && view.pending.is_some() => {
+				format!("{:x}{:x}", view.pending.unwrap(), b & 0xf)
+			}
+			Some(b) => format!("{:02x}", b),
+			None => "??".to_string(),
+		};
+		spans.push(if at_cursor {
+			Span::styled(text, cursor_style)
+		} else {
+			Span::raw(text)
+		});
+		spans.push(Span::raw(if i == 7 { "  " } else { " " }));
+
+		let c = match bytes.get(i as usize) {
+			Some(b) if b.is_ascii_graphic() || *b == b' ' => (*b as char).to_string(),
+			Some(_) => ".".to_string(),
+			None => " ".to_string(),
+		};
+		ascii.push(if at_cursor {
+			Span::styled(c, cursor_style)
+		} else {
+			Span::raw(c)
+		});
+	}
+
+	spans.push(Span::raw("|"));
+	spans.extend(ascii);
+	spans.push(Span::raw("|"));
+	Line::from(spans)
+}
+
+/// The bytes at the cursor as every type.
+fn decode_lines(app: &mut App) -> Vec<Line<'static>> {
+	let cursor = app.hex.cursor;
+	let bytes = app
+		.session
+		.read_bytes(cursor, MAX_STRING_LEN)
+		.unwrap_or_default();
+
+	let mut lines = vec![Line::raw(format!("{:#x}", cursor))];
+	lines.push(Line::raw(app.session.describe(cursor)));
+	if let Some(region) = app
+		.session
+		.process()
+		.and_then(|p| p.get_maps().find_region_by_addr(cursor))
+	{
+		lines.push(Line::raw(format!(
+			"{} ({:#x} bytes)",
+			region.perm,
+			region.size()
+		)));
+	}
+	lines.push(Line::raw(""));
+
+	if bytes.is_empty() {
+		lines.push(Line::raw("not readable"));
+		return lines;
+	}
+
+	for mode in ScanMode::ALL {
+		if matches!(mode, ScanMode::Pattern) {
+			continue;
+		}
+		let mut value = mode.format_value(&bytes);
+		if matches!(mode, ScanMode::String) {
+			value = format!(
+				"{:?}",
+				value.chars().take(STRING_PREVIEW).collect::<String>()
+			);
+		}
+		lines.push(Line::raw(format!("{:<7}{}", mode.name(), value)));
+	}
+	lines
+}
+
+pub fn draw(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
+	let inner = block.inner(area);
+	frame.render_widget(block, area);
+	let [dump, decode] =
+		Layout::horizontal([Constraint::Min(0), Constraint::Length(36)]).areas(inner);
+
+	let Some(regions) = app
+		.session
+		.process()
+		.map(|p| p.get_maps().get_regions().to_vec())
+	else {
+		return;
+	};
+
+	let lines = app.hex.scroll_into_view(&regions, dump.height as usize);
+	app.memory_rows = lines
+		.iter()
+		.filter(|l| matches!(l, HexLine::Row(_)))
+		.count();
+
+	let text: Vec<Line> = lines
+		.into_iter()
+		.map(|line| match line {
+			HexLine::Boundary(region) => Line::styled(boundary_text(&regions, region), BOUNDARY),
+			HexLine::Row(addr) => {
+				let bytes = app
+					.session
+					.read_bytes(addr, ROW_LEN as usize)
+					.unwrap_or_default();
+				row_line(&app.hex, addr, &bytes)
+			}
+		})
+		.collect();
+	frame.render_widget(Paragraph::new(text), dump);
+
+	let decoded = decode_lines(app);
+	frame.render_widget(
+		Paragraph::new(decoded).block(Block::new().borders(Borders::LEFT)),
+		decode,
+	);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn region(start: u64, end: u64) -> ProcMemoryRegion {
+		ProcMemoryRegion {
+			start,
+			end,
+			perm: "rw-p".to_string(),
+			offset: 0,
+			dev: "00:00".to_string(),
+			inode: 0,
+			pathname: None,
+			smaps: None,
+		}
+	}
+
+	#[test]
+	fn layout_marks_region_boundaries() {
+		let regions = [region(0x1000, 0x1020), region(0x1030, 0x1040)];
+
+		assert_eq!(
+			layout(&regions, 0x1000, 7),
+			[
+				HexLine::Boundary(Some(0)),
+				HexLine::Row(0x1000),
+				HexLine::Row(0x1010),
+				HexLine::Boundary(None),
+				HexLine::Row(0x1020),
+				HexLine::Boundary(Some(1)),
+				HexLine::Row(0x1030),
+			]
+		);
+		// No boundary above the first row unless a region starts there.
+		assert_eq!(layout(&regions, 0x1010, 1), [HexLine::Row(0x1010)]);
+	}
+
+	#[test]
+	fn scrolling_keeps_the_cursor_visible() {
+		let regions = [region(0x1000, 0x2000)];
+		let mut view = HexView::default();
+		view.goto(0x1000);
+
+		view.move_by(5 * ROW_LEN as i64 + 3);
+		let lines = view.scroll_into_view(&regions, 4);
+		assert_eq!(view.top, 0x1020);
+		assert_eq!(lines.last(), Some(&HexLine::Row(0x1050)));
+
+		view.move_by(-(5 * ROW_LEN as i64));
+		view.scroll_into_view(&regions, 4);
+		assert_eq!(view.top, 0x1000);
+	}
+
+	#[test]
+	fn region_jumps_and_nibbles() {
+		let regions = [region(0x1000, 0x2000), region(0x5000, 0x6000)];
+		let mut view = HexView::default();
+		view.goto(0x1800);
+
+		view.next_region(&regions);
+		assert_eq!(view.cursor, 0x5000);
+		view.previous_region(&regions);
+		assert_eq!(view.cursor, 0x1000);
+
+		assert_eq!(view.type_nibble(0xa), None);
+		assert_eq!(view.type_nibble(0x5), Some(0xa5));
+		assert_eq!(view.pending, None);
+	}
+}
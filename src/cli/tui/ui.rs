@@ -0,0 +1,231 @@
+use ratatui::{
+	Frame,
+	layout::{Constraint, Layout, Rect},
+	style::{Color, Modifier, Style},
+	text::{Line, Span},
+	widgets::{Block, Cell, Paragraph, Row, Table, TableState},
+};
+
+use super::{
+	app::{App, Cursor, Pane},
+	hex,
+};
+
+const FOCUSED: Style = Style::new().fg(Color::Cyan);
+const SELECTED: Style = Style::new().add_modifier(Modifier::REVERSED);
+const HEADER: Style = Style::new().add_modifier(Modifier::BOLD);
+const CHANGED: Style = Style::new().fg(Color::Yellow);
+const FROZEN: Style = Style::new().fg(Color::LightBlue);
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+	let [main, status] =
+		Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+	// The memory pane gets the full width while it is browsed.
+	let processes_width = if app.focus == Pane::Memory { 0 } else { 32 };
+	let [processes, right] =
+		Layout::horizontal([Constraint::Length(processes_width), Constraint::Min(0)]).areas(main);
+	let [results, watches, memory] = Layout::vertical([
+		Constraint::Percentage(40),
+		Constraint::Percentage(25),
+		Constraint::Min(0),
+	])
+	.areas(right);
+
+	if processes_width > 0 {
+		draw_processes(frame, app, processes);
+	}
+	draw_results(frame, app, results);
+	draw_watches(frame, app, watches);
+	draw_memory(frame, app, memory);
+	draw_status(frame, app, status);
+}
+
+fn block(app: &App, pane: Pane, title: String) -> Block<'static> {
+	let block = Block::bordered().title(title);
+	if app.focus == pane {
+		block.border_style(FOCUSED)
+	} else {
+		block
+	}
+}
+
+/// A table of the rows in the cursor's window, with the selected one highlighted.
+fn draw_table(
+	frame: &mut Frame,
+	area: Rect,
+	block: Block,
+	header: Row,
+	widths: &[Constraint],
+	rows: Vec<Row>,
+	cursor: Cursor,
+) {
+	let mut state = TableState::default();
+	if !rows.is_empty() {
+		state.select(Some(cursor.selected - cursor.offset));
+	}
+	let table = Table::new(rows, widths)
+		.header(header.style(HEADER))
+		.block(block)
+		.row_highlight_style(SELECTED);
+	frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// Lines available for rows inside a bordered table with a header.
+fn table_height(area: Rect) -> usize {
+	area.height.saturating_sub(3) as usize
+}
+
+fn draw_processes(frame: &mut Frame, app: &mut App, area: Rect) {
+	let attached = app.session.pid();
+	let window = app
+		.process_cursor
+		.window(table_height(area), app.processes.len());
+
+	let rows = app.processes[window]
+		.iter()
+		.map(|p| {
+			let row = Row::new([p.pid.to_string(), p.name.clone()]);
+			if Some(p.pid) == attached {
+				row.style(HEADER)
+			} else {
+				row
+			}
+		})
+		.collect();
+
+	let title = if app.process_filter.is_empty() {
+		format!("Processes ({})", app.processes.len())
+	} else {
+		format!(
+			"Processes /{} ({})",
+			app.process_filter,
+			app.processes.len()
+		)
+	};
+	draw_table(
+		frame,
+		area,
+		block(app, Pane::Processes, title),
+		Row::new(["PID", "NAME"]),
+		&[Constraint::Length(8), Constraint::Min(0)],
+		rows,
+		app.process_cursor,
+	);
+}
+
+fn draw_results(frame: &mut Frame, app: &mut App, area: Rect) {
+	let mode = app.session.mode().ok();
+	let window = app
+		.result_cursor
+		.window(table_height(area), app.session.results().len());
+
+	let mut rows = Vec::new();
+	if let Some(mode) = mode {
+		for i in window {
+			let (addr, last) = {
+				let result = &app.session.results()[i];
+				(result.addr, result.last.clone())
+			};
+			let current = app.session.read_bytes(addr, last.len()).unwrap_or_default();
+			let value = Cell::from(mode.format_value(&current));
+
+			rows.push(Row::new([
+				Cell::from(i.to_string()),
+				Cell::from(format!("{:#x}", addr)),
+				Cell::from(app.session.describe(addr)),
+				if current == last {
+					value
+				} else {
+					value.style(CHANGED)
+				},
+				Cell::from(mode.format_value(&last)),
+			]));
+		}
+	}
+
+	let title = match mode {
+		Some(mode) => format!("Results ({}, {})", app.session.results().len(), mode.name()),
+		None => "Results".to_string(),
+	};
+	draw_table(
+		frame,
+		area,
+		block(app, Pane::Results, title),
+		Row::new(["#", "ADDRESS", "REGION", "VALUE", "PREVIOUS"]),
+		&[
+			Constraint::Length(6),
+			Constraint::Length(16),
+			Constraint::Fill(1),
+			Constraint::Fill(1),
+			Constraint::Fill(1),
+		],
+		rows,
+		app.result_cursor,
+	);
+}
+
+fn draw_watches(frame: &mut Frame, app: &mut App, area: Rect) {
+	let window = app
+		.watch_cursor
+		.window(table_height(area), app.session.watches().len());
+
+	let mut rows = Vec::new();
+	for i in window {
+		let current = app.session.watch_value(i).unwrap_or_default();
+		let watch = &app.session.watches()[i];
+		let frozen = watch.frozen.is_some();
+
+		let row = Row::new([
+			watch.label.clone(),
+			format!("{:#x}", watch.addr),
+			watch.mode.name().to_string(),
+			watch.mode.format_value(&current),
+			if frozen { "frozen" } else { "" }.to_string(),
+		]);
+		rows.push(if frozen { row.style(FROZEN) } else { row });
+	}
+
+	draw_table(
+		frame,
+		area,
+		block(
+			app,
+			Pane::Watches,
+			format!("Watches ({})", app.session.watches().len()),
+		),
+		Row::new(["LABEL", "ADDRESS", "TYPE", "VALUE", ""]),
+		&[
+			Constraint::Fill(1),
+			Constraint::Length(16),
+			Constraint::Length(8),
+			Constraint::Fill(2),
+			Constraint::Length(6),
+		],
+		rows,
+		app.watch_cursor,
+	);
+}
+
+fn draw_memory(frame: &mut Frame, app: &mut App, area: Rect) {
+	let cursor = app.hex.cursor;
+	let title = format!(
+		"Memory {:#x} {}{}",
+		cursor,
+		app.session.describe(cursor),
+		if app.hex.editing { " [editing]" } else { "" }
+	);
+	let block = block(app, Pane::Memory, title);
+	hex::draw(frame, app, area, block);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+	let line = match &app.prompt {
+		Some((prompt, input)) => Line::from(vec![
+			Span::styled(format!("{}: ", prompt.label()), HEADER),
+			Span::raw(input.as_str()),
+			Span::styled(" ", SELECTED),
+		]),
+		None => Line::raw(app.status.as_str()),
+	};
+	frame.render_widget(Paragraph::new(line), area);
+}
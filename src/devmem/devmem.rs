@@ -0,0 +1,98 @@
+use std::{
+	fs::{File, OpenOptions},
+	path::{Path, PathBuf},
+};
+
+use crate::errors::{Error, ErrorKind};
+
+/// Physical memory through `/dev/mem`. Addresses are physical, there are no maps, and what
+/// can be read depends on the kernel: with `CONFIG_STRICT_DEVMEM` only I/O memory and the
+/// first megabyte are accessible, even as root.
+pub struct DevMem {
+	path: PathBuf,
+	file: File,
+	writable: bool,
+}
+
+impl DevMem {
+	/// Opens `/dev/mem`. Without `write` the file is opened read-only and reopened for
+	/// writing by the first write.
+	pub fn new(write: bool) -> Result<Self, Error> {
+		Self::open("/dev/mem", write)
+	}
+
+	/// Opens another file with the same layout as `/dev/mem`, e.g. `/dev/kmem` or a raw
+	/// physical memory dump.
+	pub fn open(path: impl AsRef<Path>, write: bool) -> Result<Self, Error> {
+		let path = path.as_ref().to_path_buf();
+		let kind = if write {
+			ErrorKind::WriteFailed
+		} else {
+			ErrorKind::ReadFailed
+		};
+		let file = open_mem(&path, write)
+			.map_err(|e| Error::os(kind, format!("{}", path.display()), e))?;
+
+		Ok(Self {
+			path,
+			file,
+			writable: write,
+		})
+	}
+
+	pub(super) fn file(&self) -> &File {
+		&self.file
+	}
+
+	/// Reopens the file for writing if it was opened read-only.
+	pub fn make_writable(&mut self) -> Result<(), Error> {
+		if self.writable {
+			return Ok(());
+		}
+
+		self.file = open_mem(&self.path, true).map_err(|e| {
+			Error::os(
+				ErrorKind::WriteFailed,
+				format!("{} for writing", self.path.display()),
+				e,
+			)
+		})?;
+		self.writable = true;
+		Ok(())
+	}
+}
+
+fn open_mem(path: &Path, write: bool) -> std::io::Result<File> {
+	OpenOptions::new().read(true).write(write).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::traits::{ReadProcessMemory, WriteProcessMemory};
+
+	#[test]
+	fn reads_and_writes_at_physical_offsets() {
+		let path = std::env::temp_dir().join(format!("lime-devmem-{}", std::process::id()));
+		let mut image = vec![0u8; 0x2000];
+		image[0x1000..0x1008].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+		std::fs::write(&path, &image).unwrap();
+
+		let mut mem = DevMem::open(&path, false).unwrap();
+		assert_eq!(
+			mem.read_value::<u64>(0x1000).unwrap(),
+			0x1122_3344_5566_7788
+		);
+
+		mem.write_value::<u32>(0x1ffc, &0xdead_beef).unwrap();
+		let mut buf = [0u8; 8];
+		assert_eq!(mem.read_bytes(0x1ffc, &mut buf).unwrap(), 4);
+		assert_eq!(&buf[..4], &0xdead_beefu32.to_le_bytes());
+
+		let err = mem.read_value::<u64>(0x1ffc).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::ReadFailed);
+		assert_eq!(err.addr(), Some(0x1ffc));
+
+		std::fs::remove_file(&path).unwrap();
+	}
+}
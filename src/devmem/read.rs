@@ -1 +1,28 @@
+use std::os::unix::fs::FileExt;
 
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::ReadProcessMemory,
+};
+
+use super::devmem::DevMem;
+
+impl ReadProcessMemory for DevMem {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		let mut value = T::zeroed();
+		self.file()
+			.read_exact_at(value.as_bytes_mut(), addr)
+			.map_err(|e| {
+				Error::os(ErrorKind::ReadFailed, "physical memory", e)
+					.with_addr(addr)
+					.with_size(size_of::<T>())
+			})?;
+
+		Ok(value)
+	}
+
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		Ok(self.file().read_at(buf, addr).unwrap_or(0))
+	}
+}
@@ -1 +1,25 @@
+use std::os::unix::fs::FileExt;
 
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::WriteProcessMemory,
+};
+
+use super::devmem::DevMem;
+
+impl WriteProcessMemory for DevMem {
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
+		self.write_bytes(addr, value.as_bytes())
+	}
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		self.make_writable()?;
+
+		self.file().write_all_at(buf, addr).map_err(|e| {
+			Error::os(ErrorKind::WriteFailed, "physical memory", e)
+				.with_addr(addr)
+				.with_size(buf.len())
+		})
+	}
+}
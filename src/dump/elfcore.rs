@@ -0,0 +1,415 @@
+use std::{
+	fs::File,
+	io::{BufWriter, Seek, SeekFrom, Write},
+	path::Path,
+};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::elf::{
+		ELF64_EHDR_SIZE, ELF64_PHDR_SIZE, EM_CURRENT, ET_CORE, ElfHeader, LIME_NOTE_NAME, NT_AUXV,
+		NT_FILE, NT_LIME_MAPS, NT_PRPSINFO, NT_PRSTATUS, PF_R, PF_W, PF_X, PT_LOAD, PT_NOTE,
+		ProgramHeader, push_note,
+	},
+	maps::{ProcMemoryMaps, ProcMemoryRegion},
+	process::{query::ProcessInfo, regs::ThreadRegisters, stat::ProcStat},
+	procmem::{pagemap::page_size, procmem::ProcMem},
+	ptrace::regs::ThreadGroupStop,
+	traits::ReadProcessMemory,
+};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Summary of a written core file.
+#[derive(Debug, Clone, Default)]
+pub struct CoreDumpStats {
+	pub regions: usize,
+	pub threads: usize,
+	/// Memory bytes stored in the file.
+	pub bytes_written: u64,
+	/// Bytes of readable regions that failed to read and were stored as zeroes.
+	pub bytes_zero_filled: u64,
+	/// Regions that are not readable at all and only have their extent recorded.
+	pub regions_skipped: usize,
+}
+
+/// Stops every thread of `pid`, writes an ELF core file of it to `path` and lets it continue.
+///
+/// If the threads cannot be stopped (e.g. no ptrace permission) the memory is still dumped,
+/// without `NT_PRSTATUS` notes.
+pub fn dump_process_core(pid: u32, path: impl AsRef<Path>) -> Result<CoreDumpStats, Error> {
+	let stop = ThreadGroupStop::stop(pid).ok();
+	let threads = stop.as_ref().map(|s| s.registers()).unwrap_or_default();
+
+	let mut procmem = ProcMem::new(pid, false)?;
+	let maps = procmem.get_maps().clone();
+
+	let file = File::create(path.as_ref()).map_err(|e| {
+		Error::os(
+			ErrorKind::WriteFailed,
+			format!("{}", path.as_ref().display()),
+			e,
+		)
+	})?;
+	let mut out = BufWriter::new(file);
+
+	let stats = write_core(&mut procmem, pid, &maps, &threads, &mut out)?;
+	out.flush().map_err(|e| {
+		Error::os(
+			ErrorKind::WriteFailed,
+			format!("{}", path.as_ref().display()),
+			e,
+		)
+	})?;
+
+	drop(stop);
+	Ok(stats)
+}
+
+/// Writes an ELF core file with one `PT_LOAD` per region of `maps` and `NT_PRSTATUS`,
+/// `NT_PRPSINFO`, `NT_AUXV` and `NT_FILE` notes, plus an `NT_LIME_MAPS` note with the maps
+/// themselves for `offline::elfcore::CoreFile`.
+///
+/// Process metadata is read from `/proc/<pid>` on a best effort basis. Regions without read
+/// permission get a `PT_LOAD` with a file size of 0; chunks of readable regions that fail to
+/// read are zero filled.
+pub fn write_core<R: ReadProcessMemory, W: Write + Seek>(
+	reader: &mut R,
+	pid: u32,
+	maps: &ProcMemoryMaps,
+	threads: &[ThreadRegisters],
+	out: &mut W,
+) -> Result<CoreDumpStats, Error> {
+	let regions = maps.get_regions();
+	let phnum = regions.len() + 1;
+	if phnum >= 0xffff {
+		return Err(Error::new(
+			ErrorKind::Unsupported,
+			format!(
+				"{} regions exceed the ELF program header limit",
+				regions.len()
+			),
+		));
+	}
+
+	let page = page_size();
+	let notes = build_notes(pid, maps, threads, page);
+	let notes_offset = (ELF64_EHDR_SIZE + phnum * ELF64_PHDR_SIZE) as u64;
+	let mut offset = (notes_offset + notes.len() as u64).next_multiple_of(page);
+
+	let mut stats = CoreDumpStats {
+		regions: regions.len(),
+		threads: threads.len(),
+		..Default::default()
+	};
+
+	let mut phdrs = vec![ProgramHeader {
+		p_type: PT_NOTE,
+		p_offset: notes_offset,
+		p_filesz: notes.len() as u64,
+		p_align: 4,
+		..Default::default()
+	}];
+
+	for region in regions {
+		let filesz = if region.is_readable() {
+			seek(out, offset)?;
+			let zero_filled = copy_region(reader, region, out)?;
+			stats.bytes_zero_filled += zero_filled;
+			region.size()
+		} else {
+			stats.regions_skipped += 1;
+			0
+		};
+
+		phdrs.push(ProgramHeader {
+			p_type: PT_LOAD,
+			p_flags: segment_flags(region),
+			p_offset: offset,
+			p_vaddr: region.start,
+			p_paddr: 0,
+			p_filesz: filesz,
+			p_memsz: region.size(),
+			p_align: page,
+		});
+
+		stats.bytes_written += filesz;
+		offset += filesz;
+	}
+
+	let header = ElfHeader {
+		e_type: ET_CORE,
+		e_machine: EM_CURRENT,
+		e_entry: 0,
+		e_phoff: ELF64_EHDR_SIZE as u64,
+		e_shoff: 0,
+		e_phentsize: ELF64_PHDR_SIZE as u16,
+		e_phnum: phnum as u16,
+	};
+
+	seek(out, 0)?;
+	write(out, &header.to_bytes())?;
+	for phdr in &phdrs {
+		write(out, &phdr.to_bytes())?;
+	}
+	write(out, &notes)?;
+
+	seek(out, offset)?;
+	Ok(stats)
+}
+
+fn copy_region<R: ReadProcessMemory, W: Write>(
+	reader: &mut R,
+	region: &ProcMemoryRegion,
+	out: &mut W,
+) -> Result<u64, Error> {
+	let mut buf = vec![0u8; CHUNK_SIZE];
+	let mut zero_filled = 0;
+	let mut current = region.start;
+
+	while current < region.end {
+		let len = CHUNK_SIZE.min((region.end - current) as usize);
+		let chunk = &mut buf[..len];
+		let n = reader.read_bytes(current, chunk).unwrap_or(0);
+		if n < len {
+			chunk[n..].fill(0);
+			zero_filled += (len - n) as u64;
+		}
+
+		write(out, chunk)?;
+		current += len as u64;
+	}
+
+	Ok(zero_filled)
+}
+
+fn segment_flags(region: &ProcMemoryRegion) -> u32 {
+	let mut flags = 0;
+	if region.is_readable() {
+		flags |= PF_R;
+	}
+	if region.is_writeable() {
+		flags |= PF_W;
+	}
+	if region.is_executable() {
+		flags |= PF_X;
+	}
+	flags
+}
+
+fn build_notes(pid: u32, maps: &ProcMemoryMaps, threads: &[ThreadRegisters], page: u64) -> Vec<u8> {
+	let mut notes = Vec::new();
+
+	for regs in threads {
+		push_note(&mut notes, "CORE", NT_PRSTATUS, &prstatus(pid, regs));
+	}
+
+	if let Ok(info) = ProcessInfo::from_pid(pid) {
+		push_note(&mut notes, "CORE", NT_PRPSINFO, &prpsinfo(&info));
+	}
+
+	if let Ok(auxv) = std::fs::read(format!("/proc/{}/auxv", pid)) {
+		push_note(&mut notes, "CORE", NT_AUXV, &auxv);
+	}
+
+	push_note(&mut notes, "CORE", NT_FILE, &file_note(maps, page));
+	push_note(
+		&mut notes,
+		LIME_NOTE_NAME,
+		NT_LIME_MAPS,
+		maps.to_maps_string().as_bytes(),
+	);
+	notes
+}
+
+/// `struct elf_prstatus`: signal info, pids, times, then `pr_reg` and `pr_fpvalid`.
+fn prstatus(pid: u32, regs: &ThreadRegisters) -> Vec<u8> {
+	let stat = ProcStat::read(&format!("/proc/{}/task/{}/stat", pid, regs.tid)).ok();
+
+	let mut desc = vec![0u8; 32];
+	desc.extend_from_slice(&regs.tid.to_le_bytes());
+	desc.extend_from_slice(&stat.as_ref().map_or(0, |s| s.ppid).to_le_bytes());
+	desc.extend_from_slice(&stat.as_ref().map_or(0, |s| s.pgrp).to_le_bytes());
+	desc.extend_from_slice(&stat.as_ref().map_or(0, |s| s.session).to_le_bytes());
+	desc.resize(desc.len() + 4 * 16, 0); // utime, stime, cutime, cstime
+	desc.extend_from_slice(regs.as_bytes());
+	desc.extend_from_slice(&0u32.to_le_bytes()); // pr_fpvalid
+	desc.resize(desc.len().next_multiple_of(8), 0);
+	desc
+}
+
+/// `struct elf_prpsinfo`, 136 bytes on 64-bit Linux.
+fn prpsinfo(info: &ProcessInfo) -> Vec<u8> {
+	let stat = ProcStat::read(&format!("/proc/{}/stat", info.pid)).ok();
+	let state = stat.as_ref().map_or('R', |s| s.state.as_char());
+
+	let mut desc = vec![0u8; 136];
+	desc[1] = state as u8; // pr_sname
+	desc[16..20].copy_from_slice(&info.uid.unwrap_or(0).to_le_bytes());
+	desc[24..28].copy_from_slice(&info.pid.to_le_bytes());
+	desc[28..32].copy_from_slice(&info.ppid.to_le_bytes());
+	desc[32..36].copy_from_slice(&stat.as_ref().map_or(0, |s| s.pgrp).to_le_bytes());
+	desc[36..40].copy_from_slice(&stat.as_ref().map_or(0, |s| s.session).to_le_bytes());
+
+	let fname = info.name.as_bytes();
+	let n = fname.len().min(15);
+	desc[40..40 + n].copy_from_slice(&fname[..n]);
+
+	let args = info.cmdline_string();
+	let n = args.len().min(79);
+	desc[56..56 + n].copy_from_slice(&args.as_bytes()[..n]);
+
+	desc
+}
+
+/// `NT_FILE`: count, page size, `(start, end, offset in pages)` per file mapping, then the
+/// NUL terminated file names.
+fn file_note(maps: &ProcMemoryMaps, page: u64) -> Vec<u8> {
+	let files: Vec<&ProcMemoryRegion> = maps
+		.get_regions()
+		.iter()
+		.filter(|r| r.inode != 0 && r.pathname.as_deref().is_some_and(|p| p.starts_with('/')))
+		.collect();
+
+	let mut desc = Vec::new();
+	desc.extend_from_slice(&(files.len() as u64).to_le_bytes());
+	desc.extend_from_slice(&page.to_le_bytes());
+
+	for region in &files {
+		desc.extend_from_slice(&region.start.to_le_bytes());
+		desc.extend_from_slice(&region.end.to_le_bytes());
+		desc.extend_from_slice(&(region.offset / page).to_le_bytes());
+	}
+
+	for region in &files {
+		desc.extend_from_slice(region.pathname.as_deref().unwrap_or_default().as_bytes());
+		desc.push(0);
+	}
+
+	desc
+}
+
+fn seek<W: Seek>(out: &mut W, offset: u64) -> Result<(), Error> {
+	out.seek(SeekFrom::Start(offset)).map_err(|e| {
+		Error::os(
+			ErrorKind::OutOfBounds,
+			format!("core file offset {}", offset),
+			e,
+		)
+	})?;
+	Ok(())
+}
+
+fn write<W: Write>(out: &mut W, bytes: &[u8]) -> Result<(), Error> {
+	out.write_all(bytes)
+		.map_err(|e| Error::os(ErrorKind::WriteFailed, "core file", e))?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use super::*;
+	use crate::{
+		internal::elf::{le_u32, le_u64, parse_notes},
+		mock::process::MockProcess,
+	};
+
+	fn registers(tid: u32) -> ThreadRegisters {
+		let mut bytes = vec![0u8; size_of::<libc::user_regs_struct>()];
+		bytes[..8].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+		ThreadRegisters::from_bytes(tid, &bytes).unwrap()
+	}
+
+	#[test]
+	fn writes_headers_notes_and_segments() {
+		let mut process = MockProcess::new(1)
+			.with_region(0x10000, 0x2000, "r-xp", Some("/usr/bin/game"))
+			.with_region(0x20000, 0x1000, "rw-p", Some("[heap]"))
+			.with_region(0x30000, 0x1000, "---p", None);
+		process.poke(0x10000, b"\x7fELF");
+		process.poke(0x20ff8, b"heap end");
+		// Only file mappings with an inode go into NT_FILE.
+		let mut regions = process.get_maps().get_regions().to_vec();
+		regions[0].inode = 42;
+		regions[0].offset = 0x1000;
+		let maps = ProcMemoryMaps::from_regions(regions);
+
+		let pid = std::process::id();
+		let mut out = Cursor::new(Vec::new());
+		let stats = write_core(&mut process, pid, &maps, &[registers(pid)], &mut out).unwrap();
+		let core = out.into_inner();
+
+		assert_eq!(stats.regions, 3);
+		assert_eq!(stats.threads, 1);
+		assert_eq!(stats.regions_skipped, 1);
+		assert_eq!(stats.bytes_written, 0x3000);
+		assert_eq!(stats.bytes_zero_filled, 0);
+
+		let header = ElfHeader::parse(&core).unwrap();
+		assert_eq!(header.e_type, ET_CORE);
+		assert_eq!(header.e_phnum, 4);
+		let phdrs: Vec<ProgramHeader> = (0..header.e_phnum as usize)
+			.map(|i| {
+				let at = header.e_phoff as usize + i * ELF64_PHDR_SIZE;
+				ProgramHeader::parse(&core[at..at + ELF64_PHDR_SIZE]).unwrap()
+			})
+			.collect();
+
+		assert_eq!(phdrs[0].p_type, PT_NOTE);
+		let loads = &phdrs[1..];
+		assert!(loads.iter().all(|p| p.p_type == PT_LOAD));
+		assert_eq!(
+			loads.iter().map(|p| p.p_vaddr).collect::<Vec<_>>(),
+			[0x10000, 0x20000, 0x30000]
+		);
+		assert_eq!(loads[0].p_flags, PF_R | PF_X);
+		assert_eq!(loads[1].p_flags, PF_R | PF_W);
+		assert_eq!((loads[2].p_filesz, loads[2].p_memsz), (0, 0x1000));
+
+		// Segments are page aligned, back to back after the notes, and end the file.
+		let page = page_size();
+		assert_eq!(loads[0].p_offset % page, 0);
+		assert!(loads[0].p_offset >= phdrs[0].p_offset + phdrs[0].p_filesz);
+		assert_eq!(loads[1].p_offset, loads[0].p_offset + 0x2000);
+		assert_eq!(core.len() as u64, loads[1].p_offset + 0x1000);
+
+		let text = loads[0].p_offset as usize;
+		assert_eq!(&core[text..text + 4], b"\x7fELF");
+		let heap = loads[1].p_offset as usize;
+		assert_eq!(&core[heap + 0xff8..heap + 0x1000], b"heap end");
+
+		let at = phdrs[0].p_offset as usize;
+		let notes = parse_notes(&core[at..at + phdrs[0].p_filesz as usize]);
+		let note = |name: &str, n_type| {
+			notes
+				.iter()
+				.find(|n| n.name == name && n.n_type == n_type)
+				.unwrap_or_else(|| panic!("no {} note {:#x}", name, n_type))
+				.desc
+		};
+
+		let prstatus = note("CORE", NT_PRSTATUS);
+		assert_eq!(le_u32(prstatus, 32), pid);
+		assert_eq!(le_u64(prstatus, 112), 0x1122_3344_5566_7788);
+
+		let prpsinfo = note("CORE", NT_PRPSINFO);
+		assert_eq!(prpsinfo.len(), 136);
+		assert_eq!(le_u32(prpsinfo, 24), pid);
+
+		let auxv = note("CORE", NT_AUXV);
+		assert_eq!(auxv, std::fs::read("/proc/self/auxv").unwrap());
+
+		let file = note("CORE", NT_FILE);
+		assert_eq!((le_u64(file, 0), le_u64(file, 8)), (1, page));
+		assert_eq!(
+			(le_u64(file, 16), le_u64(file, 24), le_u64(file, 32)),
+			(0x10000, 0x12000, 0x1000 / page)
+		);
+		assert_eq!(&file[40..], b"/usr/bin/game\0");
+
+		let lime_maps = note(LIME_NOTE_NAME, NT_LIME_MAPS);
+		assert_eq!(lime_maps, maps.to_maps_string().as_bytes());
+	}
+}
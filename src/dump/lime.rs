@@ -0,0 +1,97 @@
+use std::{
+	fs::File,
+	io::{BufWriter, Write},
+	path::Path,
+};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::lime::LimeRangeHeader,
+	maps::ProcMemoryMaps,
+	offline::lime::maps_sidecar_path,
+	procmem::procmem::ProcMem,
+	traits::ReadProcessMemory,
+};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct LimeDumpStats {
+	pub ranges: usize,
+	pub bytes_written: u64,
+	/// Bytes that failed to read and were stored as zeroes.
+	pub bytes_zero_filled: u64,
+}
+
+/// Dumps every readable region of `pid` to `path` in LiME framing and writes the region
+/// metadata to `<path>.maps`, which `offline::lime::LimeImage` reads back.
+pub fn dump_process_lime(pid: u32, path: impl AsRef<Path>) -> Result<LimeDumpStats, Error> {
+	let path = path.as_ref();
+	let mut procmem = ProcMem::new(pid, false)?;
+	let maps = procmem.get_maps().clone();
+
+	let file = File::create(path)
+		.map_err(|e| Error::os(ErrorKind::WriteFailed, format!("{}", path.display()), e))?;
+	let mut out = BufWriter::new(file);
+
+	let (stats, written) = write_lime(&mut procmem, &maps, &mut out)?;
+	out.flush()
+		.map_err(|e| Error::os(ErrorKind::WriteFailed, format!("{}", path.display()), e))?;
+
+	let sidecar = maps_sidecar_path(path);
+	std::fs::write(&sidecar, written.to_maps_string())
+		.map_err(|e| Error::os(ErrorKind::WriteFailed, format!("{}", sidecar.display()), e))?;
+
+	Ok(stats)
+}
+
+/// Writes one LiME range per readable region of `maps`. Chunks that fail to read are zero
+/// filled, as the range header has already promised their length.
+///
+/// Returns the maps of the regions that were written.
+pub fn write_lime<R: ReadProcessMemory, W: Write>(
+	reader: &mut R,
+	maps: &ProcMemoryMaps,
+	out: &mut W,
+) -> Result<(LimeDumpStats, ProcMemoryMaps), Error> {
+	let mut stats = LimeDumpStats::default();
+	let mut written = Vec::new();
+	let mut buf = vec![0u8; CHUNK_SIZE];
+
+	for region in maps
+		.get_regions()
+		.iter()
+		.filter(|r| r.is_readable() && r.size() > 0)
+	{
+		write(
+			out,
+			&LimeRangeHeader::for_range(region.start, region.end).to_bytes(),
+		)?;
+
+		let mut current = region.start;
+		while current < region.end {
+			let len = CHUNK_SIZE.min((region.end - current) as usize);
+			let chunk = &mut buf[..len];
+			let n = reader.read_bytes(current, chunk).unwrap_or(0);
+			if n < len {
+				chunk[n..].fill(0);
+				stats.bytes_zero_filled += (len - n) as u64;
+			}
+
+			write(out, chunk)?;
+			current += len as u64;
+		}
+
+		stats.ranges += 1;
+		stats.bytes_written += region.size();
+		written.push(region.clone());
+	}
+
+	Ok((stats, ProcMemoryMaps::from_regions(written)))
+}
+
+fn write<W: Write>(out: &mut W, bytes: &[u8]) -> Result<(), Error> {
+	out.write_all(bytes)
+		.map_err(|e| Error::os(ErrorKind::WriteFailed, "lime image", e))?;
+	Ok(())
+}
@@ -1,94 +1,296 @@
-use crate::traits::InternalLimeError;
-
-#[derive(Debug)]
-pub enum MemAddrError {
-	AddressInvalid(String),
-	AddressOutOfBounds(String),
-	AddressNotReadable(String),
-	InvalidPid(String),
-	ParseError(String),
-	NoPermission(String),
-}
+use std::fmt::{Debug, Display};
 
-#[derive(Debug)]
-pub enum RPMError {
-	FailedToRead(String),
-	ReadOutOfBounds(String),
-	BadDataType(String),
+/// What went wrong, for callers that want to react to specific failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+	InvalidAddress,
+	/// The access is outside of the mapped memory or crosses the end of a region.
+	OutOfBounds,
+	/// The region's permissions do not allow the access.
+	Protected,
+	/// The process does not exist or its `/proc` entries could not be opened.
+	InvalidPid,
+	/// Unexpected contents in a `/proc` file.
+	Parse,
+	ReadFailed,
+	WriteFailed,
+	BadDataType,
+	InvalidPattern,
+	PatternNotFound,
+	InvalidElf,
+	InvalidLime,
+	AttachFailed,
+	WaitFailed,
+	RegistersFailed,
+	PeekPokeFailed,
+	DetachFailed,
+	LaunchFailed,
+	UnexpectedStop,
+	Timeout,
+	Unsupported,
+	/// The kernel (permissions, Yama, capabilities) does not let us access the process.
+	AccessDenied,
+	/// Any other I/O error.
+	Io,
 }
 
-#[derive(Debug)]
-pub enum WPMError {
-	FailedToWrite(String),
-	WriteOutOfBounds(String),
-	BadDataType(String),
+impl ErrorKind {
+	pub fn description(&self) -> &'static str {
+		match self {
+			ErrorKind::InvalidAddress => "Address is invalid",
+			ErrorKind::OutOfBounds => "Address is out of bounds of process memory",
+			ErrorKind::Protected => "Wrong permissions for region",
+			ErrorKind::InvalidPid => "Invalid pid supplied",
+			ErrorKind::Parse => "Parse error",
+			ErrorKind::ReadFailed => "Failed to read",
+			ErrorKind::WriteFailed => "Failed to write",
+			ErrorKind::BadDataType => "Bad data type",
+			ErrorKind::InvalidPattern => "Pattern was invalid",
+			ErrorKind::PatternNotFound => "Pattern not found",
+			ErrorKind::InvalidElf => "Invalid ELF file",
+			ErrorKind::InvalidLime => "Invalid LiME image",
+			ErrorKind::AttachFailed => "Failed to attach",
+			ErrorKind::WaitFailed => "Failed to wait for tracee",
+			ErrorKind::RegistersFailed => "Failed to access registers",
+			ErrorKind::PeekPokeFailed => "Failed to access tracee memory",
+			ErrorKind::DetachFailed => "Failed to detach",
+			ErrorKind::LaunchFailed => "Failed to launch",
+			ErrorKind::UnexpectedStop => "Tracee stopped unexpectedly",
+			ErrorKind::Timeout => "Timed out",
+			ErrorKind::Unsupported => "Not supported",
+			ErrorKind::AccessDenied => "Access denied",
+			ErrorKind::Io => "I/O error",
+		}
+	}
 }
 
-#[derive(Debug)]
-pub enum InvalidFormat {
-	ContainsInvalidCharacters(String),
-	IsNonValidPattern(String),
+impl Display for ErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.description())
+	}
 }
 
-#[derive(Debug)]
-pub enum GeneralErrors {
-	PatternNotFound(String),
-	PatternIsEmpty(String),
-	PatternLargerThanBuffer(String),
+/// The error type of every fallible lime function.
+///
+/// Besides the kind and a message it carries whatever is known about the failed access: the
+/// address, its size, the region it was checked against and the OS error, so `EPERM` and
+/// `ESRCH` can be told apart with `raw_os_error`.
+pub struct Error {
+	kind: ErrorKind,
+	message: String,
+	addr: Option<u64>,
+	size: Option<usize>,
+	region: Option<(u64, u64)>,
+	os_error: Option<std::io::Error>,
 }
 
-impl InternalLimeError for WPMError {
-	fn string(&self) -> String {
-		match self {
-			WPMError::BadDataType(e) => format!("Bad data type: {}", e),
-			WPMError::WriteOutOfBounds(e) => format!("Write out of bounds: {}", e),
-			WPMError::FailedToWrite(e) => format!("Failed to write: {}", e),
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+	pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+		Self {
+			kind,
+			message: message.into(),
+			addr: None,
+			size: None,
+			region: None,
+			os_error: None,
 		}
 	}
+
+	/// An error caused by `os_error`.
+	pub fn os(kind: ErrorKind, message: impl Into<String>, os_error: std::io::Error) -> Self {
+		Self::new(kind, message).with_os_error(os_error)
+	}
+
+	/// An error caused by the current `errno`, e.g. right after a failed libc call.
+	pub fn last_os_error(kind: ErrorKind, message: impl Into<String>) -> Self {
+		Self::os(kind, message, std::io::Error::last_os_error())
+	}
+
+	pub fn with_addr(mut self, addr: u64) -> Self {
+		self.addr = Some(addr);
+		self
+	}
+
+	pub fn with_size(mut self, size: usize) -> Self {
+		self.size = Some(size);
+		self
+	}
+
+	pub fn with_region(mut self, start: u64, end: u64) -> Self {
+		self.region = Some((start, end));
+		self
+	}
+
+	pub fn with_os_error(mut self, os_error: std::io::Error) -> Self {
+		self.os_error = Some(os_error);
+		self
+	}
+
+	pub fn kind(&self) -> ErrorKind {
+		self.kind
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn addr(&self) -> Option<u64> {
+		self.addr
+	}
+
+	pub fn size(&self) -> Option<usize> {
+		self.size
+	}
+
+	/// `(start, end)` of the region the access was checked against.
+	pub fn region(&self) -> Option<(u64, u64)> {
+		self.region
+	}
+
+	pub fn os_error(&self) -> Option<&std::io::Error> {
+		self.os_error.as_ref()
+	}
+
+	/// The `errno` behind the error, if it came from the OS.
+	pub fn raw_os_error(&self) -> Option<i32> {
+		self.os_error.as_ref().and_then(|e| e.raw_os_error())
+	}
+
+	pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+		self.os_error.as_ref().map(|e| e.kind())
+	}
 }
 
-impl InternalLimeError for RPMError {
-	fn string(&self) -> String {
-		match self {
-			RPMError::BadDataType(e) => format!("Bad data type: {}", e),
-			RPMError::ReadOutOfBounds(e) => format!("Read out of bounds: {}", e),
-			RPMError::FailedToRead(e) => format!("Failed to read: {}", e),
+impl Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.kind)?;
+		if !self.message.is_empty() {
+			write!(f, ": {}", self.message)?;
+		}
+		if let Some(addr) = self.addr {
+			write!(f, " at {:#x}", addr)?;
+		}
+		if let Some(size) = self.size {
+			write!(f, " (size {})", size)?;
+		}
+		if let Some((start, end)) = self.region {
+			write!(f, " in region {:#x}-{:#x}", start, end)?;
+		}
+		if let Some(e) = &self.os_error {
+			write!(f, ": {}", e)?;
 		}
+		Ok(())
 	}
 }
 
-impl InternalLimeError for MemAddrError {
-	fn string(&self) -> String {
-		match self {
-			MemAddrError::AddressInvalid(e) => format!("Address is invalid: {}", e),
-			MemAddrError::AddressOutOfBounds(e) => {
-				format!("Address is out of bound of process memory: {}", e)
-			}
-			MemAddrError::AddressNotReadable(e) => format!("Unreadable mem region: {}", e),
-			MemAddrError::InvalidPid(e) => format!("Invalid pid supplied: {}", e),
-			MemAddrError::ParseError(e) => format!("Error while parsing /maps: {}", e),
-			MemAddrError::NoPermission(e) => format!("Wrong permissions for region: {}", e),
+impl Debug for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut s = f.debug_struct("Error");
+		s.field("kind", &self.kind).field("message", &self.message);
+		if let Some(addr) = self.addr {
+			s.field("addr", &format_args!("{:#x}", addr));
+		}
+		if let Some(size) = self.size {
+			s.field("size", &size);
+		}
+		if let Some((start, end)) = self.region {
+			s.field("region", &format_args!("{:#x}-{:#x}", start, end));
+		}
+		if let Some(e) = &self.os_error {
+			s.field("os_error", e);
 		}
+		s.finish()
 	}
 }
 
-impl InternalLimeError for InvalidFormat {
-	fn string(&self) -> String {
-		match self {
-			InvalidFormat::IsNonValidPattern(e) => format!("Pattern was invalid: {}", e),
-			InvalidFormat::ContainsInvalidCharacters(e) => {
-				format!("Pattern contained invalid characters: {}", e)
-			}
+/// The OS error is part of the `Display` output, so it is not also returned as the `source`,
+/// which would make error chain printers show it twice. Use `os_error` to get at it.
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+	fn from(e: std::io::Error) -> Self {
+		let kind = match e.kind() {
+			std::io::ErrorKind::PermissionDenied => ErrorKind::AccessDenied,
+			std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+			std::io::ErrorKind::Unsupported => ErrorKind::Unsupported,
+			_ => ErrorKind::Io,
+		};
+		Self::os(kind, String::new(), e)
+	}
+}
+
+impl From<Error> for std::io::Error {
+	fn from(e: Error) -> Self {
+		match e.os_error {
+			Some(os_error) if e.message.is_empty() => os_error,
+			_ => std::io::Error::other(e),
 		}
 	}
 }
 
-impl InternalLimeError for GeneralErrors {
-	fn string(&self) -> String {
-		match self {
-			GeneralErrors::PatternIsEmpty(e) => format!("Pattern was empty: {}", e),
-			GeneralErrors::PatternNotFound(e) => format!("Pattern not found in buffer: {}", e),
-			GeneralErrors::PatternLargerThanBuffer(e) => format!("Pattern is larger than buffer: {}", e),
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn is_send_sync_and_std_error() {
+		fn assert_bounds<T: std::error::Error + Send + Sync + 'static>() {}
+		assert_bounds::<Error>();
+	}
+
+	#[test]
+	fn display_includes_context() {
+		let err = Error::new(ErrorKind::Protected, "region is write protected")
+			.with_addr(0x1008)
+			.with_size(4)
+			.with_region(0x1000, 0x2000);
+
+		assert_eq!(
+			err.to_string(),
+			"Wrong permissions for region: region is write protected at 0x1008 (size 4) in region \
+			 0x1000-0x2000"
+		);
+	}
+
+	#[test]
+	fn keeps_errno() {
+		let err = Error::os(
+			ErrorKind::AttachFailed,
+			"pid 1",
+			std::io::Error::from_raw_os_error(libc::EPERM),
+		);
+
+		assert_eq!(err.raw_os_error(), Some(libc::EPERM));
+		assert_eq!(err.io_kind(), Some(std::io::ErrorKind::PermissionDenied));
+	}
+
+	#[test]
+	fn errno_is_printed_once_in_a_chain() {
+		let err = Error::os(
+			ErrorKind::AttachFailed,
+			"pid 1",
+			std::io::Error::from_raw_os_error(libc::EPERM),
+		);
+		let errno = std::io::Error::from_raw_os_error(libc::EPERM).to_string();
+
+		let mut chain = err.to_string();
+		let mut source = std::error::Error::source(&err);
+		while let Some(e) = source {
+			chain.push_str(&format!(": {}", e));
+			source = e.source();
 		}
+		assert_eq!(chain.matches(&errno).count(), 1);
+	}
+
+	#[test]
+	fn converts_from_io_error() {
+		let err: Error = std::io::Error::from_raw_os_error(libc::EACCES).into();
+		assert_eq!(err.kind(), ErrorKind::AccessDenied);
+
+		let io: std::io::Error = err.into();
+		assert_eq!(io.raw_os_error(), Some(libc::EACCES));
 	}
 }
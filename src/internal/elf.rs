@@ -0,0 +1,231 @@
+use std::io::Read;
+
+use crate::errors::{Error, ErrorKind};
+
+pub const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+pub const ELFCLASS64: u8 = 2;
+pub const ELFDATA2LSB: u8 = 1;
+pub const EV_CURRENT: u8 = 1;
+
+pub const ET_DYN: u16 = 3;
+pub const ET_CORE: u16 = 4;
+
+#[cfg(target_arch = "x86_64")]
+pub const EM_X86_64: u16 = 62;
+#[cfg(target_arch = "aarch64")]
+pub const EM_AARCH64: u16 = 183;
+
+pub const ELF64_EHDR_SIZE: usize = 64;
+pub const ELF64_PHDR_SIZE: usize = 56;
+
+pub const PT_LOAD: u32 = 1;
+pub const PT_NOTE: u32 = 4;
+
+pub const PF_X: u32 = 1;
+pub const PF_W: u32 = 2;
+pub const PF_R: u32 = 4;
+
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_PRPSINFO: u32 = 3;
+pub const NT_AUXV: u32 = 6;
+pub const NT_FILE: u32 = 0x4649_4c45;
+
+/// Name of the notes lime adds to the core files it writes.
+pub const LIME_NOTE_NAME: &str = "LIME";
+/// The dumped regions in `/proc/<pid>/maps` format. `NT_FILE` only lists file mappings, so
+/// without it names like `[heap]` and `[stack]` would be lost.
+pub const NT_LIME_MAPS: u32 = 0x4c4d_4150;
+
+#[cfg(target_arch = "x86_64")]
+pub const EM_CURRENT: u16 = EM_X86_64;
+
+#[cfg(target_arch = "aarch64")]
+pub const EM_CURRENT: u16 = EM_AARCH64;
+
+/// The fields of an `Elf64_Ehdr` lime needs. Only 64-bit little endian files are supported.
+#[derive(Debug, Clone, Copy)]
+pub struct ElfHeader {
+	pub e_type: u16,
+	pub e_machine: u16,
+	pub e_entry: u64,
+	pub e_phoff: u64,
+	pub e_shoff: u64,
+	pub e_phentsize: u16,
+	pub e_phnum: u16,
+}
+
+impl ElfHeader {
+	pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+		if bytes.len() < ELF64_EHDR_SIZE || bytes[0..4] != ELF_MAGIC {
+			return Err(Error::new(ErrorKind::InvalidElf, "bad magic".to_string()));
+		}
+
+		if bytes[4] != ELFCLASS64 || bytes[5] != ELFDATA2LSB {
+			return Err(Error::new(
+				ErrorKind::InvalidElf,
+				"only 64-bit little endian files are supported".to_string(),
+			));
+		}
+
+		Ok(Self {
+			e_type: le_u16(bytes, 16),
+			e_machine: le_u16(bytes, 18),
+			e_entry: le_u64(bytes, 24),
+			e_phoff: le_u64(bytes, 32),
+			e_shoff: le_u64(bytes, 40),
+			e_phentsize: le_u16(bytes, 54),
+			e_phnum: le_u16(bytes, 56),
+		})
+	}
+
+	pub fn read_from_file(path: &str) -> Result<Self, Error> {
+		let mut header = [0u8; ELF64_EHDR_SIZE];
+		std::fs::File::open(path)
+			.and_then(|mut f| f.read_exact(&mut header))
+			.map_err(|e| Error::os(ErrorKind::ReadFailed, path, e))?;
+
+		Self::parse(&header)
+	}
+
+	pub fn is_position_independent(&self) -> bool {
+		self.e_type == ET_DYN
+	}
+}
+
+pub fn le_u16(bytes: &[u8], offset: usize) -> u16 {
+	u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+pub fn le_u32(bytes: &[u8], offset: usize) -> u32 {
+	let mut b = [0u8; 4];
+	b.copy_from_slice(&bytes[offset..offset + 4]);
+	u32::from_le_bytes(b)
+}
+
+pub fn le_u64(bytes: &[u8], offset: usize) -> u64 {
+	let mut b = [0u8; 8];
+	b.copy_from_slice(&bytes[offset..offset + 8]);
+	u64::from_le_bytes(b)
+}
+
+impl ElfHeader {
+	/// Serializes an `Elf64_Ehdr` for a file without section headers.
+	pub fn to_bytes(self) -> [u8; ELF64_EHDR_SIZE] {
+		let mut b = [0u8; ELF64_EHDR_SIZE];
+		b[0..4].copy_from_slice(&ELF_MAGIC);
+		b[4] = ELFCLASS64;
+		b[5] = ELFDATA2LSB;
+		b[6] = EV_CURRENT;
+		b[16..18].copy_from_slice(&self.e_type.to_le_bytes());
+		b[18..20].copy_from_slice(&self.e_machine.to_le_bytes());
+		b[20..24].copy_from_slice(&(EV_CURRENT as u32).to_le_bytes());
+		b[24..32].copy_from_slice(&self.e_entry.to_le_bytes());
+		b[32..40].copy_from_slice(&self.e_phoff.to_le_bytes());
+		b[40..48].copy_from_slice(&self.e_shoff.to_le_bytes());
+		b[52..54].copy_from_slice(&(ELF64_EHDR_SIZE as u16).to_le_bytes());
+		b[54..56].copy_from_slice(&self.e_phentsize.to_le_bytes());
+		b[56..58].copy_from_slice(&self.e_phnum.to_le_bytes());
+		b
+	}
+}
+
+/// An `Elf64_Phdr`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgramHeader {
+	pub p_type: u32,
+	pub p_flags: u32,
+	pub p_offset: u64,
+	pub p_vaddr: u64,
+	pub p_paddr: u64,
+	pub p_filesz: u64,
+	pub p_memsz: u64,
+	pub p_align: u64,
+}
+
+impl ProgramHeader {
+	pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+		if bytes.len() < ELF64_PHDR_SIZE {
+			return Err(Error::new(
+				ErrorKind::InvalidElf,
+				"truncated program header".to_string(),
+			));
+		}
+
+		Ok(Self {
+			p_type: le_u32(bytes, 0),
+			p_flags: le_u32(bytes, 4),
+			p_offset: le_u64(bytes, 8),
+			p_vaddr: le_u64(bytes, 16),
+			p_paddr: le_u64(bytes, 24),
+			p_filesz: le_u64(bytes, 32),
+			p_memsz: le_u64(bytes, 40),
+			p_align: le_u64(bytes, 48),
+		})
+	}
+
+	pub fn to_bytes(self) -> [u8; ELF64_PHDR_SIZE] {
+		let mut b = [0u8; ELF64_PHDR_SIZE];
+		b[0..4].copy_from_slice(&self.p_type.to_le_bytes());
+		b[4..8].copy_from_slice(&self.p_flags.to_le_bytes());
+		b[8..16].copy_from_slice(&self.p_offset.to_le_bytes());
+		b[16..24].copy_from_slice(&self.p_vaddr.to_le_bytes());
+		b[24..32].copy_from_slice(&self.p_paddr.to_le_bytes());
+		b[32..40].copy_from_slice(&self.p_filesz.to_le_bytes());
+		b[40..48].copy_from_slice(&self.p_memsz.to_le_bytes());
+		b[48..56].copy_from_slice(&self.p_align.to_le_bytes());
+		b
+	}
+}
+
+/// Appends an ELF note (`Elf64_Nhdr`, name and descriptor, each padded to 4 bytes).
+pub fn push_note(buf: &mut Vec<u8>, name: &str, n_type: u32, desc: &[u8]) {
+	let name_len = name.len() + 1;
+	buf.extend_from_slice(&(name_len as u32).to_le_bytes());
+	buf.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+	buf.extend_from_slice(&n_type.to_le_bytes());
+	buf.extend_from_slice(name.as_bytes());
+	buf.push(0);
+	buf.resize(buf.len().next_multiple_of(4), 0);
+	buf.extend_from_slice(desc);
+	buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+/// A parsed ELF note.
+#[derive(Debug, Clone)]
+pub struct Note<'a> {
+	pub name: &'a str,
+	pub n_type: u32,
+	pub desc: &'a [u8],
+}
+
+/// Iterates the notes of a `PT_NOTE` segment. Stops at the first malformed note.
+pub fn parse_notes(mut bytes: &[u8]) -> Vec<Note<'_>> {
+	let mut notes = Vec::new();
+
+	while bytes.len() >= 12 {
+		let name_len = le_u32(bytes, 0) as usize;
+		let desc_len = le_u32(bytes, 4) as usize;
+		let n_type = le_u32(bytes, 8);
+
+		let name_start = 12;
+		let desc_start = name_start + name_len.next_multiple_of(4);
+		let next = desc_start + desc_len.next_multiple_of(4);
+		if desc_start + desc_len > bytes.len() {
+			break;
+		}
+
+		let name = std::str::from_utf8(&bytes[name_start..name_start + name_len])
+			.unwrap_or_default()
+			.trim_end_matches('\0');
+
+		notes.push(Note {
+			name,
+			n_type,
+			desc: &bytes[desc_start..desc_start + desc_len],
+		});
+
+		bytes = &bytes[next.min(bytes.len())..];
+	}
+
+	notes
+}
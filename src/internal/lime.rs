@@ -0,0 +1,108 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::elf::{le_u32, le_u64},
+};
+
+/// "EMiL" when read as little endian bytes.
+pub const LIME_MAGIC: u32 = 0x4c69_4d45;
+pub const LIME_VERSION: u32 = 1;
+pub const LIME_HEADER_SIZE: usize = 32;
+
+/// The 32-byte header preceding every memory range in a LiME image. `e_addr` is inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimeRangeHeader {
+	pub s_addr: u64,
+	pub e_addr: u64,
+}
+
+impl LimeRangeHeader {
+	/// Builds the header for the half-open range `[start, end)`.
+	pub fn for_range(start: u64, end: u64) -> Self {
+		Self {
+			s_addr: start,
+			e_addr: end - 1,
+		}
+	}
+
+	pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+		if bytes.len() < LIME_HEADER_SIZE {
+			return Err(Error::new(
+				ErrorKind::InvalidLime,
+				"truncated range header".to_string(),
+			));
+		}
+
+		let magic = le_u32(bytes, 0);
+		if magic != LIME_MAGIC {
+			return Err(Error::new(
+				ErrorKind::InvalidLime,
+				format!("bad magic {:#x}", magic),
+			));
+		}
+
+		let version = le_u32(bytes, 4);
+		if version != LIME_VERSION {
+			return Err(Error::new(
+				ErrorKind::InvalidLime,
+				format!("unsupported version {}", version),
+			));
+		}
+
+		let header = Self {
+			s_addr: le_u64(bytes, 8),
+			e_addr: le_u64(bytes, 16),
+		};
+
+		if header.e_addr < header.s_addr {
+			return Err(Error::new(
+				ErrorKind::InvalidLime,
+				format!(
+					"range end {:#x} before start {:#x}",
+					header.e_addr, header.s_addr
+				),
+			));
+		}
+
+		Ok(header)
+	}
+
+	pub fn to_bytes(self) -> [u8; LIME_HEADER_SIZE] {
+		let mut b = [0u8; LIME_HEADER_SIZE];
+		b[0..4].copy_from_slice(&LIME_MAGIC.to_le_bytes());
+		b[4..8].copy_from_slice(&LIME_VERSION.to_le_bytes());
+		b[8..16].copy_from_slice(&self.s_addr.to_le_bytes());
+		b[16..24].copy_from_slice(&self.e_addr.to_le_bytes());
+		b
+	}
+
+	/// `None` for a range that ends at `u64::MAX`, whose length does not fit.
+	pub fn len(&self) -> Option<u64> {
+		self.e_addr.checked_sub(self.s_addr)?.checked_add(1)
+	}
+
+	/// Exclusive end address, `None` for a range that ends at `u64::MAX`.
+	pub fn end(&self) -> Option<u64> {
+		self.e_addr.checked_add(1)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ranges_ending_at_the_top_have_no_length() {
+		let header = LimeRangeHeader {
+			s_addr: 0x1000,
+			e_addr: 0x1fff,
+		};
+		assert_eq!(LimeRangeHeader::parse(&header.to_bytes()).unwrap(), header);
+		assert_eq!((header.len(), header.end()), (Some(0x1000), Some(0x2000)));
+
+		let top = LimeRangeHeader {
+			s_addr: 0,
+			e_addr: u64::MAX,
+		};
+		assert_eq!((top.len(), top.end()), (None, None));
+	}
+}
@@ -1,20 +1,21 @@
-#![allow(dead_code)]
-
 use std::cmp::min;
 
 use basic_pattern_scanner::pattern::types::Pattern;
 
 use crate::{
-	errors::{GeneralErrors, InvalidFormat, MemAddrError},
-	traits::{InternalLimeError, ReadProcessMemory},
+	errors::{Error, ErrorKind},
+	traits::ReadProcessMemory,
 };
 
 // Uses the default scanner, when not on nightly and without the simd_std_unstable feature flag enabled will be Scaler, otherwise will be SIMD
 use basic_pattern_scanner::scanner::scan_all;
 
+/// Granularity at which unreadable memory is skipped.
+const PAGE_SIZE: u64 = 0x1000;
+
 /// Strips optional `0x`/`0X` prefixes from each token, then delegates to
 /// `Pattern::from_ida_str`. Accepts both `?` and `??` as wildcards.
-pub fn parse_pattern(pattern: &str) -> Result<Pattern, Box<dyn InternalLimeError>> {
+pub fn parse_pattern(pattern: &str) -> Result<Pattern, Error> {
 	let normalized: String = pattern
 		.split_whitespace()
 		.map(|t| {
@@ -27,9 +28,8 @@ pub fn parse_pattern(pattern: &str) -> Result<Pattern, Box<dyn InternalLimeError
 		.collect::<Vec<_>>()
 		.join(" ");
 
-	Pattern::from_ida_str(&normalized).map_err(|e| {
-		Box::new(InvalidFormat::IsNonValidPattern(format!("{:?}", e))) as Box<dyn InternalLimeError>
-	})
+	Pattern::from_ida_str(&normalized)
+		.map_err(|e| Error::new(ErrorKind::InvalidPattern, format!("{:?}", e)))
 }
 
 pub struct OffsetScanner {
@@ -45,17 +45,19 @@ impl OffsetScanner {
 		&self,
 		buffer: &[u8],
 		pattern: &Pattern,
-	) -> Result<Vec<u64>, Box<dyn InternalLimeError>> {
+	) -> Result<Vec<u64>, Error> {
 		if pattern.bytes.is_empty() {
-			return Err(Box::new(GeneralErrors::PatternIsEmpty(
+			return Err(Error::new(
+				ErrorKind::InvalidPattern,
 				"Empty pattern".to_string(),
-			)));
+			));
 		}
 
 		if buffer.len() < pattern.bytes.len() {
-			return Err(Box::new(GeneralErrors::PatternLargerThanBuffer(
+			return Err(Error::new(
+				ErrorKind::InvalidPattern,
 				"Pattern larger than buffer".to_string(),
-			)));
+			));
 		}
 
 		let results: Vec<u64> = scan_all(buffer, pattern)
@@ -64,9 +66,10 @@ impl OffsetScanner {
 			.collect();
 
 		match results.len() {
-			0 => Err(Box::new(GeneralErrors::PatternNotFound(
+			0 => Err(Error::new(
+				ErrorKind::PatternNotFound,
 				"No matches found".to_string(),
-			))),
+			)),
 			_ => Ok(results),
 		}
 	}
@@ -77,22 +80,41 @@ impl OffsetScanner {
 		start_addr: u64,
 		end_addr: u64,
 		pattern: &Pattern,
-	) -> Result<Vec<u64>, Box<dyn InternalLimeError>> {
+	) -> Result<Vec<u64>, Error> {
+		let mut coverage = RangeCoverage::new(start_addr, end_addr);
+		self.scan_range_for_pattern_tracked(reader, start_addr, end_addr, pattern, &mut coverage)
+	}
+
+	/// Same as `scan_range_for_pattern`, but records how many bytes could actually be read
+	/// and which parts of the range failed to read in `coverage`.
+	pub fn scan_range_for_pattern_tracked<T: ReadProcessMemory>(
+		&self,
+		reader: &mut T,
+		start_addr: u64,
+		end_addr: u64,
+		pattern: &Pattern,
+		coverage: &mut RangeCoverage,
+	) -> Result<Vec<u64>, Error> {
 		if pattern.bytes.is_empty() {
-			return Err(Box::new(GeneralErrors::PatternIsEmpty(
+			return Err(Error::new(
+				ErrorKind::InvalidPattern,
 				"Empty pattern".to_string(),
-			)));
+			));
 		}
 
 		if start_addr >= end_addr {
-			return Err(Box::new(MemAddrError::AddressOutOfBounds(format!(
-				"start address ({}) bigger or equal to end address ({})",
-				start_addr, end_addr
-			))));
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				format!(
+					"start address ({}) bigger or equal to end address ({})",
+					start_addr, end_addr
+				),
+			));
 		}
 
 		let mut results = Vec::new();
 		let mut current = start_addr;
+		let mut covered_until = start_addr;
 		let overlap_size = pattern.bytes.len().saturating_sub(1);
 
 		while current < end_addr {
@@ -107,12 +129,30 @@ impl OffsetScanner {
 			let n = reader.read_bytes(current, &mut buffer).unwrap_or(0);
 			buffer.truncate(n);
 
+			let read_end = current + n as u64;
+			if read_end > covered_until {
+				coverage.inspected += read_end - covered_until.max(current);
+				covered_until = read_end;
+			}
+
 			if buffer.len() >= pattern.bytes.len() {
 				for m in scan_all(&buffer, pattern) {
 					results.push(current + m.offset as u64);
 				}
 			}
 
+			if n < read_size {
+				// Skip the page the read stopped in and carry on after it. No overlap is
+				// needed, matches cannot span unreadable bytes.
+				let next_page = (read_end | (PAGE_SIZE - 1))
+					.saturating_add(1)
+					.min(end_addr);
+				coverage.add_failed(read_end.max(covered_until), next_page);
+				covered_until = covered_until.max(next_page);
+				current = next_page;
+				continue;
+			}
+
 			current += read_size as u64;
 			current = current.saturating_sub(overlap_size as u64);
 		}
@@ -121,12 +161,86 @@ impl OffsetScanner {
 		results.dedup_by(|a, b| a == b);
 
 		match results.len() {
-			0 => Err(Box::new(GeneralErrors::PatternNotFound(
+			0 => Err(Error::new(
+				ErrorKind::PatternNotFound,
 				"No matches found".to_string(),
-			))),
+			)),
 			_ => Ok(results),
 		}
 	}
+
+	/// Scans every `(start, end)` range and returns all matches sorted. Ranges that cannot be
+	/// scanned are skipped.
+	pub fn scan_ranges_for_pattern<T: ReadProcessMemory>(
+		&self,
+		reader: &mut T,
+		ranges: &[(u64, u64)],
+		pattern: &Pattern,
+	) -> Vec<u64> {
+		let mut results = Vec::new();
+
+		for (start, end) in ranges {
+			if let Ok(mut r) = self.scan_range_for_pattern(reader, *start, *end, pattern) {
+				results.append(&mut r);
+			}
+		}
+
+		results.sort_unstable();
+		results
+	}
+}
+
+/// What part of a scanned range was really inspected.
+#[derive(Debug, Clone, Default)]
+pub struct RangeCoverage {
+	pub start: u64,
+	pub end: u64,
+	/// Bytes that were selected for reading, e.g. resident pages. Equal to the range size for
+	/// plain range scans.
+	pub selected: u64,
+	/// Bytes that were successfully read and scanned.
+	pub inspected: u64,
+	/// Sub-ranges that were selected but could not be read.
+	pub failed: Vec<(u64, u64)>,
+}
+
+impl RangeCoverage {
+	pub fn new(start: u64, end: u64) -> Self {
+		Self {
+			start,
+			end,
+			selected: end.saturating_sub(start),
+			inspected: 0,
+			failed: Vec::new(),
+		}
+	}
+
+	pub fn size(&self) -> u64 {
+		self.end.saturating_sub(self.start)
+	}
+
+	/// Fraction of the whole range that was scanned.
+	pub fn ratio(&self) -> f64 {
+		match self.size() {
+			0 => 0.0,
+			size => self.inspected as f64 / size as f64,
+		}
+	}
+
+	pub fn is_complete(&self) -> bool {
+		self.failed.is_empty() && self.inspected >= self.selected
+	}
+
+	fn add_failed(&mut self, start: u64, end: u64) {
+		if start >= end {
+			return;
+		}
+
+		match self.failed.last_mut() {
+			Some(last) if last.1 >= start => last.1 = last.1.max(end),
+			_ => self.failed.push((start, end)),
+		}
+	}
 }
 
 impl Default for OffsetScanner {
@@ -134,3 +248,133 @@ impl Default for OffsetScanner {
 		Self::new(64 * 1024)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	fn mock_with(at: &[(u64, &[u8])]) -> MockProcess {
+		let mut mock = MockProcess::new(1).with_region(0x1000, 0x100, "rw-p", None);
+		for (addr, bytes) in at {
+			mock.poke(*addr, bytes);
+		}
+		mock
+	}
+
+	#[test]
+	fn parse_pattern_accepts_prefixes_and_wildcards() {
+		let a = parse_pattern("0x48 8B ? ?? 0XC3").unwrap();
+		let b = parse_pattern("48 8B ?? ?? C3").unwrap();
+		assert_eq!(a.bytes.len(), 5);
+		assert_eq!(format!("{:?}", a.bytes), format!("{:?}", b.bytes));
+		assert!(parse_pattern("48 ZZ").is_err());
+	}
+
+	#[test]
+	fn match_straddling_chunk_boundary() {
+		let pattern = parse_pattern("AA BB CC DD").unwrap();
+
+		// Every split of the pattern across the 16-byte chunk edge at 0x1010.
+		for shift in 1..4 {
+			let addr = 0x1010 - shift;
+			let mut mock = mock_with(&[(addr, &[0xaa, 0xbb, 0xcc, 0xdd])]);
+			let found = OffsetScanner::new(16)
+				.scan_range_for_pattern(&mut mock, 0x1000, 0x1100, &pattern)
+				.unwrap();
+			assert_eq!(found, vec![addr], "shift {}", shift);
+		}
+	}
+
+	#[test]
+	fn matches_in_overlap_are_reported_once() {
+		let pattern = parse_pattern("AA BB").unwrap();
+		let mut mock = mock_with(&[
+			(0x100f, &[0xaa, 0xbb]),
+			(0x1010, &[0xbb]),
+			(0x1020, &[0xaa, 0xbb]),
+		]);
+
+		let found = OffsetScanner::new(16)
+			.scan_range_for_pattern(&mut mock, 0x1000, 0x1100, &pattern)
+			.unwrap();
+		assert_eq!(found, vec![0x100f, 0x1020]);
+	}
+
+	#[test]
+	fn match_at_range_edges() {
+		let pattern = parse_pattern("11 22 33").unwrap();
+		let mut mock = mock_with(&[(0x1000, &[0x11, 0x22, 0x33]), (0x10fd, &[0x11, 0x22, 0x33])]);
+
+		let found = OffsetScanner::new(32)
+			.scan_range_for_pattern(&mut mock, 0x1000, 0x1100, &pattern)
+			.unwrap();
+		assert_eq!(found, vec![0x1000, 0x10fd]);
+
+		// One byte short of the last match.
+		assert!(
+			OffsetScanner::new(32)
+				.scan_range_for_pattern(&mut mock, 0x1001, 0x10ff, &pattern)
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn coverage_records_unreadable_tail() {
+		let pattern = parse_pattern("AA").unwrap();
+		let mut mock = mock_with(&[(0x1010, &[0xaa])]);
+		let mut coverage = RangeCoverage::new(0x1000, 0x1200);
+
+		let found = OffsetScanner::new(64)
+			.scan_range_for_pattern_tracked(&mut mock, 0x1000, 0x1200, &pattern, &mut coverage)
+			.unwrap();
+		assert_eq!(found, vec![0x1010]);
+		assert_eq!(coverage.inspected, 0x100);
+		assert_eq!(coverage.failed, vec![(0x1100, 0x1200)]);
+		assert!(!coverage.is_complete());
+		assert_eq!(coverage.ratio(), 0.5);
+	}
+
+	#[test]
+	fn coverage_skips_unreadable_pages_only() {
+		let pattern = parse_pattern("AA BB").unwrap();
+		let mut mock = MockProcess::new(1)
+			.with_region(0x1000, 0x1000, "rw-p", None)
+			.with_region(0x2000, 0x1000, "---p", None)
+			.with_region(0x3000, 0x1000, "rw-p", None);
+		mock.poke(0x1ffe, &[0xaa, 0xbb]);
+		mock.poke(0x3800, &[0xaa, 0xbb]);
+		let mut coverage = RangeCoverage::new(0x1000, 0x4000);
+
+		let found = OffsetScanner::default()
+			.scan_range_for_pattern_tracked(&mut mock, 0x1000, 0x4000, &pattern, &mut coverage)
+			.unwrap();
+		assert_eq!(found, vec![0x1ffe, 0x3800]);
+		assert_eq!(coverage.inspected, 0x2000);
+		assert_eq!(coverage.failed, vec![(0x2000, 0x3000)]);
+	}
+
+	#[test]
+	fn empty_and_oversized_patterns_are_rejected() {
+		let scanner = OffsetScanner::default();
+		let pattern = parse_pattern("AA BB CC").unwrap();
+		assert!(
+			scanner
+				.scan_buf_for_pattern(&[0xaa, 0xbb], &pattern)
+				.is_err()
+		);
+		assert_eq!(
+			scanner
+				.scan_buf_for_pattern(&[0, 0xaa, 0xbb, 0xcc], &pattern)
+				.unwrap(),
+			vec![1]
+		);
+
+		let mut mock = mock_with(&[]);
+		assert!(
+			scanner
+				.scan_range_for_pattern(&mut mock, 0x1010, 0x1010, &pattern)
+				.is_err()
+		);
+	}
+}
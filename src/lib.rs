@@ -0,0 +1,125 @@
+//! Reading, writing and scanning the memory of other processes on Linux.
+//!
+//! Every way of getting at a process' memory is a backend behind its own cargo feature:
+//!
+//! - `procmem`: `/proc/<pid>/mem`, plus pagemap and soft-dirty guided scans and live dumps
+//! - `procvm`: `process_vm_readv`/`process_vm_writev`
+//! - `ptrace`: register access, stopping thread groups and launching stopped processes
+//! - `devmem`: physical memory through `/dev/mem` (off by default)
+//! - `offline`: ELF core files and LiME images
+//!
+//! `derive` adds `#[derive(RemoteStruct)]` for reading whole structs, see `remote::structs`.
+//! `remote::ptr` and `remote::cstr` follow pointers, arrays and strings in the target, and
+//! `remote::containers` decodes C++ and Rust strings and vectors.
+//!
+//! Backends implement the traits in `traits`, so code written against `ReadProcessMemory`,
+//! `WriteProcessMemory` and `ProcessMemoryPatternScan` works with any of them, including the
+//! in-memory `mock::process::MockProcess`. `Process` picks a live backend automatically.
+
+mod internal {
+	// Shared by `process::auxv` and the core file reader (`offline`) and writer (`procmem` +
+	// `ptrace`), so builds without both leave parts of it unused.
+	#[cfg_attr(
+		not(all(feature = "offline", feature = "procmem", feature = "ptrace")),
+		allow(dead_code)
+	)]
+	pub mod elf;
+	// The writer in `dump::lime` also needs `procmem`.
+	#[cfg(feature = "offline")]
+	#[cfg_attr(not(feature = "procmem"), allow(dead_code))]
+	pub mod lime;
+	pub mod patterns {
+		pub mod offsets;
+	}
+}
+
+pub use internal::patterns::offsets::{OffsetScanner, RangeCoverage, parse_pattern};
+#[cfg(any(feature = "procvm", feature = "procmem", feature = "ptrace"))]
+pub use process::handle::{Backend, Process};
+
+pub mod process {
+	pub mod access;
+	pub mod auxv;
+	pub mod find;
+	#[cfg(any(feature = "procvm", feature = "procmem", feature = "ptrace"))]
+	pub mod handle;
+	pub mod query;
+	pub mod regs;
+	pub mod stat;
+	pub mod threads;
+	pub mod watch;
+}
+
+#[cfg(feature = "procmem")]
+pub mod dump {
+	#[cfg(feature = "ptrace")]
+	pub mod elfcore;
+	#[cfg(feature = "offline")]
+	pub mod lime;
+}
+
+#[cfg(feature = "devmem")]
+pub mod devmem {
+	#[allow(clippy::module_inception)]
+	pub mod devmem;
+	mod read;
+	mod write;
+}
+
+pub mod mock {
+	pub mod process;
+}
+
+#[cfg(feature = "offline")]
+pub mod offline {
+	pub mod elfcore;
+	pub mod lime;
+}
+
+#[cfg(feature = "procvm")]
+pub mod procvm {
+	#[allow(clippy::module_inception)]
+	pub mod procvm;
+	mod read;
+	mod write;
+}
+
+#[cfg(feature = "procmem")]
+pub mod procmem {
+	pub mod pagemap;
+	#[allow(clippy::module_inception)]
+	pub mod procmem;
+	pub mod read;
+	pub mod scan;
+	pub mod softdirty;
+	pub mod write;
+}
+
+#[cfg(feature = "ptrace")]
+pub mod ptrace {
+	pub mod launch;
+	pub mod mem;
+	mod read;
+	pub mod regs;
+	mod write;
+}
+
+pub mod remote {
+	pub mod containers;
+	pub mod cstr;
+	pub mod ptr;
+	pub mod structs;
+}
+
+#[cfg(feature = "table")]
+pub mod table {
+	pub mod address;
+	pub mod cheat_engine;
+	pub mod cheat_table;
+}
+
+pub mod errors;
+pub mod maps;
+pub mod pod;
+pub mod scan_mode;
+pub mod traits;
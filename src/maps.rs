@@ -0,0 +1,594 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	traits::ScanTarget,
+};
+
+#[derive(Clone)]
+pub struct ProcMemoryRegion {
+	pub start: u64,
+	pub end: u64,
+	pub perm: String,
+	pub offset: u64,
+	pub dev: String,
+	pub inode: u64,
+	pub pathname: Option<String>,
+	pub smaps: Option<SmapsInfo>,
+}
+
+/// Per-region statistics from `/proc/<pid>/smaps`. Sizes are in bytes.
+#[derive(Clone, Debug, Default)]
+pub struct SmapsInfo {
+	pub rss: u64,
+	pub pss: u64,
+	pub shared_clean: u64,
+	pub shared_dirty: u64,
+	pub private_clean: u64,
+	pub private_dirty: u64,
+	pub anonymous: u64,
+	pub anon_huge_pages: u64,
+	pub swap: u64,
+	pub vm_flags: Vec<String>,
+}
+
+impl SmapsInfo {
+	fn parse_line(&mut self, key: &str, rest: &str) {
+		if key == "VmFlags" {
+			self.vm_flags = rest.split_whitespace().map(|f| f.to_string()).collect();
+			return;
+		}
+
+		// "<n> kB"
+		let Some(kb) = rest
+			.split_whitespace()
+			.next()
+			.and_then(|v| v.parse::<u64>().ok())
+		else {
+			return;
+		};
+		let bytes = kb * 1024;
+
+		match key {
+			"Rss" => self.rss = bytes,
+			"Pss" => self.pss = bytes,
+			"Shared_Clean" => self.shared_clean = bytes,
+			"Shared_Dirty" => self.shared_dirty = bytes,
+			"Private_Clean" => self.private_clean = bytes,
+			"Private_Dirty" => self.private_dirty = bytes,
+			"Anonymous" => self.anonymous = bytes,
+			"AnonHugePages" => self.anon_huge_pages = bytes,
+			"Swap" => self.swap = bytes,
+			_ => {}
+		}
+	}
+
+	pub fn has_vm_flag(&self, flag: &str) -> bool {
+		self.vm_flags.iter().any(|f| f == flag)
+	}
+}
+
+impl ProcMemoryRegion {
+	pub fn contains(&self, addr: u64) -> bool {
+		addr >= self.start && addr < self.end
+	}
+
+	pub fn is_readable(&self) -> bool {
+		self.perm.chars().nth(0) == Some('r')
+	}
+
+	pub fn is_writeable(&self) -> bool {
+		self.perm.chars().nth(1) == Some('w')
+	}
+
+	pub fn is_executable(&self) -> bool {
+		self.perm.chars().nth(2) == Some('x')
+	}
+
+	pub fn is_anonymous(&self) -> bool {
+		match &self.pathname {
+			None => true,
+			Some(p) => p.is_empty() || p.starts_with('['),
+		}
+	}
+
+	pub fn size(&self) -> u64 {
+		self.end - self.start
+	}
+
+	pub fn to_maps_line(&self) -> String {
+		let line = format!(
+			"{:x}-{:x} {} {:08x} {} {}",
+			self.start, self.end, self.perm, self.offset, self.dev, self.inode
+		);
+
+		match &self.pathname {
+			Some(path) => format!("{:<73}{}", line, path),
+			None => line,
+		}
+	}
+
+	/// `None` when the maps were loaded without smaps.
+	pub fn is_resident(&self) -> Option<bool> {
+		self.smaps.as_ref().map(|s| s.rss > 0)
+	}
+
+	/// `None` when the maps were loaded without smaps.
+	pub fn has_private_dirty(&self) -> Option<bool> {
+		self.smaps.as_ref().map(|s| s.private_dirty > 0)
+	}
+}
+
+#[derive(Clone)]
+pub struct ProcMemoryMaps {
+	regions: Vec<ProcMemoryRegion>,
+}
+
+impl ProcMemoryMaps {
+	pub fn new(pid: u32) -> Result<Self, Error> {
+		let maps_path = format!("/proc/{pid}/maps");
+		let content = std::fs::read_to_string(&maps_path).map_err(|e| {
+			Error::os(
+				ErrorKind::InvalidPid,
+				format!("Failed to read maps for pid {}", pid),
+				e,
+			)
+		})?;
+
+		Self::parse_maps(&content)
+	}
+
+	/// Parses the contents of a `/proc/<pid>/maps` file.
+	pub fn parse_maps(content: &str) -> Result<Self, Error> {
+		let mut regions = Vec::new();
+
+		for l in content.lines() {
+			if let Some(reg) = Self::parse_maps_line(l)? {
+				regions.push(reg);
+			}
+		}
+
+		Ok(Self { regions })
+	}
+
+	/// Formats the regions like `/proc/<pid>/maps`, so `parse_maps` can read them back.
+	pub fn to_maps_string(&self) -> String {
+		self.regions
+			.iter()
+			.map(|r| format!("{}\n", r.to_maps_line()))
+			.collect()
+	}
+
+	/// Like `new`, but reads `/proc/<pid>/smaps` so every region carries its `SmapsInfo`.
+	/// This is considerably slower than reading `maps`.
+	pub fn new_with_smaps(pid: u32) -> Result<Self, Error> {
+		let smaps_path = format!("/proc/{pid}/smaps");
+		let content = std::fs::read_to_string(&smaps_path).map_err(|e| {
+			Error::os(
+				ErrorKind::InvalidPid,
+				format!("Failed to read smaps for pid {}", pid),
+				e,
+			)
+		})?;
+
+		Self::parse_smaps(&content)
+	}
+
+	fn parse_smaps(content: &str) -> Result<Self, Error> {
+		let mut regions: Vec<ProcMemoryRegion> = Vec::new();
+
+		for l in content.lines() {
+			let first = l.split_whitespace().next().unwrap_or_default();
+
+			if let Some(key) = first.strip_suffix(':') {
+				if let Some(region) = regions.last_mut() {
+					let rest = l[l.find(':').unwrap_or(0) + 1..].trim();
+					region
+						.smaps
+						.get_or_insert_with(SmapsInfo::default)
+						.parse_line(key, rest);
+				}
+				continue;
+			}
+
+			if let Some(mut reg) = Self::parse_maps_line(l)? {
+				reg.smaps = Some(SmapsInfo::default());
+				regions.push(reg);
+			}
+		}
+
+		Ok(Self { regions })
+	}
+
+	pub fn from_regions(regions: Vec<ProcMemoryRegion>) -> Self {
+		Self { regions }
+	}
+
+	pub fn has_smaps(&self) -> bool {
+		self.regions.iter().any(|r| r.smaps.is_some())
+	}
+
+	/// Reloads the maps of `pid` with smaps data if `target` filters on it. Rss and
+	/// Private_Dirty change all the time, so this happens before every filtered scan rather
+	/// than only when no smaps data is loaded.
+	pub fn refresh_for_scan(&mut self, pid: u32, target: &ScanTarget) -> Result<(), Error> {
+		if target.needs_smaps() {
+			*self = Self::new_with_smaps(pid)?;
+		}
+		Ok(())
+	}
+
+	fn parse_maps_line(line: &str) -> Result<Option<ProcMemoryRegion>, Error> {
+		let parts: Vec<&str> = line.split_whitespace().collect();
+		if parts.len() < 5 {
+			return Ok(None); // Skip malformed lines
+		}
+
+		// Parse address range
+		let addr_range = parts[0];
+		let addr_parts: Vec<&str> = addr_range.split('-').collect();
+		if addr_parts.len() != 2 {
+			return Ok(None);
+		}
+
+		let start = u64::from_str_radix(addr_parts[0], 16)
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid start address: {}", e)))?;
+
+		let end = u64::from_str_radix(addr_parts[1], 16)
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid end address: {}", e)))?;
+
+		let permissions = parts[1].to_string();
+
+		let offset = u64::from_str_radix(parts[2], 16)
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid offset: {}", e)))?;
+
+		let device = parts[3].to_string();
+
+		let inode = parts[4]
+			.parse::<u64>()
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid inode: {}", e)))?;
+
+		let pathname = if parts.len() > 5 {
+			Some(parts[5..].join(" "))
+		} else {
+			None
+		};
+
+		Ok(Some(ProcMemoryRegion {
+			start,
+			end,
+			perm: permissions,
+			offset,
+			dev: device,
+			inode,
+			pathname,
+			smaps: None,
+		}))
+	}
+
+	pub fn find_region_by_addr(&self, addr: u64) -> Option<&ProcMemoryRegion> {
+		self.regions.iter().find(|x| x.contains(addr))
+	}
+
+	pub fn can_read(&self, addr: u64, size: usize) -> Result<(), Error> {
+		let end_addr = addr.saturating_add((size as u64).saturating_sub(1));
+
+		let Some(reg) = self.find_region_by_addr(addr) else {
+			return Err(Error::new(ErrorKind::OutOfBounds, "address is not mapped")
+				.with_addr(addr)
+				.with_size(size));
+		};
+
+		if !reg.is_readable() {
+			return Err(Error::new(ErrorKind::Protected, "region is read protected")
+				.with_addr(addr)
+				.with_size(size)
+				.with_region(reg.start, reg.end));
+		}
+
+		if !reg.contains(end_addr) {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				"read extends beyond region boundary",
+			)
+			.with_addr(addr)
+			.with_size(size)
+			.with_region(reg.start, reg.end));
+		}
+		Ok(())
+	}
+
+	pub fn can_write(&self, addr: u64, size: usize) -> Result<(), Error> {
+		let end_addr = addr.saturating_add((size as u64).saturating_sub(1));
+
+		let Some(reg) = self.find_region_by_addr(addr) else {
+			return Err(Error::new(ErrorKind::OutOfBounds, "address is not mapped")
+				.with_addr(addr)
+				.with_size(size));
+		};
+
+		if !reg.is_writeable() {
+			return Err(
+				Error::new(ErrorKind::Protected, "region is write protected")
+					.with_addr(addr)
+					.with_size(size)
+					.with_region(reg.start, reg.end),
+			);
+		}
+
+		if !reg.contains(end_addr) {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				"write extends beyond region boundary",
+			)
+			.with_addr(addr)
+			.with_size(size)
+			.with_region(reg.start, reg.end));
+		}
+		Ok(())
+	}
+
+	pub fn can_execute(&self, addr: u64) -> Result<(), Error> {
+		let Some(reg) = self.find_region_by_addr(addr) else {
+			return Err(Error::new(ErrorKind::OutOfBounds, "address is not mapped").with_addr(addr));
+		};
+
+		if !reg.is_executable() {
+			return Err(
+				Error::new(ErrorKind::Protected, "region is execute protected")
+					.with_addr(addr)
+					.with_region(reg.start, reg.end),
+			);
+		}
+		Ok(())
+	}
+
+	pub fn get_regions(&self) -> &[ProcMemoryRegion] {
+		&self.regions
+	}
+
+	pub fn find_regions_by_name(&self, name: &str) -> Vec<&ProcMemoryRegion> {
+		self.regions
+			.iter()
+			.filter(|region| {
+				region
+					.pathname
+					.as_ref()
+					.is_some_and(|path| path.contains(name))
+			})
+			.collect()
+	}
+
+	pub fn find_regions_by_name_exact(&self, name: &str) -> Vec<&ProcMemoryRegion> {
+		self.regions
+			.iter()
+			.filter(|region| region.pathname.as_ref().is_some_and(|p| p == name))
+			.collect()
+	}
+
+	pub fn find_regions_by_prefix(&self, prefix: &str) -> Vec<&ProcMemoryRegion> {
+		self.regions
+			.iter()
+			.filter(|region| {
+				region
+					.pathname
+					.as_ref()
+					.is_some_and(|p| p.starts_with(prefix))
+			})
+			.collect()
+	}
+
+	pub fn find_regions_by_suffix(&self, suffix: &str) -> Vec<&ProcMemoryRegion> {
+		self.regions
+			.iter()
+			.filter(|region| {
+				region
+					.pathname
+					.as_ref()
+					.is_some_and(|p| p.ends_with(suffix))
+			})
+			.collect()
+	}
+
+	pub fn get_heap_regions(&self) -> Vec<&ProcMemoryRegion> {
+		self.find_regions_by_name_exact("[heap]")
+	}
+
+	pub fn get_stack_regions(&self) -> Vec<&ProcMemoryRegion> {
+		self.find_regions_by_prefix("[stack")
+	}
+
+	/// Looks for a `[stack:TID]` mapping first, then for the region containing `sp`.
+	pub fn get_thread_stack_region(&self, tid: u32, sp: Option<u64>) -> Option<&ProcMemoryRegion> {
+		let tagged = format!("[stack:{}]", tid);
+		if let Some(region) = self.find_regions_by_name_exact(&tagged).into_iter().next() {
+			return Some(region);
+		}
+
+		sp.and_then(|sp| self.find_region_by_addr(sp))
+	}
+
+	pub fn get_anonymous_regions(&self) -> Vec<&ProcMemoryRegion> {
+		self.regions
+			.iter()
+			.filter(|r| r.is_readable() && r.is_anonymous())
+			.collect()
+	}
+
+	/// Resolves a scan target to `(start, end)` ranges. Thread stacks depend on the backend,
+	/// so they are looked up through `thread_stack`.
+	pub fn resolve_scan_target(
+		&self,
+		target: &ScanTarget,
+		thread_stack: &dyn Fn(u32) -> Option<(u64, u64)>,
+	) -> Vec<(u64, u64)> {
+		match target {
+			ScanTarget::HeapAndStack => {
+				let mut r = self.get_heap_regions();
+				r.append(&mut self.get_stack_regions());
+				r.iter().map(|r| (r.start, r.end)).collect()
+			}
+			ScanTarget::Anonymous => self
+				.get_anonymous_regions()
+				.iter()
+				.map(|r| (r.start, r.end))
+				.collect(),
+			ScanTarget::AnonymousNonHeapAndStack => self
+				.get_anonymous_regions()
+				.iter()
+				.filter(|f| match f.pathname.as_deref() {
+					None => false,
+					Some(x) => !x.contains("[heap]") && !x.contains("[stack"),
+				})
+				.map(|r| (r.start, r.end))
+				.collect(),
+			ScanTarget::Module(name) => self
+				.find_regions_by_name(name)
+				.iter()
+				.filter(|r| r.is_readable() && r.is_executable())
+				.map(|r| (r.start, r.end))
+				.collect(),
+			ScanTarget::Range(start, end) => vec![(*start, *end)],
+			ScanTarget::ThreadStack(tid) => thread_stack(*tid).into_iter().collect(),
+			ScanTarget::Filtered(inner, filter) => self
+				.resolve_scan_target(inner, thread_stack)
+				.into_iter()
+				.filter(|(start, _)| {
+					self.find_region_by_addr(*start)
+						.and_then(|r| r.smaps.as_ref())
+						.is_some_and(|s| filter.matches(s))
+				})
+				.collect(),
+		}
+	}
+
+	pub fn get_module_base(&self, module_name: &str) -> Option<u64> {
+		self.find_regions_by_name(module_name)
+			.iter()
+			.filter(|region| region.is_executable())
+			.map(|region| region.start)
+			.min()
+	}
+
+	pub fn get_module_probable_load_base(&self, module_name: &str) -> Option<u64> {
+		self.find_regions_by_name(module_name)
+			.iter()
+			.filter(|region| region.is_executable())
+			.map(|region| region.start - region.offset)
+			.min()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAPS: &str = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 08:01 1234                       /usr/bin/game
+55d0c0a02000-55d0c0a05000 r-xp 00002000 08:01 1234                       /usr/bin/game
+55d0c0a05000-55d0c0a06000 rw-p 00005000 08:01 1234                       /usr/bin/game
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0                          [heap]
+7f0000000000-7f0000001000 ---p 00000000 00:00 0 
+7f0000001000-7f0000801000 rw-p 00000000 00:00 0                          [stack:4321]
+7ffc00000000-7ffc00021000 rw-p 00000000 00:00 0                          [stack]
+";
+
+	#[test]
+	fn parse_maps_roundtrip() {
+		let maps = ProcMemoryMaps::parse_maps(MAPS).unwrap();
+		assert_eq!(maps.get_regions().len(), 7);
+
+		let text = &maps.get_regions()[1];
+		assert_eq!(text.start, 0x55d0c0a02000);
+		assert_eq!(text.offset, 0x2000);
+		assert_eq!(text.inode, 1234);
+		assert_eq!(text.pathname.as_deref(), Some("/usr/bin/game"));
+		assert!(maps.get_regions()[4].pathname.is_none());
+
+		let reparsed = ProcMemoryMaps::parse_maps(&maps.to_maps_string()).unwrap();
+		assert_eq!(reparsed.to_maps_string(), maps.to_maps_string());
+	}
+
+	#[test]
+	fn region_end_is_exclusive() {
+		let maps = ProcMemoryMaps::parse_maps(MAPS).unwrap();
+		let region = maps.find_region_by_addr(0x55d0c0a02000).unwrap();
+		assert_eq!(region.perm, "r-xp");
+		assert!(region.contains(0x55d0c0a04fff));
+		assert!(!region.contains(0x55d0c0a05000));
+	}
+
+	#[test]
+	fn can_read_checks_permissions_and_bounds() {
+		let maps = ProcMemoryMaps::parse_maps(MAPS).unwrap();
+
+		assert!(maps.can_read(0x55d0c0a00000, 8).is_ok());
+		assert!(maps.can_read(0x55d0c1020ff8, 8).is_ok());
+		assert!(maps.can_read(0x55d0c1020ffc, 8).is_err());
+		assert!(maps.can_read(0x7f0000000010, 1).is_err());
+
+		let err = maps.can_read(0x1000, 1).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::OutOfBounds);
+		assert_eq!(err.addr(), Some(0x1000));
+	}
+
+	#[test]
+	fn can_write_checks_permissions_and_bounds() {
+		let maps = ProcMemoryMaps::parse_maps(MAPS).unwrap();
+
+		let err = maps.can_write(0x55d0c0a02000, 4).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::Protected);
+		assert_eq!(err.region(), Some((0x55d0c0a02000, 0x55d0c0a05000)));
+
+		assert!(maps.can_write(0x55d0c0a05000, 4).is_ok());
+		assert!(maps.can_write(0x55d0c0a05ffc, 4).is_ok());
+		assert!(maps.can_write(0x55d0c0a05ffd, 4).is_err());
+		assert!(maps.can_write(0x7f0000000000, 1).is_err());
+	}
+
+	#[test]
+	fn region_lookups() {
+		let maps = ProcMemoryMaps::parse_maps(MAPS).unwrap();
+
+		assert_eq!(maps.get_module_base("game"), Some(0x55d0c0a02000));
+		assert_eq!(
+			maps.get_module_probable_load_base("game"),
+			Some(0x55d0c0a00000)
+		);
+		assert_eq!(maps.get_heap_regions().len(), 1);
+		assert_eq!(
+			maps.get_thread_stack_region(4321, None).map(|r| r.start),
+			Some(0x7f0000001000)
+		);
+		assert_eq!(
+			maps.get_thread_stack_region(1, Some(0x7ffc00000100))
+				.map(|r| r.start),
+			Some(0x7ffc00000000)
+		);
+		assert!(maps.get_thread_stack_region(1, None).is_none());
+	}
+
+	#[test]
+	fn parse_smaps_fields() {
+		let smaps = "\
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                  12 kB
+Private_Dirty:         8 kB
+Swap:                  4 kB
+VmFlags: rd wr mr mw me ac sd
+7f0000000000-7f0000001000 ---p 00000000 00:00 0 
+Size:                  4 kB
+Rss:                   0 kB
+VmFlags: mr mw me ac sd
+";
+		let maps = ProcMemoryMaps::parse_smaps(smaps).unwrap();
+		assert!(maps.has_smaps());
+
+		let heap = maps.get_regions()[0].smaps.as_ref().unwrap();
+		assert_eq!(heap.rss, 12 * 1024);
+		assert_eq!(heap.private_dirty, 8 * 1024);
+		assert_eq!(heap.swap, 4 * 1024);
+		assert!(heap.has_vm_flag("wr"));
+		assert_eq!(maps.get_regions()[0].is_resident(), Some(true));
+		assert_eq!(maps.get_regions()[1].is_resident(), Some(false));
+	}
+}
@@ -0,0 +1,370 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::patterns::offsets::{OffsetScanner, parse_pattern},
+	maps::{ProcMemoryMaps, ProcMemoryRegion},
+	pod::Pod,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget, WriteProcessMemory},
+};
+
+/// An in-memory stand-in for a process, for testing code written against the memory traits
+/// without a live pid.
+///
+/// Regions are declared with `/proc/<pid>/maps` style permissions and pathnames, and reads,
+/// writes and scans honour them exactly like `ProcMem` does.
+#[derive(Clone)]
+pub struct MockProcess {
+	pub pid: u32,
+	maps: ProcMemoryMaps,
+	memory: Vec<Vec<u8>>,
+}
+
+impl Default for MockProcess {
+	fn default() -> Self {
+		Self::new(1)
+	}
+}
+
+impl MockProcess {
+	pub fn new(pid: u32) -> Self {
+		Self {
+			pid,
+			maps: ProcMemoryMaps::from_regions(Vec::new()),
+			memory: Vec::new(),
+		}
+	}
+
+	/// Adds a zero filled region of `size` bytes.
+	pub fn with_region(self, start: u64, size: usize, perm: &str, pathname: Option<&str>) -> Self {
+		self.with_region_data(start, vec![0u8; size], perm, pathname)
+	}
+
+	/// Adds a region whose contents are `data`.
+	///
+	/// # Panics
+	///
+	/// If the region overlaps an existing one.
+	pub fn with_region_data(
+		mut self,
+		start: u64,
+		data: Vec<u8>,
+		perm: &str,
+		pathname: Option<&str>,
+	) -> Self {
+		let end = start + data.len() as u64;
+		assert!(
+			!self
+				.maps
+				.get_regions()
+				.iter()
+				.any(|r| start < r.end && r.start < end),
+			"region {:#x}-{:#x} overlaps an existing region",
+			start,
+			end
+		);
+
+		let mut regions = self.maps.get_regions().to_vec();
+		regions.push(ProcMemoryRegion {
+			start,
+			end,
+			perm: perm.to_string(),
+			offset: 0,
+			dev: "00:00".to_string(),
+			inode: 0,
+			pathname: pathname.map(|p| p.to_string()),
+			smaps: None,
+		});
+		self.memory.push(data);
+
+		let mut order: Vec<usize> = (0..regions.len()).collect();
+		order.sort_by_key(|i| regions[*i].start);
+		let regions = order.iter().map(|i| regions[*i].clone()).collect();
+		let mut memory = std::mem::take(&mut self.memory);
+		self.memory = order
+			.iter()
+			.map(|i| std::mem::take(&mut memory[*i]))
+			.collect();
+		self.maps = ProcMemoryMaps::from_regions(regions);
+		self
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		&self.maps
+	}
+
+	/// Copies `bytes` to `addr` ignoring permissions, e.g. to set up read-only memory.
+	///
+	/// # Panics
+	///
+	/// If the bytes are not inside a single region.
+	pub fn poke(&mut self, addr: u64, bytes: &[u8]) {
+		let (index, offset) = self
+			.locate(addr)
+			.unwrap_or_else(|| panic!("{:#x} is not mapped", addr));
+		self.memory[index][offset..offset + bytes.len()].copy_from_slice(bytes);
+	}
+
+	/// Returns the contents of `len` bytes at `addr` ignoring permissions.
+	pub fn peek(&self, addr: u64, len: usize) -> Option<&[u8]> {
+		let (index, offset) = self.locate(addr)?;
+		self.memory[index].get(offset..offset + len)
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			let region = self.maps.get_thread_stack_region(tid, None).or_else(|| {
+				(tid == self.pid)
+					.then(|| {
+						self.maps
+							.find_regions_by_name_exact("[stack]")
+							.into_iter()
+							.next()
+					})
+					.flatten()
+			});
+			region.map(|r| (r.start, r.end))
+		})
+	}
+
+	fn locate(&self, addr: u64) -> Option<(usize, usize)> {
+		self.maps
+			.get_regions()
+			.iter()
+			.position(|r| r.contains(addr))
+			.map(|i| (i, (addr - self.maps.get_regions()[i].start) as usize))
+	}
+}
+
+impl ReadProcessMemory for MockProcess {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		self.maps.can_read(addr, size_of::<T>())?;
+
+		self.peek(addr, size_of::<T>())
+			.and_then(T::from_bytes)
+			.ok_or_else(|| Error::new(ErrorKind::OutOfBounds, format!("Address {}", addr)))
+	}
+
+	/// Reads across adjacent readable regions and stops at the first byte that is unmapped
+	/// or not readable, like a short read from `/proc/<pid>/mem`.
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		let mut done = 0;
+
+		while done < buf.len() {
+			let current = addr + done as u64;
+			let Some((index, offset)) = self.locate(current) else {
+				break;
+			};
+			if !self.maps.get_regions()[index].is_readable() {
+				break;
+			}
+
+			let data = &self.memory[index][offset..];
+			let len = data.len().min(buf.len() - done);
+			buf[done..done + len].copy_from_slice(&data[..len]);
+			done += len;
+		}
+
+		Ok(done)
+	}
+}
+
+impl WriteProcessMemory for MockProcess {
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
+		self.maps.can_write(addr, size_of::<T>())?;
+
+		let bytes = value.as_bytes();
+		let (index, offset) = self
+			.locate(addr)
+			.ok_or_else(|| Error::new(ErrorKind::OutOfBounds, format!("0x{:x}", addr)))?;
+		self.memory[index][offset..offset + bytes.len()].copy_from_slice(bytes);
+
+		Ok(())
+	}
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		self.maps.can_write(addr, buf.len())?;
+
+		let (index, offset) = self
+			.locate(addr)
+			.ok_or_else(|| Error::new(ErrorKind::OutOfBounds, format!("0x{:x}", addr)))?;
+		self.memory[index][offset..offset + buf.len()].copy_from_slice(buf);
+
+		Ok(())
+	}
+}
+
+impl ProcessMemoryPatternScan for MockProcess {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		self.scan_for_pattern_in(pattern, ScanTarget::HeapAndStack)
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		let pattern = parse_pattern(pattern).ok()?;
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn process() -> MockProcess {
+		MockProcess::new(100)
+			.with_region(0x1000, 0x1000, "r-xp", Some("/usr/bin/game"))
+			.with_region(0x2000, 0x1000, "rw-p", Some("/usr/bin/game"))
+			.with_region(0x10000, 0x2000, "rw-p", Some("[heap]"))
+			.with_region(0x20000, 0x1000, "---p", None)
+			.with_region(0x21000, 0x1000, "rw-p", None)
+			.with_region(0x7000_0000, 0x1000, "rw-p", Some("[stack]"))
+	}
+
+	#[test]
+	fn regions_are_sorted_by_start() {
+		let mock = MockProcess::new(1)
+			.with_region(0x5000, 0x100, "rw-p", None)
+			.with_region(0x1000, 0x100, "r--p", None);
+
+		let starts: Vec<u64> = mock
+			.get_maps()
+			.get_regions()
+			.iter()
+			.map(|r| r.start)
+			.collect();
+		assert_eq!(starts, vec![0x1000, 0x5000]);
+	}
+
+	#[test]
+	#[should_panic(expected = "overlaps")]
+	fn overlapping_regions_panic() {
+		let _ = MockProcess::new(1)
+			.with_region(0x1000, 0x1000, "rw-p", None)
+			.with_region(0x1800, 0x1000, "rw-p", None);
+	}
+
+	#[test]
+	fn read_write_roundtrip() {
+		let mut mock = process();
+		mock.write_value(0x10010, &0xdead_beef_u32).unwrap();
+		assert_eq!(mock.read_value::<u32>(0x10010).unwrap(), 0xdead_beef);
+		assert_eq!(
+			mock.peek(0x10010, 4).unwrap(),
+			&0xdead_beef_u32.to_le_bytes()
+		);
+	}
+
+	#[test]
+	fn write_to_read_only_region_fails() {
+		let mut mock = process();
+		assert!(mock.write_value(0x1010, &1u32).is_err());
+		assert_eq!(mock.peek(0x1010, 4).unwrap(), &[0, 0, 0, 0]);
+	}
+
+	#[test]
+	fn read_from_protected_region_fails() {
+		let mut mock = process();
+		assert!(mock.read_value::<u8>(0x20000).is_err());
+
+		let mut buf = [0u8; 16];
+		assert_eq!(mock.read_bytes(0x20000, &mut buf).unwrap(), 0);
+	}
+
+	#[test]
+	fn value_spanning_two_regions_is_rejected() {
+		let mut mock = process();
+		assert!(mock.read_value::<u64>(0x1ffc).is_err());
+		assert!(mock.write_value(0x1ffc, &0u64).is_err());
+	}
+
+	#[test]
+	fn write_at_region_start_uses_that_region() {
+		// 0x2000 is the exclusive end of the read-only text and the start of the data.
+		let mut mock = process();
+		mock.write_value(0x2000, &7u32).unwrap();
+		assert_eq!(mock.read_value::<u32>(0x2000).unwrap(), 7);
+	}
+
+	#[test]
+	fn read_bytes_crosses_adjacent_regions_and_stops_at_gaps() {
+		let mut mock = process();
+		mock.poke(0x1ffe, &[1, 2]);
+		mock.poke(0x2000, &[3, 4]);
+
+		let mut buf = [0u8; 4];
+		assert_eq!(mock.read_bytes(0x1ffe, &mut buf).unwrap(), 4);
+		assert_eq!(buf, [1, 2, 3, 4]);
+
+		let mut buf = [0u8; 32];
+		assert_eq!(mock.read_bytes(0x2ff0, &mut buf).unwrap(), 16);
+	}
+
+	#[test]
+	fn read_bytes_stops_at_protected_region() {
+		let mut mock = process();
+		let mut buf = [0u8; 64];
+		assert_eq!(mock.read_bytes(0x1ffe0, &mut buf).unwrap(), 0);
+		assert_eq!(mock.read_bytes(0x11ff0, &mut buf).unwrap(), 16);
+	}
+
+	#[test]
+	fn pointer_chain_walk() {
+		let mut mock = process();
+		// game+0x2010 -> heap+0x100 -> heap+0x800, value at +0x18
+		mock.write_value(0x2010, &0x10100_u64).unwrap();
+		mock.write_value(0x10108, &0x10800_u64).unwrap();
+		mock.write_value(0x10818, &1337_u32).unwrap();
+
+		let base = mock.get_maps().get_module_base("game").unwrap();
+		let first: u64 = mock.read_value(base + 0x1010).unwrap();
+		let second: u64 = mock.read_value(first + 0x8).unwrap();
+		assert_eq!(mock.read_value::<u32>(second + 0x18).unwrap(), 1337);
+	}
+
+	#[test]
+	fn scan_targets() {
+		let mut mock = process();
+		let needle = [0xde, 0xad, 0xbe, 0xef];
+		mock.poke(0x10020, &needle);
+		mock.poke(0x7000_0100, &needle);
+		mock.poke(0x21000, &needle);
+		mock.poke(0x1100, &needle);
+
+		let pattern = "DE AD BE EF";
+		assert_eq!(
+			mock.scan_for_pattern(pattern).unwrap(),
+			vec![0x10020, 0x7000_0100]
+		);
+		assert_eq!(
+			mock.scan_for_pattern_in(pattern, ScanTarget::Anonymous)
+				.unwrap(),
+			vec![0x10020, 0x21000, 0x7000_0100]
+		);
+		assert_eq!(
+			mock.scan_for_pattern_in(pattern, ScanTarget::Module("game"))
+				.unwrap(),
+			vec![0x1100]
+		);
+		assert_eq!(
+			mock.scan_for_pattern_in(pattern, ScanTarget::ThreadStack(100))
+				.unwrap(),
+			vec![0x7000_0100]
+		);
+		assert!(
+			mock.scan_for_pattern_in(pattern, ScanTarget::ThreadStack(101))
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn scan_with_wildcards() {
+		let mut mock = process();
+		mock.poke(0x10040, &[0x48, 0x8b, 0x05, 0x11, 0x22, 0x33, 0x44, 0xc3]);
+
+		assert_eq!(
+			mock.scan_for_pattern_in("48 8B 05 ?? ?? ?? ?? C3", ScanTarget::HeapAndStack)
+				.unwrap(),
+			vec![0x10040]
+		);
+	}
+}
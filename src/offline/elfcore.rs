@@ -0,0 +1,346 @@
+use std::{fs::File, os::unix::fs::FileExt, path::Path};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::{
+		elf::{
+			ELF64_EHDR_SIZE, ET_CORE, ElfHeader, LIME_NOTE_NAME, NT_FILE, NT_LIME_MAPS, NT_PRPSINFO,
+			NT_PRSTATUS, PF_R, PF_W, PF_X, PT_LOAD, PT_NOTE, ProgramHeader, le_u32, le_u64,
+			parse_notes,
+		},
+		patterns::offsets::{OffsetScanner, parse_pattern},
+	},
+	maps::{ProcMemoryMaps, ProcMemoryRegion},
+	pod::Pod,
+	process::regs::ThreadRegisters,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget},
+};
+
+/// Offset of `pr_pid` in `struct elf_prstatus`.
+const PRSTATUS_PID_OFFSET: usize = 32;
+/// Offset of `pr_reg` in `struct elf_prstatus`.
+const PRSTATUS_REG_OFFSET: usize = 112;
+/// Offset of `pr_pid` in `struct elf_prpsinfo`.
+const PRPSINFO_PID_OFFSET: usize = 24;
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+	vaddr: u64,
+	memsz: u64,
+	offset: u64,
+	filesz: u64,
+}
+
+/// Read-only access to the memory of a process captured in an ELF core file, either written
+/// by the kernel or by `dump::elfcore`.
+///
+/// Regions come from the `PT_LOAD` headers. Their names, offsets and permissions come from the
+/// `NT_LIME_MAPS` note of files lime wrote, and otherwise from the `NT_FILE` note, which only
+/// lists file mappings; then the region holding the first thread's stack pointer is named
+/// `[stack]`. Memory that is not stored in the file (a `p_filesz` shorter than `p_memsz`) is
+/// treated as unreadable. There is no smaps data, so `ScanTarget::Filtered` never matches.
+pub struct CoreFile {
+	file: File,
+	maps: ProcMemoryMaps,
+	segments: Vec<Segment>,
+	threads: Vec<ThreadRegisters>,
+	pid: Option<u32>,
+}
+
+impl CoreFile {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+		let path = path.as_ref();
+		let file = File::open(path)
+			.map_err(|e| Error::os(ErrorKind::ReadFailed, format!("{}", path.display()), e))?;
+		let file_len = file
+			.metadata()
+			.map_err(|e| Error::os(ErrorKind::ReadFailed, format!("{}", path.display()), e))?
+			.len();
+
+		let mut raw = [0u8; ELF64_EHDR_SIZE];
+		read_exact_at(&file, &mut raw, 0)?;
+		let header = ElfHeader::parse(&raw)?;
+		if header.e_type != ET_CORE {
+			return Err(Error::new(
+				ErrorKind::InvalidElf,
+				format!("{} is not a core file", path.display()),
+			));
+		}
+
+		let mut phdrs = Vec::with_capacity(header.e_phnum as usize);
+		let mut raw = vec![0u8; header.e_phentsize as usize];
+		for i in 0..header.e_phnum as u64 {
+			let offset = header
+				.e_phoff
+				.checked_add(i * header.e_phentsize as u64)
+				.ok_or_else(|| invalid(format!("program headers at {:#x}", header.e_phoff)))?;
+			read_exact_at(&file, &mut raw, offset)?;
+			phdrs.push(ProgramHeader::parse(&raw)?);
+		}
+
+		let mut file_mappings = Vec::new();
+		let mut lime_maps = None;
+		let mut threads = Vec::new();
+		let mut pid = None;
+
+		for phdr in phdrs.iter().filter(|p| p.p_type == PT_NOTE) {
+			if phdr
+				.p_offset
+				.checked_add(phdr.p_filesz)
+				.is_none_or(|end| end > file_len)
+			{
+				return Err(Error::new(
+					ErrorKind::InvalidElf,
+					format!(
+						"note segment at {:#x} of {} bytes is past the end of the file",
+						phdr.p_offset, phdr.p_filesz
+					),
+				));
+			}
+			let mut notes = vec![0u8; phdr.p_filesz as usize];
+			read_exact_at(&file, &mut notes, phdr.p_offset)?;
+
+			for note in parse_notes(&notes) {
+				if note.name == LIME_NOTE_NAME && note.n_type == NT_LIME_MAPS {
+					let text = String::from_utf8_lossy(note.desc);
+					lime_maps = Some(ProcMemoryMaps::parse_maps(&text)?);
+					continue;
+				}
+				if note.name != "CORE" {
+					continue;
+				}
+				match note.n_type {
+					NT_PRSTATUS if note.desc.len() > PRSTATUS_REG_OFFSET => {
+						let tid = le_u32(note.desc, PRSTATUS_PID_OFFSET);
+						if let Some(regs) =
+							ThreadRegisters::from_bytes(tid, &note.desc[PRSTATUS_REG_OFFSET..])
+						{
+							threads.push(regs);
+						}
+					}
+					NT_PRPSINFO if note.desc.len() >= PRPSINFO_PID_OFFSET + 4 => {
+						pid = Some(le_u32(note.desc, PRPSINFO_PID_OFFSET));
+					}
+					NT_FILE => file_mappings = parse_file_note(note.desc)?,
+					_ => {}
+				}
+			}
+		}
+
+		let mut segments = Vec::new();
+		let mut regions = Vec::new();
+		for phdr in phdrs
+			.iter()
+			.filter(|p| p.p_type == PT_LOAD && p.p_memsz > 0)
+		{
+			let (Some(end), Some(_)) = (
+				phdr.p_vaddr.checked_add(phdr.p_memsz),
+				phdr.p_offset.checked_add(phdr.p_filesz),
+			) else {
+				return Err(invalid(format!(
+					"load segment at {:#x} of {} bytes wraps around",
+					phdr.p_vaddr, phdr.p_memsz
+				)));
+			};
+			segments.push(Segment {
+				vaddr: phdr.p_vaddr,
+				memsz: phdr.p_memsz,
+				offset: phdr.p_offset,
+				filesz: phdr.p_filesz,
+			});
+
+			let start = phdr.p_vaddr;
+			let dumped = lime_maps.as_ref().and_then(|maps| {
+				maps.get_regions()
+					.iter()
+					.find(|r| r.start == start && r.end == end)
+			});
+			if let Some(region) = dumped {
+				regions.push(region.clone());
+				continue;
+			}
+
+			let mapping = file_mappings.iter().find(|m| m.0 == start);
+			regions.push(ProcMemoryRegion {
+				start,
+				end,
+				perm: perm_string(phdr.p_flags),
+				offset: mapping.map_or(0, |m| m.2),
+				dev: "00:00".to_string(),
+				inode: 0,
+				pathname: mapping.map(|m| m.3.clone()),
+				smaps: None,
+			});
+		}
+
+		// Kernel cores do not name anonymous regions, but the main thread's stack can be found.
+		if lime_maps.is_none()
+			&& let Some(sp) = threads.first().map(|t| t.sp())
+			&& let Some(stack) = regions
+				.iter_mut()
+				.find(|r| r.contains(sp) && r.pathname.is_none())
+		{
+			stack.pathname = Some("[stack]".to_string());
+		}
+
+		// The main thread's prstatus comes first, but fall back to it if prpsinfo is missing.
+		let pid = pid.or_else(|| threads.first().map(|t| t.tid));
+
+		Ok(Self {
+			file,
+			maps: ProcMemoryMaps::from_regions(regions),
+			segments,
+			threads,
+			pid,
+		})
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		&self.maps
+	}
+
+	pub fn threads(&self) -> &[ThreadRegisters] {
+		&self.threads
+	}
+
+	pub fn pid(&self) -> Option<u32> {
+		self.pid
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			let sp = self.threads.iter().find(|t| t.tid == tid).map(|t| t.sp());
+			self.maps
+				.get_thread_stack_region(tid, sp)
+				.map(|r| (r.start, r.end))
+		})
+	}
+
+	fn segment_for(&self, addr: u64) -> Option<&Segment> {
+		self.segments
+			.iter()
+			.find(|s| addr >= s.vaddr && addr - s.vaddr < s.memsz)
+	}
+}
+
+impl ReadProcessMemory for CoreFile {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		self.maps.can_read(addr, size_of::<T>())?;
+
+		let mut value = T::zeroed();
+		let n = self.read_bytes(addr, value.as_bytes_mut())?;
+		if n < size_of::<T>() {
+			return Err(Error::new(
+				ErrorKind::ReadFailed,
+				format!("{:#x} is not stored in the core file", addr),
+			));
+		}
+
+		Ok(value)
+	}
+
+	/// Reads across adjacent segments and stops at the first byte that is not in the file.
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		let mut done = 0;
+
+		while done < buf.len() {
+			let Some(current) = addr.checked_add(done as u64) else {
+				break;
+			};
+			let Some(segment) = self.segment_for(current).copied() else {
+				break;
+			};
+
+			let rel = current - segment.vaddr;
+			if rel >= segment.filesz {
+				break;
+			}
+
+			let len = ((segment.filesz - rel) as usize).min(buf.len() - done);
+			let n = self
+				.file
+				.read_at(&mut buf[done..done + len], segment.offset + rel)
+				.unwrap_or(0);
+			done += n;
+
+			if n < len {
+				break;
+			}
+		}
+
+		Ok(done)
+	}
+}
+
+impl ProcessMemoryPatternScan for CoreFile {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		self.scan_for_pattern_in(pattern, ScanTarget::HeapAndStack)
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		let pattern = parse_pattern(pattern).ok()?;
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
+	}
+}
+
+/// `(start, end, file offset in bytes, path)` for every entry of an `NT_FILE` note.
+fn parse_file_note(desc: &[u8]) -> Result<Vec<(u64, u64, u64, String)>, Error> {
+	if desc.len() < 16 {
+		return Ok(Vec::new());
+	}
+
+	let entries = le_u64(desc, 0);
+	let page_size = le_u64(desc, 8);
+	let count = usize::try_from(entries)
+		.ok()
+		.filter(|count| {
+			count
+				.checked_mul(24)
+				.and_then(|size| size.checked_add(16))
+				.is_some_and(|end| end <= desc.len())
+		})
+		.ok_or_else(|| invalid(format!("NT_FILE note with {} entries", entries)))?;
+	let names_start = 16 + count * 24;
+
+	let names = desc[names_start..]
+		.split(|b| *b == 0)
+		.map(|n| String::from_utf8_lossy(n).into_owned());
+
+	(0..count)
+		.map(|i| 16 + i * 24)
+		.zip(names)
+		.map(|(o, name)| {
+			let offset = le_u64(desc, o + 16)
+				.checked_mul(page_size)
+				.ok_or_else(|| invalid(format!("NT_FILE offset of {}", name)))?;
+			Ok((le_u64(desc, o), le_u64(desc, o + 8), offset, name))
+		})
+		.collect()
+}
+
+fn invalid(message: String) -> Error {
+	Error::new(ErrorKind::InvalidElf, message)
+}
+
+fn perm_string(flags: u32) -> String {
+	let mut perm = String::with_capacity(4);
+	perm.push(if flags & PF_R != 0 { 'r' } else { '-' });
+	perm.push(if flags & PF_W != 0 { 'w' } else { '-' });
+	perm.push(if flags & PF_X != 0 { 'x' } else { '-' });
+	perm.push('p');
+	perm
+}
+
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> Result<(), Error> {
+	file.read_exact_at(buf, offset).map_err(|e| {
+		Error::os(
+			ErrorKind::ReadFailed,
+			format!("core file at {:#x}", offset),
+			e,
+		)
+	})?;
+	Ok(())
+}
@@ -0,0 +1,208 @@
+use std::{
+	fs::File,
+	os::unix::fs::FileExt,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::{
+		lime::{LIME_HEADER_SIZE, LimeRangeHeader},
+		patterns::offsets::{OffsetScanner, parse_pattern},
+	},
+	maps::{ProcMemoryMaps, ProcMemoryRegion},
+	pod::Pod,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget},
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Range {
+	start: u64,
+	end: u64,
+	offset: u64,
+}
+
+/// Read-only access to a LiME memory image: a sequence of 32-byte range headers, each
+/// followed by the bytes of that range.
+///
+/// Plain LiME images carry no permissions or names, so every range becomes an anonymous
+/// `r--p` region (scan those with `ScanTarget::Anonymous`). Images written by `dump::lime`
+/// have a `<image>.maps` file next to them in `/proc/<pid>/maps` format; `open` picks it up
+/// and uses its metadata for matching ranges.
+pub struct LimeImage {
+	file: File,
+	ranges: Vec<Range>,
+	maps: ProcMemoryMaps,
+}
+
+impl LimeImage {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+		let sidecar = maps_sidecar_path(path.as_ref());
+		let maps = std::fs::read_to_string(&sidecar)
+			.ok()
+			.and_then(|content| ProcMemoryMaps::parse_maps(&content).ok());
+
+		Self::open_with_maps(path, maps)
+	}
+
+	/// Opens an image, taking region metadata from `maps` instead of the sidecar file.
+	pub fn open_with_maps(
+		path: impl AsRef<Path>,
+		maps: Option<ProcMemoryMaps>,
+	) -> Result<Self, Error> {
+		let path = path.as_ref();
+		let file = File::open(path)
+			.map_err(|e| Error::os(ErrorKind::ReadFailed, format!("{}", path.display()), e))?;
+		let file_len = file
+			.metadata()
+			.map_err(|e| Error::os(ErrorKind::ReadFailed, format!("{}", path.display()), e))?
+			.len();
+
+		let mut ranges = Vec::new();
+		let mut offset = 0;
+		let mut raw = [0u8; LIME_HEADER_SIZE];
+
+		while offset + LIME_HEADER_SIZE as u64 <= file_len {
+			file.read_exact_at(&mut raw, offset)
+				.map_err(|e| Error::os(ErrorKind::ReadFailed, format!("{}", path.display()), e))?;
+			let header = LimeRangeHeader::parse(&raw)?;
+			let data_offset = offset + LIME_HEADER_SIZE as u64;
+
+			let (Some(end), Some(data_end)) = (
+				header.end(),
+				header.len().and_then(|len| data_offset.checked_add(len)),
+			) else {
+				return Err(Error::new(
+					ErrorKind::InvalidLime,
+					format!(
+						"range {:#x}-{:#x} does not fit in the address space",
+						header.s_addr, header.e_addr
+					),
+				));
+			};
+			if data_end > file_len {
+				return Err(Error::new(
+					ErrorKind::OutOfBounds,
+					format!(
+						"range {:#x}-{:#x} extends past the end of {}",
+						header.s_addr,
+						header.e_addr,
+						path.display()
+					),
+				));
+			}
+
+			ranges.push(Range {
+				start: header.s_addr,
+				end,
+				offset: data_offset,
+			});
+			offset = data_end;
+		}
+
+		let regions = ranges
+			.iter()
+			.map(|range| {
+				maps.as_ref()
+					.and_then(|m| m.get_regions().iter().find(|r| r.start == range.start))
+					.filter(|r| r.end == range.end)
+					.cloned()
+					.unwrap_or_else(|| ProcMemoryRegion {
+						start: range.start,
+						end: range.end,
+						perm: "r--p".to_string(),
+						offset: 0,
+						dev: "00:00".to_string(),
+						inode: 0,
+						pathname: None,
+						smaps: None,
+					})
+			})
+			.collect();
+
+		Ok(Self {
+			file,
+			ranges,
+			maps: ProcMemoryMaps::from_regions(regions),
+		})
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		&self.maps
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			self.maps
+				.get_thread_stack_region(tid, None)
+				.map(|r| (r.start, r.end))
+		})
+	}
+
+	fn range_for(&self, addr: u64) -> Option<&Range> {
+		self.ranges.iter().find(|r| addr >= r.start && addr < r.end)
+	}
+}
+
+pub fn maps_sidecar_path(image: &Path) -> PathBuf {
+	let mut name = image.as_os_str().to_owned();
+	name.push(".maps");
+	PathBuf::from(name)
+}
+
+impl ReadProcessMemory for LimeImage {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		self.maps.can_read(addr, size_of::<T>())?;
+
+		let mut value = T::zeroed();
+		let n = self.read_bytes(addr, value.as_bytes_mut())?;
+		if n < size_of::<T>() {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				format!("{:#x} is not in the image", addr),
+			));
+		}
+
+		Ok(value)
+	}
+
+	/// Reads across adjacent ranges and stops at the first byte that is not in the image.
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		let mut done = 0;
+
+		while done < buf.len() {
+			let current = addr + done as u64;
+			let Some(range) = self.range_for(current).copied() else {
+				break;
+			};
+
+			let rel = current - range.start;
+			let len = ((range.end - current) as usize).min(buf.len() - done);
+			let n = self
+				.file
+				.read_at(&mut buf[done..done + len], range.offset + rel)
+				.unwrap_or(0);
+			done += n;
+
+			if n < len {
+				break;
+			}
+		}
+
+		Ok(done)
+	}
+}
+
+impl ProcessMemoryPatternScan for LimeImage {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		self.scan_for_pattern_in(pattern, ScanTarget::HeapAndStack)
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		let pattern = parse_pattern(pattern).ok()?;
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
+	}
+}
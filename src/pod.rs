@@ -0,0 +1,185 @@
+//! Types that can be copied to and from another process' memory as raw bytes.
+
+use std::fmt;
+
+/// Plain old data: any `size_of::<Self>()` bytes are a valid value.
+///
+/// `read_value` and `write_value` are bounded by this, so reading memory the target controls
+/// can never produce an invalid `bool`, `char`, enum or reference.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of the type, it must not have padding bytes and it
+/// must not contain pointers or references that Rust would follow. `#[repr(C)]` structs of
+/// `Pod` fields without padding qualify:
+///
+/// ```
+/// #[derive(Clone, Copy)]
+/// #[repr(C)]
+/// struct Vec3 {
+///     x: f32,
+///     y: f32,
+///     z: f32,
+/// }
+///
+/// unsafe impl lime::pod::Pod for Vec3 {}
+/// ```
+pub unsafe trait Pod: Copy + 'static {
+	fn zeroed() -> Self {
+		// SAFETY: all zeroes is one of the bit patterns `Pod` types accept.
+		unsafe { std::mem::zeroed() }
+	}
+
+	fn as_bytes(&self) -> &[u8] {
+		// SAFETY: `Pod` types have no padding, so every byte is initialized.
+		unsafe { std::slice::from_raw_parts((self as *const Self).cast::<u8>(), size_of::<Self>()) }
+	}
+
+	fn as_bytes_mut(&mut self) -> &mut [u8] {
+		// SAFETY: as for `as_bytes`, and any bytes written leave a valid value.
+		unsafe {
+			std::slice::from_raw_parts_mut((self as *mut Self).cast::<u8>(), size_of::<Self>())
+		}
+	}
+
+	/// The value in the first `size_of::<Self>()` bytes, `None` if there are fewer.
+	fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let mut value = Self::zeroed();
+		let len = size_of::<Self>();
+		value.as_bytes_mut().copy_from_slice(bytes.get(..len)?);
+		Some(value)
+	}
+}
+
+macro_rules! impl_pod {
+	($($t:ty),*) => {
+		$(unsafe impl Pod for $t {})*
+	};
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Numbers whose bytes can be swapped, for `Le` and `Be`.
+pub trait Endian: Pod {
+	fn swap_bytes(self) -> Self;
+}
+
+macro_rules! impl_endian {
+	($($t:ty),*) => {
+		$(
+			impl Endian for $t {
+				fn swap_bytes(self) -> Self {
+					<$t>::swap_bytes(self)
+				}
+			}
+		)*
+	};
+}
+
+impl_endian!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Endian for f32 {
+	fn swap_bytes(self) -> Self {
+		f32::from_bits(self.to_bits().swap_bytes())
+	}
+}
+
+impl Endian for f64 {
+	fn swap_bytes(self) -> Self {
+		f64::from_bits(self.to_bits().swap_bytes())
+	}
+}
+
+macro_rules! byte_order {
+	($name:ident, $endian:literal, $doc:literal) => {
+		#[doc = $doc]
+		#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+		#[repr(transparent)]
+		pub struct $name<T>(T);
+
+		// SAFETY: a transparent wrapper of a `Pod` type.
+		unsafe impl<T: Pod> Pod for $name<T> {}
+
+		impl<T: Endian> $name<T> {
+			pub fn new(value: T) -> Self {
+				Self(Self::convert(value))
+			}
+
+			pub fn get(self) -> T {
+				Self::convert(self.0)
+			}
+
+			fn convert(value: T) -> T {
+				if cfg!(target_endian = $endian) {
+					value
+				} else {
+					value.swap_bytes()
+				}
+			}
+		}
+
+		impl<T: Endian> From<T> for $name<T> {
+			fn from(value: T) -> Self {
+				Self::new(value)
+			}
+		}
+
+		impl<T: Endian + fmt::Debug> fmt::Debug for $name<T> {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				f.debug_tuple(stringify!($name)).field(&self.get()).finish()
+			}
+		}
+	};
+}
+
+byte_order!(
+	Le,
+	"little",
+	"A number stored little endian in the target, whatever the host's byte order."
+);
+byte_order!(
+	Be,
+	"big",
+	"A number stored big endian in the target, like network protocols and some file formats."
+);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		mock::process::MockProcess,
+		traits::{ReadProcessMemory, WriteProcessMemory},
+	};
+
+	#[test]
+	fn bytes_round_trip() {
+		let value = [0x1122u16, 0x3344];
+		assert_eq!(<[u16; 2]>::from_bytes(value.as_bytes()), Some(value));
+		assert_eq!(u32::from_bytes(&[1, 2, 3]), None);
+		assert_eq!(u8::from_bytes(&[7, 8]), Some(7));
+	}
+
+	#[test]
+	fn byte_order_wrappers() {
+		assert_eq!(Be::new(0x1234u16).as_bytes(), [0x12, 0x34]);
+		assert_eq!(Le::new(0x1234u16).as_bytes(), [0x34, 0x12]);
+		assert_eq!(Be::<u32>::from_bytes(&[0, 0, 1, 0]).unwrap().get(), 0x100);
+		assert_eq!(Be::new(1.5f32).get(), 1.5);
+		assert_eq!(format!("{:?}", Be::new(7u8)), "Be(7)");
+	}
+
+	#[test]
+	fn processes_read_and_write_wrapped_values() {
+		let mut process = MockProcess::new(1).with_region(0x1000, 0x1000, "rw-p", None);
+		process.poke(0x1000, &[0xde, 0xad, 0xbe, 0xef]);
+		assert_eq!(
+			process.read_value::<Be<u32>>(0x1000).unwrap().get(),
+			0xdeadbeef
+		);
+
+		process.write_value(0x1004, &Be::new(0x0102u16)).unwrap();
+		assert_eq!(process.peek(0x1004, 2), Some(&[1u8, 2][..]));
+	}
+}
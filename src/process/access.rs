@@ -0,0 +1,145 @@
+//! Predicts whether we may access another process' memory at all. `/proc/<pid>/mem`,
+//! `process_vm_readv` and ptrace all go through the same kernel check
+//! (`PTRACE_MODE_ATTACH`), including Yama's.
+
+use crate::{
+	errors::{Error, ErrorKind},
+	process::stat::ProcStat,
+};
+
+const YAMA_PTRACE_SCOPE: &str = "/proc/sys/kernel/yama/ptrace_scope";
+const CAP_SYS_PTRACE: u32 = 19;
+
+/// Yama's `kernel.yama.ptrace_scope`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtraceScope {
+	/// 0: any process of the same user.
+	Classic,
+	/// 1: only descendants, or processes that allowed us with `PR_SET_PTRACER`.
+	Restricted,
+	/// 2: only with `CAP_SYS_PTRACE`.
+	AdminOnly,
+	/// 3: nobody, not even root.
+	NoAttach,
+}
+
+impl PtraceScope {
+	/// `None` when Yama is not enabled, which behaves like `Classic`.
+	pub fn read() -> Option<Self> {
+		let content = std::fs::read_to_string(YAMA_PTRACE_SCOPE).ok()?;
+		match content.trim() {
+			"0" => Some(PtraceScope::Classic),
+			"1" => Some(PtraceScope::Restricted),
+			"2" => Some(PtraceScope::AdminOnly),
+			"3" => Some(PtraceScope::NoAttach),
+			_ => None,
+		}
+	}
+}
+
+pub fn has_cap_sys_ptrace() -> bool {
+	std::fs::read_to_string("/proc/self/status")
+		.ok()
+		.and_then(|status| {
+			status
+				.lines()
+				.find_map(|l| l.strip_prefix("CapEff:"))
+				.and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+		})
+		.is_some_and(|caps| caps & (1 << CAP_SYS_PTRACE) != 0)
+}
+
+/// Real, effective and saved uid of `pid`.
+fn process_uids(pid: u32) -> Result<[u32; 3], Error> {
+	let status = std::fs::read_to_string(format!("/proc/{}/status", pid))
+		.map_err(|e| Error::os(ErrorKind::InvalidPid, format!("{}", pid), e))?;
+
+	// "Uid:\t<real>\t<effective>\t<saved>\t<fs>"
+	let uids: Vec<u32> = status
+		.lines()
+		.find_map(|l| l.strip_prefix("Uid:"))
+		.map(|l| {
+			l.split_whitespace()
+				.filter_map(|u| u.parse().ok())
+				.collect()
+		})
+		.unwrap_or_default();
+
+	match uids[..] {
+		[real, effective, saved, ..] => Ok([real, effective, saved]),
+		_ => Err(Error::new(
+			ErrorKind::Parse,
+			format!("Missing Uid in /proc/{}/status", pid),
+		)),
+	}
+}
+
+fn is_descendant(pid: u32, ancestor: u32) -> bool {
+	let mut current = pid;
+	while current > 1 {
+		let Ok(stat) = ProcStat::read(&format!("/proc/{}/stat", current)) else {
+			return false;
+		};
+		if stat.ppid == ancestor {
+			return true;
+		}
+		current = stat.ppid;
+	}
+	false
+}
+
+/// Returns why accessing `pid` would be denied, if it would be.
+///
+/// This is a prediction: a target that called `prctl(PR_SET_PTRACER)`, or an LSM other than
+/// Yama, can make the real answer differ.
+pub fn check_access(pid: u32) -> Result<(), Error> {
+	let uids = process_uids(pid)?;
+	let own_pid = std::process::id();
+	if pid == own_pid {
+		return Ok(());
+	}
+
+	let scope = PtraceScope::read();
+	let privileged = has_cap_sys_ptrace();
+
+	if scope == Some(PtraceScope::NoAttach) {
+		return Err(Error::new(
+			ErrorKind::AccessDenied,
+			format!(
+				"{} is 3, attaching to other processes is disabled until reboot",
+				YAMA_PTRACE_SCOPE
+			),
+		));
+	}
+
+	if privileged {
+		return Ok(());
+	}
+
+	if scope == Some(PtraceScope::AdminOnly) {
+		return Err(Error::new(
+			ErrorKind::AccessDenied,
+			format!("{} is 2, CAP_SYS_PTRACE is required", YAMA_PTRACE_SCOPE),
+		));
+	}
+
+	let euid = unsafe { libc::geteuid() };
+	if uids.iter().any(|uid| *uid != euid) {
+		return Err(Error::new(
+			ErrorKind::AccessDenied,
+			format!("pid {} runs as uid {}, we are uid {}", pid, uids[1], euid),
+		));
+	}
+
+	if scope == Some(PtraceScope::Restricted) && !is_descendant(pid, own_pid) {
+		return Err(Error::new(
+			ErrorKind::AccessDenied,
+			format!(
+				"{} is 1 and pid {} is not our descendant; run with CAP_SYS_PTRACE or have the target call prctl(PR_SET_PTRACER)",
+				YAMA_PTRACE_SCOPE, pid
+			),
+		));
+	}
+
+	Ok(())
+}
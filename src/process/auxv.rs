@@ -0,0 +1,71 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::elf::{ElfHeader, le_u64},
+	maps::ProcMemoryMaps,
+};
+
+pub const AT_NULL: u64 = 0;
+pub const AT_PHDR: u64 = 3;
+pub const AT_PAGESZ: u64 = 6;
+pub const AT_BASE: u64 = 7;
+pub const AT_ENTRY: u64 = 9;
+
+/// Reads the ELF auxiliary vector of a process as `(type, value)` pairs.
+pub fn read_auxv(pid: u32) -> Result<Vec<(u64, u64)>, Error> {
+	let path = format!("/proc/{}/auxv", pid);
+	let raw = std::fs::read(&path)
+		.map_err(|e| Error::os(ErrorKind::InvalidPid, format!("Failed to read {}", path), e))?;
+
+	let mut entries = Vec::new();
+	for chunk in raw.chunks_exact(16) {
+		let key = le_u64(chunk, 0);
+		if key == AT_NULL {
+			break;
+		}
+		entries.push((key, le_u64(chunk, 8)));
+	}
+
+	Ok(entries)
+}
+
+pub fn get_auxv_value(pid: u32, key: u64) -> Result<Option<u64>, Error> {
+	Ok(read_auxv(pid)?
+		.into_iter()
+		.find(|(k, _)| *k == key)
+		.map(|(_, v)| v))
+}
+
+/// Resolves the runtime address of the program entry point. Prefers `AT_ENTRY` and falls back
+/// to `e_entry` from the executable, relocated by its load base for PIE binaries.
+pub fn resolve_entry_point(pid: u32) -> Result<u64, Error> {
+	if let Ok(Some(entry)) = get_auxv_value(pid, AT_ENTRY) {
+		return Ok(entry);
+	}
+
+	let exe_path = format!("/proc/{}/exe", pid);
+	let header = ElfHeader::read_from_file(&exe_path)?;
+
+	if !header.is_position_independent() {
+		return Ok(header.e_entry);
+	}
+
+	let exe = std::fs::read_link(&exe_path).map_err(|e| {
+		Error::os(
+			ErrorKind::InvalidPid,
+			format!("Failed to read {}", exe_path),
+			e,
+		)
+	})?;
+	let exe = exe.to_string_lossy();
+
+	let maps = ProcMemoryMaps::new(pid)?;
+	let base = maps
+		.find_regions_by_name_exact(&exe)
+		.iter()
+		.filter(|r| r.offset == 0)
+		.map(|r| r.start)
+		.min()
+		.ok_or_else(|| Error::new(ErrorKind::InvalidAddress, format!("{} is not mapped", exe)))?;
+
+	Ok(base + header.e_entry)
+}
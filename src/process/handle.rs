@@ -0,0 +1,249 @@
+#[cfg(feature = "procmem")]
+use crate::procmem::{
+	pagemap::{PM_PRESENT, PM_SWAPPED},
+	procmem::ProcMem,
+	scan::{PagedScan, scan_pages_for_pattern},
+};
+#[cfg(feature = "procvm")]
+use crate::procvm::procvm::ProcVm;
+#[cfg(feature = "ptrace")]
+use crate::ptrace::mem::PtraceMem;
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::patterns::offsets::{OffsetScanner, RangeCoverage, parse_pattern},
+	maps::ProcMemoryMaps,
+	pod::Pod,
+	process::access::check_access,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget, WriteProcessMemory},
+};
+
+/// How a `Process` accesses memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+	/// `process_vm_readv`/`process_vm_writev`
+	ProcVm,
+	/// `/proc/<pid>/mem`
+	ProcMem,
+	/// `PTRACE_PEEKDATA`/`PTRACE_POKEDATA`, stops the main thread while open
+	Ptrace,
+}
+
+impl Backend {
+	/// The order `Process::open` tries backends in.
+	pub const PREFERENCE: [Backend; 3] = [Backend::ProcVm, Backend::ProcMem, Backend::Ptrace];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Backend::ProcVm => "process_vm",
+			Backend::ProcMem => "procmem",
+			Backend::Ptrace => "ptrace",
+		}
+	}
+
+	/// Whether support for the backend was compiled in.
+	pub fn is_enabled(&self) -> bool {
+		match self {
+			Backend::ProcVm => cfg!(feature = "procvm"),
+			Backend::ProcMem => cfg!(feature = "procmem"),
+			Backend::Ptrace => cfg!(feature = "ptrace"),
+		}
+	}
+}
+
+enum Inner {
+	#[cfg(feature = "procvm")]
+	ProcVm(ProcVm),
+	#[cfg(feature = "procmem")]
+	ProcMem(ProcMem),
+	#[cfg(feature = "ptrace")]
+	Ptrace(PtraceMem),
+}
+
+/// Calls `$body` with the backend behind `$inner` bound to `$b`.
+macro_rules! with_backend {
+	($inner:expr, $b:ident => $body:expr) => {
+		match $inner {
+			#[cfg(feature = "procvm")]
+			Inner::ProcVm($b) => $body,
+			#[cfg(feature = "procmem")]
+			Inner::ProcMem($b) => $body,
+			#[cfg(feature = "ptrace")]
+			Inner::Ptrace($b) => $body,
+		}
+	};
+}
+
+/// A live process, accessed through whichever backend works for it.
+pub struct Process {
+	pid: u32,
+	inner: Inner,
+}
+
+impl Process {
+	/// Opens `pid` with the first backend in `Backend::PREFERENCE` that is enabled and
+	/// permitted. Ptrace is only tried if `check_access` expects it to succeed, as it stops
+	/// the target.
+	pub fn open(pid: u32) -> Result<Self, Error> {
+		let access = check_access(pid);
+		let mut last_error = None;
+
+		for backend in Backend::PREFERENCE.iter().filter(|b| b.is_enabled()) {
+			if *backend == Backend::Ptrace && access.is_err() {
+				continue;
+			}
+
+			match Self::open_with(pid, *backend) {
+				Ok(process) => return Ok(process),
+				Err(e) => last_error = Some(e),
+			}
+		}
+
+		// The access check explains failures better than the backend's errno.
+		Err(access.err().or(last_error).unwrap_or_else(|| {
+			Error::new(ErrorKind::Unsupported, "no backend enabled")
+		}))
+	}
+
+	pub fn open_with(pid: u32, backend: Backend) -> Result<Self, Error> {
+		let inner = match backend {
+			#[cfg(feature = "procvm")]
+			Backend::ProcVm => Inner::ProcVm(ProcVm::new(pid)?),
+			#[cfg(feature = "procmem")]
+			// Read-only until the first write, so readers without write access get this far.
+			Backend::ProcMem => Inner::ProcMem(ProcMem::new(pid, false)?),
+			#[cfg(feature = "ptrace")]
+			Backend::Ptrace => Inner::Ptrace(PtraceMem::attach(pid)?),
+			#[allow(unreachable_patterns)]
+			_ => {
+				return Err(Error::new(
+					ErrorKind::Unsupported,
+					format!("lime was built without the {} backend", backend.name()),
+				));
+			}
+		};
+
+		Ok(Self { pid, inner })
+	}
+
+	pub fn pid(&self) -> u32 {
+		self.pid
+	}
+
+	pub fn backend(&self) -> Backend {
+		match self.inner {
+			#[cfg(feature = "procvm")]
+			Inner::ProcVm(_) => Backend::ProcVm,
+			#[cfg(feature = "procmem")]
+			Inner::ProcMem(_) => Backend::ProcMem,
+			#[cfg(feature = "ptrace")]
+			Inner::Ptrace(_) => Backend::Ptrace,
+		}
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		with_backend!(&self.inner, b => b.get_maps())
+	}
+
+	pub fn refresh_maps(&mut self) -> Result<(), Error> {
+		with_backend!(&mut self.inner, b => b.refresh_maps())
+	}
+
+	pub fn refresh_smaps(&mut self) -> Result<(), Error> {
+		with_backend!(&mut self.inner, b => b.refresh_smaps())
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		with_backend!(&self.inner, b => b.resolve_scan_target(target))
+	}
+
+	/// Like `scan_for_pattern_in`, but reports why a scan failed: an invalid pattern or range,
+	/// maps that could not be loaded, or a target none of which could be read. Without those,
+	/// an empty result is a `PatternNotFound` error.
+	pub fn try_scan_for_pattern_in(
+		&mut self,
+		pattern: &str,
+		target: ScanTarget,
+	) -> Result<Vec<u64>, Error> {
+		let pattern = parse_pattern(pattern)?;
+		with_backend!(&mut self.inner, b => b.maps.refresh_for_scan(b.pid, &target))?;
+
+		let ranges = self.resolve_scan_target(&target);
+		let scanner = OffsetScanner::default();
+		let mut results = Vec::new();
+		let mut inspected = 0;
+
+		for &(start, end) in &ranges {
+			let mut coverage = RangeCoverage::new(start, end);
+			match scanner.scan_range_for_pattern_tracked(self, start, end, &pattern, &mut coverage)
+			{
+				Ok(mut r) => results.append(&mut r),
+				Err(e) if e.kind() == ErrorKind::PatternNotFound => {}
+				Err(e) => return Err(e),
+			}
+			inspected += coverage.inspected;
+		}
+
+		if !ranges.is_empty() && inspected == 0 {
+			return Err(Error::new(
+				ErrorKind::ReadFailed,
+				format!(
+					"none of the {} selected range(s) could be read",
+					ranges.len()
+				),
+			));
+		}
+		if results.is_empty() {
+			return Err(Error::new(ErrorKind::PatternNotFound, "No matches found"));
+		}
+
+		results.sort_unstable();
+		Ok(results)
+	}
+
+	/// Scans only pages that are present in RAM or swapped out, see
+	/// `ProcMem::scan_touched_for_pattern_in`.
+	#[cfg(feature = "procmem")]
+	pub fn scan_touched_for_pattern_in(
+		&mut self,
+		pattern: &str,
+		target: ScanTarget,
+	) -> Result<PagedScan, Error> {
+		with_backend!(&mut self.inner, b => b.maps.refresh_for_scan(b.pid, &target))?;
+
+		let ranges = self.resolve_scan_target(&target);
+		let pid = self.pid;
+		scan_pages_for_pattern(self, pid, &ranges, pattern, |e| {
+			e & (PM_PRESENT | PM_SWAPPED) != 0
+		})
+	}
+}
+
+impl ReadProcessMemory for Process {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		with_backend!(&mut self.inner, b => b.read_value(addr))
+	}
+
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		with_backend!(&mut self.inner, b => b.read_bytes(addr, buf))
+	}
+}
+
+impl WriteProcessMemory for Process {
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
+		with_backend!(&mut self.inner, b => b.write_value(addr, value))
+	}
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		with_backend!(&mut self.inner, b => b.write_bytes(addr, buf))
+	}
+}
+
+impl ProcessMemoryPatternScan for Process {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		with_backend!(&mut self.inner, b => b.scan_for_pattern(pattern))
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		with_backend!(&mut self.inner, b => b.scan_for_pattern_in(pattern, target))
+	}
+}
@@ -0,0 +1,256 @@
+use std::{
+	path::PathBuf,
+	time::{Duration, SystemTime},
+};
+
+use regex::Regex;
+
+use crate::{
+	errors::{Error, ErrorKind},
+	process::stat::ProcStat,
+};
+
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+	pub pid: u32,
+	pub ppid: u32,
+	/// Contents of `/proc/<pid>/comm`, truncated by the kernel to 15 bytes.
+	pub name: String,
+	pub cmdline: Vec<String>,
+	pub exe: Option<PathBuf>,
+	pub uid: Option<u32>,
+	/// Clock ticks since boot, as in field 22 of `/proc/<pid>/stat`.
+	pub start_time: u64,
+}
+
+impl ProcessInfo {
+	pub fn from_pid(pid: u32) -> Result<Self, Error> {
+		let stat = ProcStat::read(&format!("/proc/{}/stat", pid))?;
+
+		let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid))
+			.map(|raw| {
+				raw.split(|b| *b == 0)
+					.filter(|arg| !arg.is_empty())
+					.map(|arg| String::from_utf8_lossy(arg).into_owned())
+					.collect()
+			})
+			.unwrap_or_default();
+
+		// exe is only readable for processes we are allowed to ptrace
+		let exe = std::fs::read_link(format!("/proc/{}/exe", pid)).ok();
+
+		let uid = std::fs::read_to_string(format!("/proc/{}/status", pid))
+			.ok()
+			.and_then(|status| parse_status_uid(&status));
+
+		Ok(Self {
+			pid,
+			ppid: stat.ppid,
+			name: stat.name,
+			cmdline,
+			exe,
+			uid,
+			start_time: stat.start_time,
+		})
+	}
+
+	/// The full program name: the file name of `exe` if readable, otherwise of `argv[0]`,
+	/// otherwise `comm`.
+	pub fn full_name(&self) -> String {
+		self.exe
+			.as_ref()
+			.and_then(|p| p.file_name())
+			.map(|n| n.to_string_lossy().into_owned())
+			.or_else(|| {
+				self.cmdline
+					.first()
+					.and_then(|a| a.rsplit('/').next())
+					.map(|n| n.to_string())
+			})
+			.unwrap_or_else(|| self.name.clone())
+	}
+
+	pub fn cmdline_string(&self) -> String {
+		self.cmdline.join(" ")
+	}
+
+	/// Wall clock start time, derived from `btime` in `/proc/stat` and the clock tick rate.
+	pub fn started_at(&self) -> Option<SystemTime> {
+		let stat = std::fs::read_to_string("/proc/stat").ok()?;
+		let btime = stat
+			.lines()
+			.find_map(|l| l.strip_prefix("btime "))
+			.and_then(|v| v.trim().parse::<u64>().ok())?;
+
+		let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+		if ticks <= 0 {
+			return None;
+		}
+
+		let since_boot = Duration::from_secs_f64(self.start_time as f64 / ticks as f64);
+		Some(SystemTime::UNIX_EPOCH + Duration::from_secs(btime) + since_boot)
+	}
+}
+
+fn parse_status_uid(status: &str) -> Option<u32> {
+	// "Uid:\t<real>\t<effective>\t<saved>\t<fs>"
+	status
+		.lines()
+		.find_map(|l| l.strip_prefix("Uid:"))
+		.and_then(|l| l.split_whitespace().next())
+		.and_then(|uid| uid.parse().ok())
+}
+
+pub fn list_pids() -> Result<Vec<u32>, Error> {
+	let entries = std::fs::read_dir("/proc")
+		.map_err(|e| Error::os(ErrorKind::InvalidPid, "Failed to read /proc", e))?;
+
+	let mut pids: Vec<u32> = entries
+		.flatten()
+		.filter_map(|e| e.file_name().to_string_lossy().parse::<u32>().ok())
+		.collect();
+
+	pids.sort_unstable();
+	Ok(pids)
+}
+
+/// Lists every process visible in `/proc`. Processes that exit while being inspected are
+/// skipped.
+pub fn list_processes() -> Result<Vec<ProcessInfo>, Error> {
+	Ok(list_pids()?
+		.into_iter()
+		.filter_map(|pid| ProcessInfo::from_pid(pid).ok())
+		.collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NameMatch {
+	Contains,
+	Exact,
+}
+
+/// Builder for matching processes. All configured criteria have to match.
+///
+/// Name criteria are checked against `comm` and, because `comm` is truncated to 15 bytes,
+/// also against the full program name (see [`ProcessInfo::full_name`]).
+#[derive(Debug, Clone, Default)]
+pub struct ProcessQuery {
+	name: Option<(String, NameMatch)>,
+	cmdline: Option<String>,
+	exe: Option<String>,
+	uid: Option<u32>,
+	ppid: Option<u32>,
+	regex: Option<Regex>,
+}
+
+impl ProcessQuery {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn name_contains(mut self, needle: &str) -> Self {
+		self.name = Some((needle.to_string(), NameMatch::Contains));
+		self
+	}
+
+	pub fn name_exact(mut self, name: &str) -> Self {
+		self.name = Some((name.to_string(), NameMatch::Exact));
+		self
+	}
+
+	pub fn cmdline_contains(mut self, needle: &str) -> Self {
+		self.cmdline = Some(needle.to_string());
+		self
+	}
+
+	pub fn exe_contains(mut self, needle: &str) -> Self {
+		self.exe = Some(needle.to_string());
+		self
+	}
+
+	pub fn uid(mut self, uid: u32) -> Self {
+		self.uid = Some(uid);
+		self
+	}
+
+	pub fn ppid(mut self, ppid: u32) -> Self {
+		self.ppid = Some(ppid);
+		self
+	}
+
+	/// Matches the regex against `comm`, the full program name, the command line and the exe
+	/// path; any of them matching is enough.
+	pub fn regex(mut self, pattern: &str) -> Result<Self, Error> {
+		let re = Regex::new(pattern)
+			.map_err(|e| Error::new(ErrorKind::InvalidPattern, format!("{}: {}", pattern, e)))?;
+		self.regex = Some(re);
+		Ok(self)
+	}
+
+	pub fn matches(&self, info: &ProcessInfo) -> bool {
+		if let Some((needle, mode)) = &self.name {
+			let full = info.full_name();
+			let hit = match mode {
+				NameMatch::Contains => {
+					info.name.contains(needle.as_str()) || full.contains(needle.as_str())
+				}
+				NameMatch::Exact => info.name == *needle || full == *needle,
+			};
+			if !hit {
+				return false;
+			}
+		}
+
+		if let Some(needle) = &self.cmdline
+			&& !info.cmdline_string().contains(needle.as_str())
+		{
+			return false;
+		}
+
+		if let Some(needle) = &self.exe
+			&& !info
+				.exe
+				.as_ref()
+				.is_some_and(|p| p.to_string_lossy().contains(needle.as_str()))
+		{
+			return false;
+		}
+
+		if self.uid.is_some() && self.uid != info.uid {
+			return false;
+		}
+
+		if self.ppid.is_some_and(|ppid| ppid != info.ppid) {
+			return false;
+		}
+
+		if let Some(re) = &self.regex {
+			let exe = info
+				.exe
+				.as_ref()
+				.map(|p| p.to_string_lossy().into_owned())
+				.unwrap_or_default();
+
+			if !(re.is_match(&info.name)
+				|| re.is_match(&info.full_name())
+				|| re.is_match(&info.cmdline_string())
+				|| re.is_match(&exe))
+			{
+				return false;
+			}
+		}
+
+		true
+	}
+
+	pub fn run(&self) -> Result<Vec<ProcessInfo>, Error> {
+		Ok(list_processes()?
+			.into_iter()
+			.filter(|info| self.matches(info))
+			.collect())
+	}
+
+	pub fn first(&self) -> Result<Option<ProcessInfo>, Error> {
+		Ok(self.run()?.into_iter().next())
+	}
+}
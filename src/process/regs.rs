@@ -0,0 +1,50 @@
+/// General purpose registers of a single thread, as returned by
+/// `PTRACE_GETREGSET` with `NT_PRSTATUS`.
+#[derive(Clone, Copy)]
+pub struct ThreadRegisters {
+	pub tid: u32,
+	pub regs: libc::user_regs_struct,
+}
+
+impl ThreadRegisters {
+	#[cfg(target_arch = "x86_64")]
+	pub fn sp(&self) -> u64 {
+		self.regs.rsp
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	pub fn pc(&self) -> u64 {
+		self.regs.rip
+	}
+
+	#[cfg(target_arch = "aarch64")]
+	pub fn sp(&self) -> u64 {
+		self.regs.sp
+	}
+
+	#[cfg(target_arch = "aarch64")]
+	pub fn pc(&self) -> u64 {
+		self.regs.pc
+	}
+
+	/// Inverse of `as_bytes`, e.g. for `pr_reg` read from a core file.
+	pub fn from_bytes(tid: u32, bytes: &[u8]) -> Option<Self> {
+		if bytes.len() < size_of::<libc::user_regs_struct>() {
+			return None;
+		}
+
+		let regs =
+			unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const libc::user_regs_struct) };
+		Some(Self { tid, regs })
+	}
+
+	/// Raw register block, laid out exactly like `pr_reg` in `elf_prstatus`.
+	pub fn as_bytes(&self) -> &[u8] {
+		unsafe {
+			std::slice::from_raw_parts(
+				(&self.regs as *const libc::user_regs_struct) as *const u8,
+				size_of::<libc::user_regs_struct>(),
+			)
+		}
+	}
+}
@@ -0,0 +1,80 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	process::threads::ThreadState,
+};
+
+/// The subset of `/proc/<pid>/stat` (or `/proc/<pid>/task/<tid>/stat`) lime cares about.
+#[derive(Debug, Clone)]
+pub struct ProcStat {
+	pub name: String,
+	pub state: ThreadState,
+	pub ppid: u32,
+	pub pgrp: u32,
+	pub session: u32,
+	/// Clock ticks since boot.
+	pub start_time: u64,
+}
+
+impl ProcStat {
+	pub fn read(path: &str) -> Result<Self, Error> {
+		let stat = std::fs::read_to_string(path)
+			.map_err(|e| Error::os(ErrorKind::InvalidPid, format!("Failed to read {}", path), e))?;
+
+		Self::parse(&stat)
+	}
+
+	/// `comm` is wrapped in parentheses and may itself contain spaces and `)`, so the
+	/// remaining fields are taken from after the last closing parenthesis.
+	pub fn parse(stat: &str) -> Result<Self, Error> {
+		let open = stat.find('(').ok_or_else(|| {
+			Error::new(ErrorKind::Parse, format!("Missing comm in stat: {}", stat))
+		})?;
+		let close = stat.rfind(')').ok_or_else(|| {
+			Error::new(ErrorKind::Parse, format!("Missing comm in stat: {}", stat))
+		})?;
+
+		let name = stat[open + 1..close].to_string();
+		let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+
+		// fields[0] is field 3 (state) in proc(5)
+		if fields.len() < 20 {
+			return Err(Error::new(
+				ErrorKind::Parse,
+				format!("Truncated stat: {}", stat),
+			));
+		}
+
+		let state = fields[0]
+			.chars()
+			.next()
+			.map(ThreadState::from_char)
+			.ok_or_else(|| {
+				Error::new(ErrorKind::Parse, format!("Missing state in stat: {}", stat))
+			})?;
+
+		let ppid = fields[1]
+			.parse::<u32>()
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid ppid: {}", e)))?;
+
+		let pgrp = fields[2]
+			.parse::<u32>()
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid pgrp: {}", e)))?;
+
+		let session = fields[3]
+			.parse::<u32>()
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid session: {}", e)))?;
+
+		let start_time = fields[19]
+			.parse::<u64>()
+			.map_err(|e| Error::new(ErrorKind::Parse, format!("Invalid starttime: {}", e)))?;
+
+		Ok(Self {
+			name,
+			state,
+			ppid,
+			pgrp,
+			session,
+			start_time,
+		})
+	}
+}
@@ -0,0 +1,165 @@
+#[cfg(feature = "ptrace")]
+use crate::ptrace::regs::read_thread_registers;
+use crate::{
+	errors::{Error, ErrorKind},
+	maps::{ProcMemoryMaps, ProcMemoryRegion},
+	process::stat::ProcStat,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadState {
+	Running,
+	Sleeping,
+	DiskSleep,
+	Stopped,
+	TracingStop,
+	Zombie,
+	Dead,
+	Idle,
+	Unknown(char),
+}
+
+impl ThreadState {
+	pub fn from_char(c: char) -> Self {
+		match c {
+			'R' => Self::Running,
+			'S' => Self::Sleeping,
+			'D' => Self::DiskSleep,
+			'T' => Self::Stopped,
+			't' => Self::TracingStop,
+			'Z' => Self::Zombie,
+			'X' | 'x' => Self::Dead,
+			'I' => Self::Idle,
+			c => Self::Unknown(c),
+		}
+	}
+
+	pub fn as_char(self) -> char {
+		match self {
+			Self::Running => 'R',
+			Self::Sleeping => 'S',
+			Self::DiskSleep => 'D',
+			Self::Stopped => 'T',
+			Self::TracingStop => 't',
+			Self::Zombie => 'Z',
+			Self::Dead => 'X',
+			Self::Idle => 'I',
+			Self::Unknown(c) => c,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Running => "running",
+			Self::Sleeping => "sleeping",
+			Self::DiskSleep => "disk sleep",
+			Self::Stopped => "stopped",
+			Self::TracingStop => "tracing stop",
+			Self::Zombie => "zombie",
+			Self::Dead => "dead",
+			Self::Idle => "idle",
+			Self::Unknown(_) => "unknown",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreadInfo {
+	pub pid: u32,
+	pub tid: u32,
+	pub name: String,
+	pub state: ThreadState,
+}
+
+impl ThreadInfo {
+	pub fn is_main_thread(&self) -> bool {
+		self.pid == self.tid
+	}
+}
+
+pub fn list_threads(pid: u32) -> Result<Vec<ThreadInfo>, Error> {
+	let task_dir = format!("/proc/{}/task", pid);
+	let entries = std::fs::read_dir(&task_dir).map_err(|e| {
+		Error::os(
+			ErrorKind::InvalidPid,
+			format!("Failed to read {} ", task_dir),
+			e,
+		)
+	})?;
+
+	let mut threads = Vec::new();
+	for entry in entries.flatten() {
+		let Ok(tid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+			continue;
+		};
+
+		// Threads can exit between read_dir and reading their stat file.
+		if let Ok(info) = get_thread_info(pid, tid) {
+			threads.push(info);
+		}
+	}
+
+	threads.sort_unstable_by_key(|t| t.tid);
+	Ok(threads)
+}
+
+pub fn get_thread_info(pid: u32, tid: u32) -> Result<ThreadInfo, Error> {
+	let stat = ProcStat::read(&format!("/proc/{}/task/{}/stat", pid, tid))?;
+
+	Ok(ThreadInfo {
+		pid,
+		tid,
+		name: stat.name,
+		state: stat.state,
+	})
+}
+
+/// Returns the stack pointer of a thread. Uses `/proc/pid/task/tid/syscall` when the thread
+/// is blocked, which does not require stopping it, and falls back to ptrace otherwise (when
+/// the `ptrace` feature is enabled).
+pub fn get_thread_stack_pointer(pid: u32, tid: u32) -> Option<u64> {
+	let syscall_path = format!("/proc/{}/task/{}/syscall", pid, tid);
+	if let Ok(content) = std::fs::read_to_string(&syscall_path) {
+		// "running", or "<nr> <args...> <sp> <pc>", or "-1 <sp> <pc>"
+		let fields: Vec<&str> = content.split_whitespace().collect();
+		if fields.len() >= 3 {
+			let sp = fields[fields.len() - 2];
+			if let Ok(sp) = u64::from_str_radix(sp.trim_start_matches("0x"), 16)
+				&& sp != 0
+			{
+				return Some(sp);
+			}
+		}
+	}
+
+	#[cfg(feature = "ptrace")]
+	return read_thread_registers(tid).ok().map(|r| r.sp());
+	#[cfg(not(feature = "ptrace"))]
+	None
+}
+
+/// Finds the stack mapping of a thread, either from a `[stack:TID]` entry (pre 4.5 kernels),
+/// from its stack pointer, or `[stack]` for the main thread.
+pub fn find_thread_stack_region(
+	maps: &ProcMemoryMaps,
+	pid: u32,
+	tid: u32,
+) -> Option<&ProcMemoryRegion> {
+	if let Some(region) = maps.get_thread_stack_region(tid, None) {
+		return Some(region);
+	}
+
+	let sp = get_thread_stack_pointer(pid, tid);
+	if let Some(region) = maps.get_thread_stack_region(tid, sp) {
+		return Some(region);
+	}
+
+	if pid == tid {
+		return maps
+			.find_regions_by_name_exact("[stack]")
+			.into_iter()
+			.next();
+	}
+
+	None
+}
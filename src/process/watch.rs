@@ -0,0 +1,193 @@
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+		mpsc::{self, Receiver},
+	},
+	thread::JoinHandle,
+	time::{Duration, Instant},
+};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	process::query::{ProcessInfo, ProcessQuery},
+};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Blocks until a process matching `query` exists. A `timeout` of `None` waits forever.
+pub fn wait_for_process(
+	query: &ProcessQuery,
+	timeout: Option<Duration>,
+) -> Result<ProcessInfo, Error> {
+	wait_for_process_with_interval(query, timeout, DEFAULT_POLL_INTERVAL)
+}
+
+pub fn wait_for_process_with_interval(
+	query: &ProcessQuery,
+	timeout: Option<Duration>,
+	poll_interval: Duration,
+) -> Result<ProcessInfo, Error> {
+	let start = Instant::now();
+
+	loop {
+		if let Some(info) = query.first()? {
+			return Ok(info);
+		}
+
+		if let Some(timeout) = timeout {
+			let elapsed = start.elapsed();
+			if elapsed >= timeout {
+				return Err(Error::new(
+					ErrorKind::Timeout,
+					format!("no matching process after {}ms", timeout.as_millis()),
+				));
+			}
+			std::thread::sleep(poll_interval.min(timeout - elapsed));
+		} else {
+			std::thread::sleep(poll_interval);
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum ProcessEvent {
+	Started(ProcessInfo),
+	Exited(ProcessInfo),
+}
+
+impl ProcessEvent {
+	pub fn info(&self) -> &ProcessInfo {
+		match self {
+			Self::Started(info) | Self::Exited(info) => info,
+		}
+	}
+}
+
+/// Polls `/proc` for processes matching a query and reports when they start or exit.
+///
+/// Processes are keyed by pid and start time, so a pid reused by a new matching process is
+/// reported as an exit followed by a start.
+pub struct ProcessWatcher {
+	query: ProcessQuery,
+	known: HashMap<(u32, u64), ProcessInfo>,
+	poll_interval: Duration,
+}
+
+impl ProcessWatcher {
+	/// Processes already running are reported as `Started` on the first poll.
+	pub fn new(query: ProcessQuery) -> Self {
+		Self {
+			query,
+			known: HashMap::new(),
+			poll_interval: DEFAULT_POLL_INTERVAL,
+		}
+	}
+
+	/// Like `new`, but treats processes that are already running as known, so only later
+	/// starts and exits are reported.
+	pub fn ignore_existing(query: ProcessQuery) -> Result<Self, Error> {
+		let mut watcher = Self::new(query);
+		watcher.poll()?;
+		Ok(watcher)
+	}
+
+	pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+		self.poll_interval = poll_interval;
+		self
+	}
+
+	pub fn running(&self) -> impl Iterator<Item = &ProcessInfo> {
+		self.known.values()
+	}
+
+	pub fn poll(&mut self) -> Result<Vec<ProcessEvent>, Error> {
+		let current: HashMap<(u32, u64), ProcessInfo> = self
+			.query
+			.run()?
+			.into_iter()
+			.map(|info| ((info.pid, info.start_time), info))
+			.collect();
+
+		let mut events: Vec<ProcessEvent> = self
+			.known
+			.iter()
+			.filter(|(key, _)| !current.contains_key(key))
+			.map(|(_, info)| ProcessEvent::Exited(info.clone()))
+			.collect();
+
+		let mut started: Vec<ProcessEvent> = current
+			.iter()
+			.filter(|(key, _)| !self.known.contains_key(key))
+			.map(|(_, info)| ProcessEvent::Started(info.clone()))
+			.collect();
+
+		events.sort_unstable_by_key(|e| e.info().pid);
+		started.sort_unstable_by_key(|e| e.info().pid);
+		events.append(&mut started);
+
+		self.known = current;
+		Ok(events)
+	}
+
+	/// Moves the watcher onto a background thread that sends every event to the returned
+	/// handle until it is stopped or dropped.
+	pub fn spawn(mut self) -> ProcessWatcherHandle {
+		let (tx, rx) = mpsc::channel();
+		let stop = Arc::new(AtomicBool::new(false));
+		let thread_stop = stop.clone();
+
+		let thread = std::thread::spawn(move || {
+			while !thread_stop.load(Ordering::Relaxed) {
+				if let Ok(events) = self.poll() {
+					for event in events {
+						if tx.send(event).is_err() {
+							return;
+						}
+					}
+				}
+				std::thread::sleep(self.poll_interval);
+			}
+		});
+
+		ProcessWatcherHandle {
+			events: rx,
+			stop,
+			thread: Some(thread),
+		}
+	}
+}
+
+pub struct ProcessWatcherHandle {
+	events: Receiver<ProcessEvent>,
+	stop: Arc<AtomicBool>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl ProcessWatcherHandle {
+	pub fn events(&self) -> &Receiver<ProcessEvent> {
+		&self.events
+	}
+
+	pub fn recv_timeout(&self, timeout: Duration) -> Option<ProcessEvent> {
+		self.events.recv_timeout(timeout).ok()
+	}
+
+	pub fn stop(mut self) {
+		self.shutdown();
+	}
+
+	fn shutdown(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Drop for ProcessWatcherHandle {
+	fn drop(&mut self) {
+		self.shutdown();
+	}
+}
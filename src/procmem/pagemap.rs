@@ -0,0 +1,116 @@
+use std::{fs::File, os::unix::fs::FileExt};
+
+use crate::errors::{Error, ErrorKind};
+
+pub const PM_PRESENT: u64 = 1 << 63;
+pub const PM_SWAPPED: u64 = 1 << 62;
+pub const PM_FILE_OR_SHARED_ANON: u64 = 1 << 61;
+pub const PM_EXCLUSIVE: u64 = 1 << 56;
+pub const PM_SOFT_DIRTY: u64 = 1 << 55;
+
+/// Number of pagemap entries read per `pread`, so huge reserved mappings do not need a
+/// matching allocation.
+const ENTRIES_PER_READ: usize = 64 * 1024;
+
+/// Reader for `/proc/<pid>/pagemap`, one 64-bit entry per virtual page.
+pub struct PageMap {
+	pid: u32,
+	file: File,
+	page_size: u64,
+}
+
+impl PageMap {
+	pub fn open(pid: u32) -> Result<Self, Error> {
+		let path = format!("/proc/{}/pagemap", pid);
+		let file = File::open(&path)
+			.map_err(|e| Error::os(ErrorKind::InvalidPid, format!("Failed to open {}", path), e))?;
+
+		Ok(Self {
+			pid,
+			file,
+			page_size: page_size(),
+		})
+	}
+
+	pub fn page_size(&self) -> u64 {
+		self.page_size
+	}
+
+	pub fn entry(&self, addr: u64) -> Result<u64, Error> {
+		let mut raw = [0u8; 8];
+		let offset = (addr / self.page_size) * 8;
+		self.file.read_exact_at(&mut raw, offset).map_err(|e| {
+			Error::os(
+				ErrorKind::ReadFailed,
+				format!("pagemap entry for {:#x}", addr),
+				e,
+			)
+		})?;
+
+		Ok(u64::from_le_bytes(raw))
+	}
+
+	/// Returns the merged page-aligned runs within `[start, end)` whose pagemap entry
+	/// satisfies `pred`, clamped to `start` and `end`.
+	pub fn ranges_matching(
+		&self,
+		start: u64,
+		end: u64,
+		pred: impl Fn(u64) -> bool,
+	) -> Result<Vec<(u64, u64)>, Error> {
+		let mut ranges: Vec<(u64, u64)> = Vec::new();
+		if start >= end {
+			return Ok(ranges);
+		}
+
+		let first_page = start / self.page_size;
+		let last_page = end.div_ceil(self.page_size);
+		let mut raw = vec![0u8; ENTRIES_PER_READ * 8];
+		let mut page = first_page;
+
+		while page < last_page {
+			let count = ENTRIES_PER_READ.min((last_page - page) as usize);
+			let buf = &mut raw[..count * 8];
+			self.file.read_exact_at(buf, page * 8).map_err(|e| {
+				Error::os(
+					ErrorKind::ReadFailed,
+					format!(
+						"pagemap of pid {} at {:#x}",
+						self.pid,
+						page * self.page_size
+					),
+					e,
+				)
+			})?;
+
+			for (i, entry) in buf.chunks_exact(8).enumerate() {
+				let mut b = [0u8; 8];
+				b.copy_from_slice(entry);
+				if !pred(u64::from_le_bytes(b)) {
+					continue;
+				}
+
+				let page_start = ((page + i as u64) * self.page_size).max(start);
+				let page_end = ((page + i as u64 + 1) * self.page_size).min(end);
+				match ranges.last_mut() {
+					Some(last) if last.1 == page_start => last.1 = page_end,
+					_ => ranges.push((page_start, page_end)),
+				}
+			}
+
+			page += count as u64;
+		}
+
+		Ok(ranges)
+	}
+
+	/// Runs of pages that are in RAM or in swap, i.e. pages that were ever touched.
+	pub fn touched_ranges(&self, start: u64, end: u64) -> Result<Vec<(u64, u64)>, Error> {
+		self.ranges_matching(start, end, |e| e & (PM_PRESENT | PM_SWAPPED) != 0)
+	}
+}
+
+pub fn page_size() -> u64 {
+	let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+	if size > 0 { size as u64 } else { 4096 }
+}
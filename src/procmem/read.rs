@@ -1,38 +1,35 @@
 use std::io::{Read, Seek, SeekFrom};
 use std::os::unix::fs::FileExt;
 
-use crate::{errors::RPMError, traits::ReadProcessMemory};
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::ReadProcessMemory,
+};
 
 use super::procmem::ProcMem;
 
 impl ReadProcessMemory for ProcMem {
-	fn read_value<T: Copy>(
-		&mut self,
-		addr: u64,
-	) -> Result<T, Box<dyn crate::traits::InternalLimeError>> {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
 		self.maps.can_read(addr, std::mem::size_of::<T>())?;
 
-		let mut buffer = vec![0u8; size_of::<T>()];
-		self
-			.mem_file
+		let mut value = T::zeroed();
+		self.mem_file
 			.seek(SeekFrom::Start(addr))
-			.map_err(|_e| RPMError::ReadOutOfBounds(format!("Address {}", addr)))?;
+			.map_err(|e| Error::os(ErrorKind::OutOfBounds, "seek", e).with_addr(addr))?;
 
-		self
-			.mem_file
-			.read_exact(&mut buffer)
-			.map_err(|e| RPMError::FailedToRead(format!("error: {}", e)))?;
+		self.mem_file
+			.read_exact(value.as_bytes_mut())
+			.map_err(|e| {
+				Error::os(ErrorKind::ReadFailed, "mem", e)
+					.with_addr(addr)
+					.with_size(size_of::<T>())
+			})?;
 
-		let val = unsafe { std::ptr::read_unaligned(buffer.as_ptr() as *const T) };
-
-		return Ok(val);
+		Ok(value)
 	}
 
-	fn read_bytes(
-		&mut self,
-		addr: u64,
-		buf: &mut [u8],
-	) -> Result<usize, Box<dyn crate::traits::InternalLimeError>> {
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
 		Ok(self.mem_file.read_at(buf, addr).unwrap_or(0))
 	}
 }
@@ -1,9 +1,102 @@
 use crate::{
-	internal::patterns::offsets::{OffsetScanner, parse_pattern},
-	traits::{ProcessMemoryPatternScan, ScanTarget},
+	errors::Error,
+	internal::patterns::offsets::{OffsetScanner, RangeCoverage, parse_pattern},
+	process::threads::find_thread_stack_region,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget},
 };
 
-use super::procmem::ProcMem;
+use super::{
+	pagemap::{PM_PRESENT, PM_SWAPPED, PageMap},
+	procmem::ProcMem,
+};
+
+/// Result of a pagemap guided scan: the matches and, per target range, which part of it
+/// was actually read.
+#[derive(Debug, Clone, Default)]
+pub struct PagedScan {
+	pub matches: Vec<u64>,
+	pub coverage: Vec<RangeCoverage>,
+}
+
+impl PagedScan {
+	pub fn selected_bytes(&self) -> u64 {
+		self.coverage.iter().map(|c| c.selected).sum()
+	}
+
+	pub fn inspected_bytes(&self) -> u64 {
+		self.coverage.iter().map(|c| c.inspected).sum()
+	}
+}
+
+impl ProcMem {
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			find_thread_stack_region(&self.maps, self.pid, tid).map(|r| (r.start, r.end))
+		})
+	}
+
+	/// Scans only pages that are present in RAM or swapped out, skipping pages of the target
+	/// that were never touched.
+	pub fn scan_touched_for_pattern_in(
+		&mut self,
+		pattern: &str,
+		target: ScanTarget,
+	) -> Result<PagedScan, Error> {
+		self.scan_pages_for_pattern_in(pattern, target, |e| e & (PM_PRESENT | PM_SWAPPED) != 0)
+	}
+
+	/// Scans the pages of `target` whose pagemap entry satisfies `pred`.
+	pub fn scan_pages_for_pattern_in(
+		&mut self,
+		pattern: &str,
+		target: ScanTarget,
+		pred: impl Fn(u64) -> bool,
+	) -> Result<PagedScan, Error> {
+		self.maps.refresh_for_scan(self.pid, &target)?;
+
+		let ranges = self.resolve_scan_target(&target);
+		scan_pages_for_pattern(self, self.pid, &ranges, pattern, pred)
+	}
+}
+
+/// Scans the pages of `ranges` in process `pid` whose pagemap entry satisfies `pred`,
+/// reading them through `reader`. The pagemap is always read from `/proc`, so this works
+/// with any live backend.
+pub fn scan_pages_for_pattern<T: ReadProcessMemory>(
+	reader: &mut T,
+	pid: u32,
+	ranges: &[(u64, u64)],
+	pattern: &str,
+	pred: impl Fn(u64) -> bool,
+) -> Result<PagedScan, Error> {
+	let pattern = parse_pattern(pattern)?;
+	let scanner = OffsetScanner::default();
+	let pagemap = PageMap::open(pid)?;
+
+	let mut scan = PagedScan::default();
+	for (start, end) in ranges {
+		let mut coverage = RangeCoverage::new(*start, *end);
+		let runs = pagemap.ranges_matching(*start, *end, &pred)?;
+		coverage.selected = runs.iter().map(|(s, e)| e - s).sum();
+
+		for (run_start, run_end) in runs {
+			if let Ok(mut r) = scanner.scan_range_for_pattern_tracked(
+				reader,
+				run_start,
+				run_end,
+				&pattern,
+				&mut coverage,
+			) {
+				scan.matches.append(&mut r);
+			}
+		}
+
+		scan.coverage.push(coverage);
+	}
+
+	scan.matches.sort_unstable();
+	Ok(scan)
+}
 
 impl ProcessMemoryPatternScan for ProcMem {
 	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
@@ -12,50 +105,12 @@ impl ProcessMemoryPatternScan for ProcMem {
 
 	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
 		let pattern = parse_pattern(pattern).ok()?;
-		let scanner = OffsetScanner::default();
-		let mut results = Vec::new();
-
-		let bind = self.maps.clone();
-		let regions: Vec<(u64, u64)> = match target {
-			ScanTarget::HeapAndStack => {
-				let mut r = bind.get_heap_regions();
-				r.append(&mut bind.get_stack_regions());
-				r.iter().map(|r| (r.start, r.end)).collect()
-			}
-			ScanTarget::Anonymous => bind
-				.get_anonymous_regions()
-				.iter()
-				.map(|r| (r.start, r.end))
-				.collect(),
-			ScanTarget::AnonymousNonHeapAndStack => bind
-				.get_anonymous_regions()
-				.iter()
-				.filter(|f| match f.pathname.as_deref() {
-					None => false,
-					Some(x) => !x.contains("[heap]") && !x.contains("[stack"),
-				})
-				.map(|r| (r.start, r.end))
-				.collect(),
-			ScanTarget::Module(name) => bind
-				.find_regions_by_name(name)
-				.iter()
-				.filter(|r| r.is_readable() && r.is_executable())
-				.map(|r| (r.start, r.end))
-				.collect(),
-			ScanTarget::Range(start, end) => vec![(start, end)],
-		};
-
-		for (start, end) in regions {
-			if let Ok(mut r) = scanner.scan_range_for_pattern(self, start, end, &pattern) {
-				results.append(&mut r);
-			}
-		}
 
-		if results.is_empty() {
-			None
-		} else {
-			results.sort_unstable();
-			Some(results)
-		}
+		self.maps.refresh_for_scan(self.pid, &target).ok()?;
+
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
 	}
 }
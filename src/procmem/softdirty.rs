@@ -0,0 +1,118 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	traits::ScanTarget,
+};
+
+use super::{
+	pagemap::{PM_SOFT_DIRTY, PageMap},
+	procmem::ProcMem,
+	scan::PagedScan,
+};
+
+const DIRTY_WINDOW_PAGES: u64 = 512;
+
+/// Tracks which pages a process wrote to since the last `reset`, using the kernel's
+/// soft-dirty bits (`/proc/<pid>/clear_refs` and bit 55 of pagemap entries).
+///
+/// Requires a kernel built with `CONFIG_MEM_SOFT_DIRTY`, and write access to `clear_refs`,
+/// which is only granted to the owner of the process.
+pub struct SoftDirtyTracker {
+	pid: u32,
+	pagemap: PageMap,
+}
+
+impl SoftDirtyTracker {
+	/// Opens the tracker and clears the soft-dirty bits, so tracking starts now.
+	pub fn start(pid: u32) -> Result<Self, Error> {
+		if !soft_dirty_supported() {
+			return Err(Error::new(
+				ErrorKind::Unsupported,
+				"kernel was built without CONFIG_MEM_SOFT_DIRTY".to_string(),
+			));
+		}
+
+		let tracker = Self {
+			pid,
+			pagemap: PageMap::open(pid)?,
+		};
+		tracker.reset()?;
+		Ok(tracker)
+	}
+
+	pub fn reset(&self) -> Result<(), Error> {
+		clear_soft_dirty(self.pid)
+	}
+
+	pub fn is_dirty(&self, addr: u64) -> Result<bool, Error> {
+		Ok(self.pagemap.entry(addr)? & PM_SOFT_DIRTY != 0)
+	}
+
+	pub fn dirty_ranges(&self, start: u64, end: u64) -> Result<Vec<(u64, u64)>, Error> {
+		self.pagemap.ranges_matching(start, end, is_soft_dirty)
+	}
+
+	/// Keeps the candidate addresses of a previous scan whose `size` bytes touch a page that
+	/// was written to since the last reset. Values at the other addresses cannot have changed.
+	pub fn dirty_addresses(&self, addresses: &[u64], size: usize) -> Result<Vec<u64>, Error> {
+		let page_size = self.pagemap.page_size();
+		let mut sorted = addresses.to_vec();
+		sorted.sort_unstable();
+
+		let mut dirty = Vec::new();
+		let mut ranges: Vec<(u64, u64)> = Vec::new();
+		let mut window_end = 0;
+
+		for addr in sorted {
+			let last = addr.saturating_add(size.max(1) as u64 - 1);
+
+			// Candidates tend to be clustered, so the pagemap is read for a window of pages
+			// starting at the address rather than one entry at a time.
+			if last >= window_end {
+				let window_start = addr - addr % page_size;
+				window_end = (last - last % page_size) + page_size * DIRTY_WINDOW_PAGES;
+				ranges = self.dirty_ranges(window_start, window_end)?;
+			}
+
+			if ranges.iter().any(|(s, e)| *s <= last && addr < *e) {
+				dirty.push(addr);
+			}
+		}
+
+		Ok(dirty)
+	}
+}
+
+pub fn clear_soft_dirty(pid: u32) -> Result<(), Error> {
+	let path = format!("/proc/{}/clear_refs", pid);
+	std::fs::write(&path, "4").map_err(|e| Error::os(ErrorKind::WriteFailed, path, e))?;
+	Ok(())
+}
+
+/// Without `CONFIG_MEM_SOFT_DIRTY` writing to `clear_refs` still succeeds but the bit is
+/// never set. Pages start out soft-dirty, so a page lime itself just wrote must have it.
+pub fn soft_dirty_supported() -> bool {
+	let probe = std::hint::black_box(Box::new([1u8; 8]));
+	let Ok(pagemap) = PageMap::open(std::process::id()) else {
+		return false;
+	};
+
+	pagemap
+		.entry(probe.as_ptr() as u64)
+		.is_ok_and(is_soft_dirty)
+}
+
+fn is_soft_dirty(entry: u64) -> bool {
+	entry & PM_SOFT_DIRTY != 0
+}
+
+impl ProcMem {
+	/// Scans only the pages of `target` written to since the soft-dirty bits were last
+	/// cleared, see `SoftDirtyTracker`.
+	pub fn scan_dirty_for_pattern_in(
+		&mut self,
+		pattern: &str,
+		target: ScanTarget,
+	) -> Result<PagedScan, Error> {
+		self.scan_pages_for_pattern_in(pattern, target, is_soft_dirty)
+	}
+}
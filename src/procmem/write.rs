@@ -1,29 +1,43 @@
-use std::io::{Seek, SeekFrom, Write};
+use std::{
+	io::{Seek, SeekFrom, Write},
+	os::unix::fs::FileExt,
+};
 
-use crate::{errors::WPMError, traits::WriteProcessMemory};
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::WriteProcessMemory,
+};
 
 use super::procmem::ProcMem;
 
 impl WriteProcessMemory for ProcMem {
-	fn write_value<T: Copy>(
-		&mut self,
-		addr: u64,
-		value: &T,
-	) -> Result<(), Box<dyn crate::traits::InternalLimeError>> {
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
 		self.maps.can_write(addr, std::mem::size_of::<T>())?;
+		self.make_writable()?;
 
-		let bytes =
-			unsafe { std::slice::from_raw_parts((value as *const T) as *const u8, size_of::<T>()) };
+		let bytes = value.as_bytes();
 
-		self
-			.mem_file
+		self.mem_file
 			.seek(SeekFrom::Start(addr))
-			.map_err(|e| WPMError::WriteOutOfBounds(format!("error: {}", e)))?;
-		self
-			.mem_file
-			.write_all(bytes)
-			.map_err(|e| WPMError::FailedToWrite(format!("error: {}", e)))?;
+			.map_err(|e| Error::os(ErrorKind::OutOfBounds, "seek", e).with_addr(addr))?;
+		self.mem_file.write_all(bytes).map_err(|e| {
+			Error::os(ErrorKind::WriteFailed, "mem", e)
+				.with_addr(addr)
+				.with_size(bytes.len())
+		})?;
 
-		return Ok(());
+		Ok(())
+	}
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		self.maps.can_write(addr, buf.len())?;
+		self.make_writable()?;
+
+		self.mem_file.write_all_at(buf, addr).map_err(|e| {
+			Error::os(ErrorKind::WriteFailed, "mem", e)
+				.with_addr(addr)
+				.with_size(buf.len())
+		})
 	}
 }
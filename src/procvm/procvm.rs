@@ -0,0 +1,113 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::patterns::offsets::{OffsetScanner, parse_pattern},
+	maps::ProcMemoryMaps,
+	process::threads::find_thread_stack_region,
+	traits::{ProcessMemoryPatternScan, ScanTarget},
+};
+
+/// Accesses another process with `process_vm_readv`/`process_vm_writev`. Needs the same
+/// permissions as `/proc/<pid>/mem` but no file descriptor, and cannot write to read-only
+/// mappings.
+pub struct ProcVm {
+	pub pid: u32,
+	pub maps: ProcMemoryMaps,
+}
+
+impl ProcVm {
+	/// Fails if the syscalls are not permitted for `pid`, e.g. because of Yama or a seccomp
+	/// filter, by probing the first readable region.
+	pub fn new(pid: u32) -> Result<Self, Error> {
+		let maps = ProcMemoryMaps::new(pid)?;
+
+		if let Some(region) = maps.get_regions().iter().find(|r| r.is_readable()) {
+			let mut byte = 0u8;
+			if let Err(e) = readv(pid, region.start, std::slice::from_mut(&mut byte))
+				&& matches!(
+					e.raw_os_error(),
+					Some(libc::EPERM | libc::ENOSYS | libc::ESRCH)
+				) {
+				return Err(Error::new(
+					ErrorKind::InvalidPid,
+					format!("{} - process_vm_readv: {}", pid, e),
+				));
+			}
+		}
+
+		Ok(Self { pid, maps })
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		&self.maps
+	}
+
+	pub fn refresh_maps(&mut self) -> Result<(), Error> {
+		self.maps = ProcMemoryMaps::new(self.pid)?;
+		Ok(())
+	}
+
+	pub fn refresh_smaps(&mut self) -> Result<(), Error> {
+		self.maps = ProcMemoryMaps::new_with_smaps(self.pid)?;
+		Ok(())
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			find_thread_stack_region(&self.maps, self.pid, tid).map(|r| (r.start, r.end))
+		})
+	}
+}
+
+impl ProcessMemoryPatternScan for ProcVm {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		self.scan_for_pattern_in(pattern, ScanTarget::HeapAndStack)
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		let pattern = parse_pattern(pattern).ok()?;
+
+		self.maps.refresh_for_scan(self.pid, &target).ok()?;
+
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
+	}
+}
+
+/// One `process_vm_readv` call. Returns how many bytes were read, which is short when the
+/// range runs into an unmapped or unreadable page.
+pub(super) fn readv(pid: u32, addr: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+	let local = libc::iovec {
+		iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+		iov_len: buf.len(),
+	};
+	let remote = libc::iovec {
+		iov_base: addr as *mut libc::c_void,
+		iov_len: buf.len(),
+	};
+
+	let n = unsafe { libc::process_vm_readv(pid as libc::pid_t, &local, 1, &remote, 1, 0) };
+	if n == -1 {
+		return Err(std::io::Error::last_os_error());
+	}
+	Ok(n as usize)
+}
+
+/// One `process_vm_writev` call, see `readv`.
+pub(super) fn writev(pid: u32, addr: u64, buf: &[u8]) -> std::io::Result<usize> {
+	let local = libc::iovec {
+		iov_base: buf.as_ptr() as *mut libc::c_void,
+		iov_len: buf.len(),
+	};
+	let remote = libc::iovec {
+		iov_base: addr as *mut libc::c_void,
+		iov_len: buf.len(),
+	};
+
+	let n = unsafe { libc::process_vm_writev(pid as libc::pid_t, &local, 1, &remote, 1, 0) };
+	if n == -1 {
+		return Err(std::io::Error::last_os_error());
+	}
+	Ok(n as usize)
+}
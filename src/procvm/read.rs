@@ -1 +1,33 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::ReadProcessMemory,
+};
 
+use super::procvm::{ProcVm, readv};
+
+impl ReadProcessMemory for ProcVm {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		self.maps.can_read(addr, size_of::<T>())?;
+
+		let mut value = T::zeroed();
+		let n = readv(self.pid, addr, value.as_bytes_mut()).map_err(|e| {
+			Error::os(ErrorKind::ReadFailed, "process_vm_readv", e)
+				.with_addr(addr)
+				.with_size(size_of::<T>())
+		})?;
+		if n < size_of::<T>() {
+			return Err(
+				Error::new(ErrorKind::OutOfBounds, format!("short read of {} bytes", n))
+					.with_addr(addr)
+					.with_size(size_of::<T>()),
+			);
+		}
+
+		Ok(value)
+	}
+
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		Ok(readv(self.pid, addr, buf).unwrap_or(0))
+	}
+}
@@ -1 +1,51 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::WriteProcessMemory,
+};
 
+use super::procvm::{ProcVm, writev};
+
+impl WriteProcessMemory for ProcVm {
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
+		self.maps.can_write(addr, size_of::<T>())?;
+
+		let bytes = value.as_bytes();
+
+		let n = writev(self.pid, addr, bytes).map_err(|e| {
+			Error::os(ErrorKind::WriteFailed, "process_vm_writev", e)
+				.with_addr(addr)
+				.with_size(bytes.len())
+		})?;
+		if n < bytes.len() {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				format!("short write of {} bytes", n),
+			)
+			.with_addr(addr)
+			.with_size(bytes.len()));
+		}
+
+		Ok(())
+	}
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		self.maps.can_write(addr, buf.len())?;
+
+		let n = writev(self.pid, addr, buf).map_err(|e| {
+			Error::os(ErrorKind::WriteFailed, "process_vm_writev", e)
+				.with_addr(addr)
+				.with_size(buf.len())
+		})?;
+		if n < buf.len() {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				format!("short write of {} bytes", n),
+			)
+			.with_addr(addr)
+			.with_size(buf.len()));
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,217 @@
+use std::{
+	os::unix::process::CommandExt,
+	path::PathBuf,
+	process::{Child, Command, ExitStatus},
+};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	process::auxv::resolve_entry_point,
+	ptrace::{
+		mem::{peek, poke},
+		regs::{get_stopped_thread_registers, set_stopped_thread_registers},
+	},
+};
+
+#[cfg(target_arch = "x86_64")]
+const BREAKPOINT: &[u8] = &[0xcc]; // int3
+
+#[cfg(target_arch = "aarch64")]
+const BREAKPOINT: &[u8] = &[0x00, 0x00, 0x20, 0xd4]; // brk #0
+
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+	pub program: PathBuf,
+	pub args: Vec<String>,
+	pub env: Vec<(String, String)>,
+	pub clear_env: bool,
+	pub cwd: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+	pub fn new(program: impl Into<PathBuf>) -> Self {
+		Self {
+			program: program.into(),
+			args: Vec::new(),
+			env: Vec::new(),
+			clear_env: false,
+			cwd: None,
+		}
+	}
+
+	pub fn arg(mut self, arg: impl Into<String>) -> Self {
+		self.args.push(arg.into());
+		self
+	}
+
+	pub fn args<I, S>(mut self, args: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.args.extend(args.into_iter().map(Into::into));
+		self
+	}
+
+	pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.env.push((key.into(), value.into()));
+		self
+	}
+
+	/// Start from an empty environment instead of inheriting lime's.
+	pub fn clear_env(mut self) -> Self {
+		self.clear_env = true;
+		self
+	}
+
+	pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+		self.cwd = Some(cwd.into());
+		self
+	}
+}
+
+/// A child process stopped at its entry point under lime's ptrace control. None of the
+/// program's own code has run yet, only the dynamic loader (if any).
+pub struct LaunchedProcess {
+	pub pid: u32,
+	pub entry: u64,
+	child: Child,
+	attached: bool,
+}
+
+impl LaunchedProcess {
+	/// Detaches and lets the program run.
+	pub fn resume(&mut self) -> Result<(), Error> {
+		if !self.attached {
+			return Ok(());
+		}
+
+		if unsafe { libc::ptrace(libc::PTRACE_DETACH, self.pid as libc::pid_t, 0, 0) } == -1 {
+			return Err(Error::last_os_error(
+				ErrorKind::DetachFailed,
+				format!("pid {}", self.pid),
+			));
+		}
+
+		self.attached = false;
+		Ok(())
+	}
+
+	pub fn kill(&mut self) -> Result<(), Error> {
+		self.child.kill().map_err(|e| {
+			Error::os(ErrorKind::LaunchFailed, format!("kill {}", self.pid), e) as Error
+		})?;
+		self.attached = false;
+		Ok(())
+	}
+
+	/// Resumes the program if needed and waits for it to exit.
+	pub fn wait(mut self) -> Result<ExitStatus, Error> {
+		self.resume()?;
+		self.child
+			.wait()
+			.map_err(|e| Error::os(ErrorKind::WaitFailed, format!("pid {}", self.pid), e) as Error)
+	}
+}
+
+/// Spawns `options.program` with `PTRACE_TRACEME`, runs it up to its entry point and leaves
+/// it stopped there.
+pub fn launch_stopped(options: &LaunchOptions) -> Result<LaunchedProcess, Error> {
+	let mut command = Command::new(&options.program);
+	command.args(&options.args);
+	if options.clear_env {
+		command.env_clear();
+	}
+	command.envs(options.env.iter().map(|(k, v)| (k, v)));
+	if let Some(cwd) = &options.cwd {
+		command.current_dir(cwd);
+	}
+
+	unsafe {
+		command.pre_exec(|| {
+			if libc::ptrace(libc::PTRACE_TRACEME, 0, 0, 0) == -1 {
+				return Err(std::io::Error::last_os_error());
+			}
+			Ok(())
+		});
+	}
+
+	let child = command.spawn().map_err(|e| {
+		Error::os(
+			ErrorKind::LaunchFailed,
+			format!("{}", options.program.display()),
+			e,
+		)
+	})?;
+
+	let mut launched = LaunchedProcess {
+		pid: child.id(),
+		entry: 0,
+		child,
+		attached: true,
+	};
+
+	// The child stops with SIGTRAP once execve succeeded.
+	match wait_for_trap(launched.pid).and_then(|_| run_to_entry(launched.pid)) {
+		Ok(entry) => launched.entry = entry,
+		Err(e) => {
+			let _ = launched.kill();
+			let _ = launched.child.wait();
+			return Err(e);
+		}
+	}
+
+	Ok(launched)
+}
+
+/// Runs the stopped child up to its entry point, which it returns.
+fn run_to_entry(pid: u32) -> Result<u64, Error> {
+	let entry = resolve_entry_point(pid)?;
+
+	let original = peek(pid, entry)?;
+	let mut patched = original.to_le_bytes();
+	patched[..BREAKPOINT.len()].copy_from_slice(BREAKPOINT);
+	poke(pid, entry, u64::from_le_bytes(patched))?;
+
+	if unsafe { libc::ptrace(libc::PTRACE_CONT, pid as libc::pid_t, 0, 0) } == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::LaunchFailed,
+			format!("continue {}", pid),
+		));
+	}
+
+	wait_for_trap(pid)?;
+	poke(pid, entry, original)?;
+
+	// int3 leaves the program counter after the breakpoint, brk leaves it on it.
+	let mut regs = get_stopped_thread_registers(pid)?;
+	#[cfg(target_arch = "x86_64")]
+	{
+		regs.regs.rip = entry;
+	}
+	#[cfg(target_arch = "aarch64")]
+	{
+		regs.regs.pc = entry;
+	}
+	set_stopped_thread_registers(&regs)?;
+	Ok(entry)
+}
+
+fn wait_for_trap(pid: u32) -> Result<(), Error> {
+	let mut status = 0;
+	if unsafe { libc::waitpid(pid as libc::pid_t, &mut status, 0) } == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::WaitFailed,
+			format!("pid {}", pid),
+		));
+	}
+
+	if !libc::WIFSTOPPED(status) || libc::WSTOPSIG(status) != libc::SIGTRAP {
+		return Err(Error::new(
+			ErrorKind::UnexpectedStop,
+			format!("pid {} status {:#x}", pid, status),
+		));
+	}
+
+	Ok(())
+}
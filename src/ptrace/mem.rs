@@ -0,0 +1,112 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	internal::patterns::offsets::{OffsetScanner, parse_pattern},
+	maps::ProcMemoryMaps,
+	process::threads::find_thread_stack_region,
+	traits::{ProcessMemoryPatternScan, ScanTarget},
+};
+
+pub(crate) const WORD_SIZE: u64 = size_of::<libc::c_long>() as u64;
+
+/// Accesses another process one word at a time with `PTRACE_PEEKDATA`/`PTRACE_POKEDATA`.
+///
+/// The main thread stays stopped for as long as this exists, other threads keep running.
+/// It is the slowest backend and meant as a fallback when neither `process_vm_readv` nor
+/// `/proc/<pid>/mem` can be used.
+pub struct PtraceMem {
+	pub pid: u32,
+	pub maps: ProcMemoryMaps,
+}
+
+impl PtraceMem {
+	pub fn attach(pid: u32) -> Result<Self, Error> {
+		let maps = ProcMemoryMaps::new(pid)?;
+		let raw = pid as libc::pid_t;
+
+		if unsafe { libc::ptrace(libc::PTRACE_SEIZE, raw, 0, 0) } == -1 {
+			return Err(Error::last_os_error(
+				ErrorKind::AttachFailed,
+				format!("pid {}", pid),
+			));
+		}
+
+		// From here on Drop detaches.
+		let attached = Self { pid, maps };
+
+		let mut status = 0;
+		if unsafe { libc::ptrace(libc::PTRACE_INTERRUPT, raw, 0, 0) } == -1
+			|| unsafe { libc::waitpid(raw, &mut status, libc::__WALL) } == -1
+		{
+			return Err(Error::last_os_error(
+				ErrorKind::WaitFailed,
+				format!("pid {}", pid),
+			));
+		}
+
+		Ok(attached)
+	}
+
+	pub fn get_maps(&self) -> &ProcMemoryMaps {
+		&self.maps
+	}
+
+	pub fn refresh_maps(&mut self) -> Result<(), Error> {
+		self.maps = ProcMemoryMaps::new(self.pid)?;
+		Ok(())
+	}
+
+	pub fn refresh_smaps(&mut self) -> Result<(), Error> {
+		self.maps = ProcMemoryMaps::new_with_smaps(self.pid)?;
+		Ok(())
+	}
+
+	pub fn resolve_scan_target(&self, target: &ScanTarget) -> Vec<(u64, u64)> {
+		self.maps.resolve_scan_target(target, &|tid| {
+			find_thread_stack_region(&self.maps, self.pid, tid).map(|r| (r.start, r.end))
+		})
+	}
+}
+
+impl Drop for PtraceMem {
+	fn drop(&mut self) {
+		unsafe { libc::ptrace(libc::PTRACE_DETACH, self.pid as libc::pid_t, 0, 0) };
+	}
+}
+
+impl ProcessMemoryPatternScan for PtraceMem {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>> {
+		self.scan_for_pattern_in(pattern, ScanTarget::HeapAndStack)
+	}
+
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>> {
+		let pattern = parse_pattern(pattern).ok()?;
+
+		self.maps.refresh_for_scan(self.pid, &target).ok()?;
+
+		let ranges = self.resolve_scan_target(&target);
+		let results = OffsetScanner::default().scan_ranges_for_pattern(self, &ranges, &pattern);
+
+		(!results.is_empty()).then_some(results)
+	}
+}
+
+/// Reads the word at `addr` of a thread stopped under our ptrace control.
+pub(crate) fn peek(pid: u32, addr: u64) -> Result<u64, Error> {
+	// PEEKDATA returns the word itself, so -1 is only an error if errno was set.
+	unsafe { *libc::__errno_location() = 0 };
+	let word = unsafe { libc::ptrace(libc::PTRACE_PEEKDATA, pid as libc::pid_t, addr, 0) };
+	let err = std::io::Error::last_os_error();
+	if word == -1 && err.raw_os_error() != Some(0) {
+		return Err(Error::os(ErrorKind::PeekPokeFailed, "peek", err).with_addr(addr));
+	}
+
+	Ok(word as u64)
+}
+
+pub(crate) fn poke(pid: u32, addr: u64, word: u64) -> Result<(), Error> {
+	if unsafe { libc::ptrace(libc::PTRACE_POKEDATA, pid as libc::pid_t, addr, word) } == -1 {
+		return Err(Error::last_os_error(ErrorKind::PeekPokeFailed, "poke").with_addr(addr));
+	}
+
+	Ok(())
+}
@@ -1 +1,46 @@
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::ReadProcessMemory,
+};
 
+use super::mem::{PtraceMem, WORD_SIZE, peek};
+
+impl ReadProcessMemory for PtraceMem {
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error> {
+		self.maps.can_read(addr, size_of::<T>())?;
+
+		let mut value = T::zeroed();
+		let n = self.read_bytes(addr, value.as_bytes_mut())?;
+		if n < size_of::<T>() {
+			return Err(
+				Error::new(ErrorKind::ReadFailed, format!("short read of {} bytes", n))
+					.with_addr(addr)
+					.with_size(size_of::<T>()),
+			);
+		}
+
+		Ok(value)
+	}
+
+	/// Reads whole words and stops at the first one that cannot be read.
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
+		let mut done = 0;
+
+		while done < buf.len() {
+			let current = addr + done as u64;
+			let word_addr = current - current % WORD_SIZE;
+			let Ok(word) = peek(self.pid, word_addr) else {
+				break;
+			};
+
+			let bytes = word.to_le_bytes();
+			let skip = (current - word_addr) as usize;
+			let len = (WORD_SIZE as usize - skip).min(buf.len() - done);
+			buf[done..done + len].copy_from_slice(&bytes[skip..skip + len]);
+			done += len;
+		}
+
+		Ok(done)
+	}
+}
@@ -0,0 +1,189 @@
+pub use crate::process::regs::ThreadRegisters;
+use crate::{
+	errors::{Error, ErrorKind},
+	process::threads::list_threads,
+};
+
+/// Briefly stops `tid` with `PTRACE_SEIZE` + `PTRACE_INTERRUPT`, copies its registers and
+/// detaches again. The thread keeps running afterwards.
+///
+/// `PTRACE_DETACH` only works on a stopped tracee, so a thread that could not be stopped stays
+/// traced by the calling thread until one of them exits. That is reported as `DetachFailed`.
+pub fn read_thread_registers(tid: u32) -> Result<ThreadRegisters, Error> {
+	let pid = tid as libc::pid_t;
+
+	if unsafe { libc::ptrace(libc::PTRACE_SEIZE, pid, 0, 0) } == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::AttachFailed,
+			format!("tid {}", tid),
+		));
+	}
+
+	let result = match interrupt_and_wait(pid) {
+		Ok(true) => get_stopped_thread_registers(tid),
+		// Nothing left to detach from.
+		Ok(false) => {
+			return Err(Error::new(
+				ErrorKind::UnexpectedStop,
+				format!("tid {} exited", tid),
+			));
+		}
+		Err(e) => Err(e),
+	};
+
+	if unsafe { libc::ptrace(libc::PTRACE_DETACH, pid, 0, 0) } == -1 {
+		let message = match &result {
+			Ok(_) => format!("tid {}", tid),
+			Err(e) => format!("tid {} is still traced after: {}", tid, e.message()),
+		};
+		return Err(Error::last_os_error(ErrorKind::DetachFailed, message));
+	}
+
+	result
+}
+
+/// Reads the registers of a thread that is already stopped under our ptrace control.
+pub fn get_stopped_thread_registers(tid: u32) -> Result<ThreadRegisters, Error> {
+	let mut regs: libc::user_regs_struct = unsafe { std::mem::zeroed() };
+	let mut iov = libc::iovec {
+		iov_base: (&mut regs as *mut libc::user_regs_struct) as *mut libc::c_void,
+		iov_len: size_of::<libc::user_regs_struct>(),
+	};
+
+	let res = unsafe {
+		libc::ptrace(
+			libc::PTRACE_GETREGSET,
+			tid as libc::pid_t,
+			libc::NT_PRSTATUS as usize,
+			&mut iov as *mut libc::iovec,
+		)
+	};
+
+	if res == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::RegistersFailed,
+			format!("tid {}", tid),
+		));
+	}
+
+	Ok(ThreadRegisters { tid, regs })
+}
+
+pub fn set_stopped_thread_registers(regs: &ThreadRegisters) -> Result<(), Error> {
+	let mut raw = regs.regs;
+	let mut iov = libc::iovec {
+		iov_base: (&mut raw as *mut libc::user_regs_struct) as *mut libc::c_void,
+		iov_len: size_of::<libc::user_regs_struct>(),
+	};
+
+	let res = unsafe {
+		libc::ptrace(
+			libc::PTRACE_SETREGSET,
+			regs.tid as libc::pid_t,
+			libc::NT_PRSTATUS as usize,
+			&mut iov as *mut libc::iovec,
+		)
+	};
+
+	if res == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::RegistersFailed,
+			format!("tid {}", regs.tid),
+		));
+	}
+
+	Ok(())
+}
+
+/// Stops a seized thread and waits for the stop. Returns `false` if the thread exited
+/// instead.
+fn interrupt_and_wait(pid: libc::pid_t) -> Result<bool, Error> {
+	if unsafe { libc::ptrace(libc::PTRACE_INTERRUPT, pid, 0, 0) } == -1 {
+		return Err(Error::last_os_error(
+			ErrorKind::AttachFailed,
+			format!("interrupt tid {}", pid),
+		));
+	}
+
+	let mut status = 0;
+	while unsafe { libc::waitpid(pid, &mut status, libc::__WALL) } == -1 {
+		let e = std::io::Error::last_os_error();
+		if e.kind() != std::io::ErrorKind::Interrupted {
+			return Err(Error::os(ErrorKind::WaitFailed, format!("tid {}", pid), e));
+		}
+	}
+
+	Ok(libc::WIFSTOPPED(status))
+}
+
+/// Keeps every thread of a process stopped under ptrace until dropped, e.g. to take a
+/// consistent snapshot. Threads that exit or cannot be attached are skipped.
+pub struct ThreadGroupStop {
+	pid: u32,
+	tids: Vec<u32>,
+}
+
+impl ThreadGroupStop {
+	pub fn stop(pid: u32) -> Result<Self, Error> {
+		let mut stopped = Self {
+			pid,
+			tids: Vec::new(),
+		};
+		let mut last_error = None;
+
+		for thread in list_threads(pid)? {
+			let tid = thread.tid as libc::pid_t;
+			if unsafe { libc::ptrace(libc::PTRACE_SEIZE, tid, 0, 0) } == -1 {
+				last_error = Some(Error::last_os_error(
+					ErrorKind::AttachFailed,
+					format!("tid {}", tid),
+				));
+				continue;
+			}
+
+			// Detach on drop even if the interrupt fails, as the seize itself succeeded.
+			stopped.tids.push(thread.tid);
+
+			match interrupt_and_wait(tid) {
+				Ok(true) => {}
+				Ok(false) => {
+					stopped.tids.pop();
+				}
+				Err(e) => last_error = Some(e),
+			}
+		}
+
+		match (stopped.tids.is_empty(), last_error) {
+			(true, Some(e)) => Err(e),
+			_ => Ok(stopped),
+		}
+	}
+
+	pub fn pid(&self) -> u32 {
+		self.pid
+	}
+
+	pub fn tids(&self) -> &[u32] {
+		&self.tids
+	}
+
+	/// Registers of every stopped thread, main thread first.
+	pub fn registers(&self) -> Vec<ThreadRegisters> {
+		let mut regs: Vec<ThreadRegisters> = self
+			.tids
+			.iter()
+			.filter_map(|tid| get_stopped_thread_registers(*tid).ok())
+			.collect();
+
+		regs.sort_by_key(|r| (r.tid != self.pid, r.tid));
+		regs
+	}
+}
+
+impl Drop for ThreadGroupStop {
+	fn drop(&mut self) {
+		for tid in &self.tids {
+			unsafe { libc::ptrace(libc::PTRACE_DETACH, *tid as libc::pid_t, 0, 0) };
+		}
+	}
+}
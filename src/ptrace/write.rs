@@ -1 +1,28 @@
+use crate::{errors::Error, pod::Pod, traits::WriteProcessMemory};
 
+use super::mem::{PtraceMem, WORD_SIZE, peek, poke};
+
+impl WriteProcessMemory for PtraceMem {
+	/// Read-modify-writes every word the value touches.
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error> {
+		self.maps.can_write(addr, size_of::<T>())?;
+
+		let bytes = value.as_bytes();
+
+		let mut done = 0;
+		while done < bytes.len() {
+			let current = addr + done as u64;
+			let word_addr = current - current % WORD_SIZE;
+			let skip = (current - word_addr) as usize;
+			let len = (WORD_SIZE as usize - skip).min(bytes.len() - done);
+
+			let mut word = peek(self.pid, word_addr)?.to_le_bytes();
+			word[skip..skip + len].copy_from_slice(&bytes[done..done + len]);
+			poke(self.pid, word_addr, u64::from_le_bytes(word))?;
+
+			done += len;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,345 @@
+//! Decoders for the standard containers of C++ and Rust targets, returning local copies.
+//!
+//! Layouts are those of 64-bit Linux: libstdc++ with the C++11 ABI for `std::string` and
+//! `std::vector`, and `RustVecLayout` for Rust's `String` and `Vec`. Every decoder checks the
+//! header it reads for consistency and refuses containers larger than `MAX_CONTAINER_BYTES`, so
+//! pointing one at the wrong address fails instead of reading gigabytes of garbage.
+//!
+//! ```ignore
+//! use lime::remote::containers::{read_std_string, read_std_vector};
+//!
+//! let name = read_std_string(&mut process, player + 0x28)?;
+//! let inventory: Vec<Item> = read_std_vector(&mut process, player + 0x48)?;
+//! ```
+
+use super::{ptr::RemoteSlice, structs::RemoteStruct};
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::Pod,
+	traits::ReadProcessMemory,
+};
+
+/// Largest container the decoders will read, in bytes.
+pub const MAX_CONTAINER_BYTES: usize = 1 << 24;
+
+/// Size of a libstdc++ `std::string`: pointer, length and a 16 byte inline buffer.
+pub const STD_STRING_SIZE: usize = 32;
+
+/// Size of a libstdc++ `std::vector`: begin, end and end of storage pointers.
+pub const STD_VECTOR_SIZE: usize = 24;
+
+fn bad_header(addr: u64, what: &str, message: String) -> Error {
+	Error::new(
+		ErrorKind::BadDataType,
+		format!("not a {}: {}", what, message),
+	)
+	.with_addr(addr)
+}
+
+fn check_size(addr: u64, what: &str, size: u64) -> Result<usize, Error> {
+	if size > MAX_CONTAINER_BYTES as u64 {
+		return Err(bad_header(
+			addr,
+			what,
+			format!(
+				"{} bytes is over the limit of {}",
+				size, MAX_CONTAINER_BYTES
+			),
+		));
+	}
+	Ok(size as usize)
+}
+
+fn utf8(addr: u64, bytes: Vec<u8>) -> Result<String, Error> {
+	String::from_utf8(bytes).map_err(|e| {
+		Error::new(
+			ErrorKind::BadDataType,
+			format!("string is not UTF-8: {}", e),
+		)
+		.with_addr(addr)
+	})
+}
+
+/// The bytes of the libstdc++ `std::string` at `addr`.
+///
+/// Short strings live in the string itself (the small string optimization); those are
+/// recognised by the data pointer pointing 16 bytes into the string.
+pub fn read_std_string_bytes<P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	addr: u64,
+) -> Result<Vec<u8>, Error> {
+	const WHAT: &str = "std::string";
+	let [data, len, capacity, _] = <[u64; 4]>::read_remote(process, addr)?;
+	let local = addr + 16;
+
+	let len = check_size(addr, WHAT, len)?;
+	if data == local {
+		if len > 15 {
+			return Err(bad_header(
+				addr,
+				WHAT,
+				format!("inline string of {} bytes", len),
+			));
+		}
+	} else if (capacity as usize) < len {
+		return Err(bad_header(
+			addr,
+			WHAT,
+			format!("length {} above capacity {}", len, capacity),
+		));
+	}
+	RemoteSlice::<u8, _>::new(process, data, len).to_vec()
+}
+
+/// The libstdc++ `std::string` at `addr`, which must be UTF-8.
+pub fn read_std_string<P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	addr: u64,
+) -> Result<String, Error> {
+	utf8(addr, read_std_string_bytes(process, addr)?)
+}
+
+/// The elements of the libstdc++ `std::vector<T>` at `addr`.
+///
+/// `T` must have the element's size in the target, padding included.
+pub fn read_std_vector<T: RemoteStruct, P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	addr: u64,
+) -> Result<Vec<T>, Error> {
+	const WHAT: &str = "std::vector";
+	let [begin, end, storage_end] = <[u64; 3]>::read_remote(process, addr)?;
+	if begin > end || end > storage_end {
+		return Err(bad_header(
+			addr,
+			WHAT,
+			format!(
+				"pointers {:#x}, {:#x} and {:#x} out of order",
+				begin, end, storage_end
+			),
+		));
+	}
+
+	let size = check_size(addr, WHAT, end - begin)?;
+	let len = match T::SIZE {
+		0 => 0,
+		element if size % element != 0 => {
+			return Err(bad_header(
+				addr,
+				WHAT,
+				format!("{} bytes of {} byte elements", size, element),
+			));
+		}
+		element => size / element,
+	};
+	RemoteSlice::new(process, begin, len).to_vec()
+}
+
+/// Where the fields of a Rust `Vec` (and `String`, which wraps one) are, in 8 byte words.
+///
+/// Rust does not guarantee a layout, so it depends on the compiler the target was built with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RustVecLayout {
+	pub ptr: usize,
+	pub capacity: usize,
+	pub len: usize,
+}
+
+impl RustVecLayout {
+	/// Capacity, pointer, length: what current compilers emit.
+	pub const CAP_PTR_LEN: Self = Self {
+		capacity: 0,
+		ptr: 1,
+		len: 2,
+	};
+
+	/// Pointer, capacity, length: compilers up to about 1.66.
+	pub const PTR_CAP_LEN: Self = Self {
+		ptr: 0,
+		capacity: 1,
+		len: 2,
+	};
+}
+
+impl Default for RustVecLayout {
+	fn default() -> Self {
+		Self::CAP_PTR_LEN
+	}
+}
+
+/// The elements of the Rust `Vec<T>` at `addr`.
+pub fn read_rust_vec<T: RemoteStruct, P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	addr: u64,
+	layout: RustVecLayout,
+) -> Result<Vec<T>, Error> {
+	const WHAT: &str = "Vec";
+	let words = <[u64; 3]>::read_remote(process, addr)?;
+	let field = |index: usize| {
+		words.get(index).copied().ok_or_else(|| {
+			Error::new(
+				ErrorKind::BadDataType,
+				format!("Vec field {} outside of the 3 words", index),
+			)
+		})
+	};
+	let (ptr, capacity, len) = (
+		field(layout.ptr)?,
+		field(layout.capacity)?,
+		field(layout.len)?,
+	);
+
+	if len > capacity && T::SIZE != 0 {
+		return Err(bad_header(
+			addr,
+			WHAT,
+			format!("length {} above capacity {}", len, capacity),
+		));
+	}
+	// Even empty vectors hold a dangling, non-null pointer.
+	if ptr == 0 {
+		return Err(bad_header(addr, WHAT, "null pointer".to_string()));
+	}
+	check_size(addr, WHAT, len.saturating_mul(T::SIZE as u64))?;
+	RemoteSlice::new(process, ptr, len as usize).to_vec()
+}
+
+/// The bytes of the Rust `String` at `addr`.
+pub fn read_rust_string<P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	addr: u64,
+	layout: RustVecLayout,
+) -> Result<String, Error> {
+	utf8(addr, read_rust_vec(process, addr, layout)?)
+}
+
+/// The `len` elements of the C array whose address is stored at `ptr_addr`, as in
+/// `T *items;`.
+pub fn read_c_array<T: RemoteStruct, P: ReadProcessMemory + ?Sized>(
+	process: &mut P,
+	ptr_addr: u64,
+	len: usize,
+) -> Result<Vec<T>, Error> {
+	let ptr = u64::read_remote(process, ptr_addr)?;
+	check_size(
+		ptr_addr,
+		"C array",
+		(len as u64).saturating_mul(T::SIZE as u64),
+	)?;
+	RemoteSlice::new(process, ptr, len).to_vec()
+}
+
+/// Like `read_c_array`, reading the length from `len_addr` as a `L`, e.g. `i32` for the
+/// `int count;` next to `T *items;`. Negative lengths are errors.
+pub fn read_c_array_with_len<T, L, P>(
+	process: &mut P,
+	ptr_addr: u64,
+	len_addr: u64,
+) -> Result<Vec<T>, Error>
+where
+	T: RemoteStruct,
+	L: Pod + TryInto<usize> + std::fmt::Display,
+	P: ReadProcessMemory + ?Sized,
+{
+	let len: L = process.read_value(len_addr)?;
+	let len = len
+		.try_into()
+		.map_err(|_| bad_header(len_addr, "C array", format!("invalid length {}", len)))?;
+	read_c_array(process, ptr_addr, len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	fn process() -> MockProcess {
+		MockProcess::new(1).with_region(0x1000, 0x2000, "rw-p", None)
+	}
+
+	fn poke_words(process: &mut MockProcess, addr: u64, words: &[u64]) {
+		for (i, word) in words.iter().enumerate() {
+			process.poke(addr + 8 * i as u64, &word.to_ne_bytes());
+		}
+	}
+
+	#[test]
+	fn std_strings_inline_and_on_the_heap() {
+		let mut process = process();
+		poke_words(&mut process, 0x1000, &[0x1010, 5]);
+		process.poke(0x1010, b"short\0");
+		assert_eq!(read_std_string(&mut process, 0x1000).unwrap(), "short");
+
+		let long = "a string too long for the inline buffer";
+		poke_words(&mut process, 0x1100, &[0x1800, long.len() as u64, 64]);
+		process.poke(0x1800, long.as_bytes());
+		assert_eq!(read_std_string(&mut process, 0x1100).unwrap(), long);
+
+		poke_words(&mut process, 0x1200, &[0x1210, 16]);
+		let err = read_std_string(&mut process, 0x1200).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::BadDataType);
+		poke_words(&mut process, 0x1200, &[0x1800, 1 << 40, 1 << 40]);
+		assert!(read_std_string(&mut process, 0x1200).is_err());
+	}
+
+	#[test]
+	fn std_vectors() {
+		let mut process = process();
+		poke_words(&mut process, 0x1000, &[0x1800, 0x180c, 0x1810]);
+		for (i, v) in [7u32, 8, 9].iter().enumerate() {
+			process.poke(0x1800 + 4 * i as u64, &v.to_ne_bytes());
+		}
+		assert_eq!(
+			read_std_vector::<u32, _>(&mut process, 0x1000).unwrap(),
+			[7, 8, 9]
+		);
+		assert!(read_std_vector::<u64, _>(&mut process, 0x1000).is_err());
+
+		poke_words(&mut process, 0x1000, &[0, 0, 0]);
+		assert!(
+			read_std_vector::<u32, _>(&mut process, 0x1000)
+				.unwrap()
+				.is_empty()
+		);
+		poke_words(&mut process, 0x1000, &[0x1810, 0x1800, 0x1820]);
+		assert!(read_std_vector::<u32, _>(&mut process, 0x1000).is_err());
+	}
+
+	#[test]
+	fn rust_strings_and_vecs() {
+		let mut process = process();
+		poke_words(&mut process, 0x1000, &[16, 0x1800, 5]);
+		process.poke(0x1800, b"hello");
+		let layout = RustVecLayout::default();
+		assert_eq!(
+			read_rust_string(&mut process, 0x1000, layout).unwrap(),
+			"hello"
+		);
+		assert_eq!(
+			read_rust_vec::<u8, _>(&mut process, 0x1000, layout).unwrap(),
+			b"hello"
+		);
+
+		poke_words(&mut process, 0x1000, &[0x1800, 16, 5]);
+		assert_eq!(
+			read_rust_string(&mut process, 0x1000, RustVecLayout::PTR_CAP_LEN).unwrap(),
+			"hello"
+		);
+		assert!(read_rust_string(&mut process, 0x1000, layout).is_err());
+	}
+
+	#[test]
+	fn c_arrays() {
+		let mut process = process();
+		poke_words(&mut process, 0x1000, &[0x1800]);
+		process.poke(0x1008, &2i32.to_ne_bytes());
+		process.poke(0x1800, &[1, 0, 2, 0]);
+		assert_eq!(
+			read_c_array_with_len::<u16, i32, _>(&mut process, 0x1000, 0x1008).unwrap(),
+			[1, 2]
+		);
+
+		process.poke(0x1008, &(-1i32).to_ne_bytes());
+		let err = read_c_array_with_len::<u16, i32, _>(&mut process, 0x1000, 0x1008).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::BadDataType);
+	}
+}
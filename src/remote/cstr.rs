@@ -0,0 +1,154 @@
+//! NUL terminated strings in another process.
+
+use super::ptr::check_read;
+use crate::{
+	errors::{Error, ErrorKind},
+	maps::ProcMemoryMaps,
+	traits::ReadProcessMemory,
+};
+
+/// Bytes read before giving up on finding the terminator.
+pub const DEFAULT_MAX_LEN: usize = 0x1000;
+
+const PAGE_SIZE: u64 = 0x1000;
+
+/// A C string at an address in the target.
+///
+/// The string is read a page at a time up to its terminator, so a string that ends right
+/// before unmapped memory reads fine.
+pub struct RemoteCStr<'p, P: ?Sized> {
+	process: &'p mut P,
+	maps: Option<&'p ProcMemoryMaps>,
+	addr: u64,
+	max_len: usize,
+}
+
+impl<'p, P: ReadProcessMemory + ?Sized> RemoteCStr<'p, P> {
+	pub fn new(process: &'p mut P, addr: u64) -> Self {
+		Self {
+			process,
+			maps: None,
+			addr,
+			max_len: DEFAULT_MAX_LEN,
+		}
+	}
+
+	/// Checks every read against `maps` before it is made.
+	pub fn with_maps(mut self, maps: &'p ProcMemoryMaps) -> Self {
+		self.maps = Some(maps);
+		self
+	}
+
+	/// Fails strings longer than `max_len` bytes, terminator excluded, instead of reading on.
+	pub fn with_max_len(mut self, max_len: usize) -> Self {
+		self.max_len = max_len;
+		self
+	}
+
+	pub fn addr(&self) -> u64 {
+		self.addr
+	}
+
+	pub fn is_null(&self) -> bool {
+		self.addr == 0
+	}
+
+	/// The bytes before the terminator.
+	pub fn to_bytes(&mut self) -> Result<Vec<u8>, Error> {
+		let mut bytes = Vec::new();
+		let mut addr = self.addr;
+		loop {
+			let page_left = PAGE_SIZE - addr % PAGE_SIZE;
+			let want = (page_left as usize).min(self.max_len.saturating_add(1) - bytes.len());
+			check_read(self.maps, addr, want)?;
+
+			let start = bytes.len();
+			bytes.resize(start + want, 0);
+			let read = self.process.read_bytes(addr, &mut bytes[start..])?;
+			if let Some(len) = bytes[start..start + read].iter().position(|&b| b == 0) {
+				bytes.truncate(start + len);
+				return Ok(bytes);
+			}
+			if read < want {
+				return Err(Error::new(
+					ErrorKind::ReadFailed,
+					format!("string unreadable after {} bytes", start + read),
+				)
+				.with_addr(self.addr));
+			}
+			if bytes.len() > self.max_len {
+				return Err(Error::new(
+					ErrorKind::BadDataType,
+					format!("no terminator in the first {} bytes", self.max_len),
+				)
+				.with_addr(self.addr));
+			}
+			addr += page_left;
+		}
+	}
+
+	/// The string, which must be UTF-8.
+	pub fn read(&mut self) -> Result<String, Error> {
+		String::from_utf8(self.to_bytes()?).map_err(|e| {
+			Error::new(
+				ErrorKind::BadDataType,
+				format!("string is not UTF-8: {}", e),
+			)
+			.with_addr(self.addr)
+		})
+	}
+
+	/// The string with invalid UTF-8 replaced by U+FFFD.
+	pub fn read_lossy(&mut self) -> Result<String, Error> {
+		Ok(String::from_utf8_lossy(&self.to_bytes()?).into_owned())
+	}
+
+	/// The string, `None` for null pointers.
+	pub fn read_opt(&mut self) -> Result<Option<String>, Error> {
+		if self.is_null() {
+			return Ok(None);
+		}
+		self.read().map(Some)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	#[test]
+	fn reads_up_to_the_terminator_across_pages() {
+		let mut process = MockProcess::new(1).with_region(0x1000, 0x2000, "rw-p", None);
+		process.poke(0x1ffc, b"hello\0");
+		process.poke(0x2ffd, b"end");
+		process.poke(0x2100, &[0xff, 0]);
+		let maps = process.get_maps().clone();
+
+		let mut s = RemoteCStr::new(&mut process, 0x1ffc).with_maps(&maps);
+		assert_eq!(s.read().unwrap(), "hello");
+		assert_eq!(
+			s.with_max_len(4).to_bytes().unwrap_err().kind(),
+			ErrorKind::BadDataType
+		);
+
+		let mut s = RemoteCStr::new(&mut process, 0x2100);
+		assert_eq!(s.read().unwrap_err().kind(), ErrorKind::BadDataType);
+		assert_eq!(s.read_lossy().unwrap(), "\u{fffd}");
+
+		let err = RemoteCStr::new(&mut process, 0x2ffd).read().unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::ReadFailed);
+		let err = RemoteCStr::new(&mut process, 0x2ffd)
+			.with_maps(&maps)
+			.read()
+			.unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::OutOfBounds);
+		assert_eq!(RemoteCStr::new(&mut process, 0).read_opt().unwrap(), None);
+
+		// A -1 sentinel, and a pointer into the last page.
+		for addr in [u64::MAX, u64::MAX - 0x10] {
+			let err = RemoteCStr::new(&mut process, addr).read().unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::OutOfBounds);
+		}
+	}
+}
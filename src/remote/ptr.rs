@@ -0,0 +1,492 @@
+//! Pointers to and arrays of values in another process, bound to the process they live in.
+//!
+//! ```ignore
+//! use lime::remote::ptr::RemotePtr;
+//!
+//! // A singly linked list of `Node { value: u32, next: u64 }`.
+//! let maps = process.get_maps().clone();
+//! let mut node = RemotePtr::<Node, _>::new(&mut process, head).with_maps(&maps);
+//! while let Some(n) = node.read_opt()? {
+//!     println!("{}", n.value);
+//!     node = node.with_addr(n.next);
+//! }
+//!
+//! let mut scores = RemotePtr::<u32, _>::new(&mut process, array).slice(16);
+//! let best = scores.iter().collect::<Result<Vec<_>, _>>()?.into_iter().max();
+//! ```
+//!
+//! Values are read whenever they are asked for, so they are never stale and never cached.
+//! Null pointers fail with `ErrorKind::InvalidAddress` before anything is read; with
+//! `with_maps` every read is also checked with `ProcMemoryMaps::can_read` first, so dangling
+//! pointers fail with `OutOfBounds` or `Protected` instead of returning a short read.
+
+use std::marker::PhantomData;
+
+use super::structs::{RemoteField, RemoteStruct};
+use crate::{
+	errors::{Error, ErrorKind},
+	maps::ProcMemoryMaps,
+	traits::{ReadProcessMemory, WriteProcessMemory},
+};
+
+/// Bytes read at once when iterating over a `RemoteSlice`.
+const ITER_CHUNK: usize = 0x1000;
+
+/// Fails for null pointers, for spans past the end of the address space and, when there are
+/// maps, for memory that is not readable.
+pub(crate) fn check_read(
+	maps: Option<&ProcMemoryMaps>,
+	addr: u64,
+	size: usize,
+) -> Result<(), Error> {
+	if addr == 0 {
+		return Err(Error::new(ErrorKind::InvalidAddress, "null pointer")
+			.with_addr(addr)
+			.with_size(size));
+	}
+	if addr.checked_add(size as u64).is_none() {
+		return Err(out_of_bounds(addr, size));
+	}
+	match maps {
+		Some(maps) => maps.can_read(addr, size),
+		None => Ok(()),
+	}
+}
+
+fn out_of_bounds(addr: u64, size: usize) -> Error {
+	Error::new(
+		ErrorKind::OutOfBounds,
+		"past the end of the address space".to_string(),
+	)
+	.with_addr(addr)
+	.with_size(size)
+}
+
+fn short_read(addr: u64, read: usize, size: usize) -> Error {
+	Error::new(
+		ErrorKind::ReadFailed,
+		format!("only {} of {} bytes readable", read, size),
+	)
+	.with_addr(addr)
+	.with_size(size)
+}
+
+/// A `T` at an address in the target.
+pub struct RemotePtr<'p, T, P: ?Sized> {
+	process: &'p mut P,
+	maps: Option<&'p ProcMemoryMaps>,
+	addr: u64,
+	_target: PhantomData<fn() -> T>,
+}
+
+impl<'p, T: RemoteStruct, P: ReadProcessMemory + ?Sized> RemotePtr<'p, T, P> {
+	pub fn new(process: &'p mut P, addr: u64) -> Self {
+		Self {
+			process,
+			maps: None,
+			addr,
+			_target: PhantomData,
+		}
+	}
+
+	/// Checks every read against `maps` before it is made.
+	pub fn with_maps(mut self, maps: &'p ProcMemoryMaps) -> Self {
+		self.maps = Some(maps);
+		self
+	}
+
+	/// The same process and maps, pointing somewhere else, e.g. the next node of a list.
+	pub fn with_addr(mut self, addr: u64) -> Self {
+		self.addr = addr;
+		self
+	}
+
+	/// Reinterprets the target as a `U`.
+	pub fn cast<U: RemoteStruct>(self) -> RemotePtr<'p, U, P> {
+		RemotePtr {
+			process: self.process,
+			maps: self.maps,
+			addr: self.addr,
+			_target: PhantomData,
+		}
+	}
+
+	pub fn addr(&self) -> u64 {
+		self.addr
+	}
+
+	pub fn is_null(&self) -> bool {
+		self.addr == 0
+	}
+
+	/// Whether a `T` can be read here: not null and, with maps, inside a readable region.
+	pub fn check(&self) -> Result<(), Error> {
+		check_read(self.maps, self.addr, T::SIZE)
+	}
+
+	pub fn is_valid(&self) -> bool {
+		self.check().is_ok()
+	}
+
+	/// Points `count` elements further, like `pointer::wrapping_offset`.
+	pub fn offset(self, count: isize) -> Self {
+		let addr = self
+			.addr
+			.wrapping_add_signed((count as i64).wrapping_mul(T::SIZE as i64));
+		self.with_addr(addr)
+	}
+
+	/// Reads the `T` pointed to.
+	pub fn read(&mut self) -> Result<T, Error> {
+		self.check()?;
+		T::read_remote(self.process, self.addr)
+	}
+
+	/// Reads the `T` pointed to, `None` for null pointers.
+	pub fn read_opt(&mut self) -> Result<Option<T>, Error> {
+		if self.is_null() {
+			return Ok(None);
+		}
+		self.read().map(Some)
+	}
+
+	/// `len` consecutive `T`s starting here.
+	pub fn slice(self, len: usize) -> RemoteSlice<'p, T, P> {
+		RemoteSlice {
+			process: self.process,
+			maps: self.maps,
+			addr: self.addr,
+			len,
+			_target: PhantomData,
+		}
+	}
+}
+
+impl<'p, T: RemoteStruct, P: ReadProcessMemory + ?Sized> RemotePtr<'p, RemotePtrAddr<T>, P> {
+	/// Follows a pointer to a pointer: reads the address stored here and points there.
+	pub fn follow(mut self) -> Result<RemotePtr<'p, T, P>, Error> {
+		let addr = self.read()?.0;
+		Ok(self.with_addr(addr).cast())
+	}
+}
+
+impl<T: RemoteField, P: ReadProcessMemory + WriteProcessMemory + ?Sized> RemotePtr<'_, T, P> {
+	/// Writes `value` where the pointer points.
+	pub fn write(&mut self, value: &T) -> Result<(), Error> {
+		if self.is_null() {
+			return Err(Error::new(ErrorKind::InvalidAddress, "null pointer")
+				.with_addr(self.addr)
+				.with_size(T::SIZE));
+		}
+		if self.addr.checked_add(T::SIZE as u64).is_none() {
+			return Err(out_of_bounds(self.addr, T::SIZE));
+		}
+		if let Some(maps) = self.maps {
+			maps.can_write(self.addr, T::SIZE)?;
+		}
+		value.write_remote(self.process, self.addr)
+	}
+}
+
+/// A pointer stored in the target, for `RemotePtr::follow` through pointers to pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemotePtrAddr<T>(pub u64, PhantomData<fn() -> T>);
+
+impl<T> RemotePtrAddr<T> {
+	pub fn new(addr: u64) -> Self {
+		Self(addr, PhantomData)
+	}
+}
+
+impl<T> RemoteField for RemotePtrAddr<T> {
+	const SIZE: usize = 8;
+
+	fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		u64::decode(bytes).map(Self::new)
+	}
+
+	fn encode(&self, bytes: &mut [u8]) {
+		self.0.encode(bytes);
+	}
+}
+
+/// `len` consecutive `T`s in the target.
+pub struct RemoteSlice<'p, T, P: ?Sized> {
+	process: &'p mut P,
+	maps: Option<&'p ProcMemoryMaps>,
+	addr: u64,
+	len: usize,
+	_target: PhantomData<fn() -> T>,
+}
+
+impl<'p, T: RemoteStruct, P: ReadProcessMemory + ?Sized> RemoteSlice<'p, T, P> {
+	pub fn new(process: &'p mut P, addr: u64, len: usize) -> Self {
+		RemotePtr::new(process, addr).slice(len)
+	}
+
+	/// Checks every read against `maps` before it is made.
+	pub fn with_maps(mut self, maps: &'p ProcMemoryMaps) -> Self {
+		self.maps = Some(maps);
+		self
+	}
+
+	pub fn addr(&self) -> u64 {
+		self.addr
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Bytes the elements take in the target.
+	pub fn size(&self) -> usize {
+		self.len.saturating_mul(T::SIZE)
+	}
+
+	/// Whether the whole slice can be read: not null and, with maps, inside a readable region.
+	/// Empty slices are always valid.
+	pub fn check(&self) -> Result<(), Error> {
+		if self.is_empty() {
+			return Ok(());
+		}
+		check_read(self.maps, self.addr, self.checked_size()?)
+	}
+
+	fn checked_size(&self) -> Result<usize, Error> {
+		self.len
+			.checked_mul(T::SIZE)
+			.ok_or_else(|| out_of_bounds(self.addr, usize::MAX))
+	}
+
+	pub fn is_valid(&self) -> bool {
+		self.check().is_ok()
+	}
+
+	fn element_addr(&self, index: usize) -> Result<u64, Error> {
+		if index >= self.len {
+			return Err(Error::new(
+				ErrorKind::OutOfBounds,
+				format!("index {} out of bounds for length {}", index, self.len),
+			)
+			.with_addr(self.addr));
+		}
+		index
+			.checked_mul(T::SIZE)
+			.and_then(|offset| self.addr.checked_add(offset as u64))
+			.ok_or_else(|| out_of_bounds(self.addr, self.size()))
+	}
+
+	/// Reads the element at `index`.
+	pub fn get(&mut self, index: usize) -> Result<T, Error> {
+		let addr = self.element_addr(index)?;
+		check_read(self.maps, addr, T::SIZE)?;
+		T::read_remote(self.process, addr)
+	}
+
+	/// A pointer to the element at `index`, which borrows the process from the slice.
+	pub fn at(&mut self, index: usize) -> Result<RemotePtr<'_, T, P>, Error> {
+		let addr = self.element_addr(index)?;
+		Ok(RemotePtr {
+			process: self.process,
+			maps: self.maps,
+			addr,
+			_target: PhantomData,
+		})
+	}
+
+	/// Reads every element with a single `read_bytes` call.
+	pub fn to_vec(&mut self) -> Result<Vec<T>, Error> {
+		// Empty Rust vectors point at a dangling address, which is not even read.
+		if self.is_empty() {
+			return Ok(Vec::new());
+		}
+		self.check()?;
+		let mut bytes = vec![0u8; self.checked_size()?];
+		let read = self.process.read_bytes(self.addr, &mut bytes)?;
+		if read < bytes.len() {
+			return Err(short_read(self.addr, read, bytes.len()));
+		}
+		(0..self.len)
+			.map(|i| T::from_remote_bytes(&bytes[i * T::SIZE..], self.process))
+			.collect()
+	}
+
+	/// Iterates over the elements, reading a page worth of them at a time. The iterator ends
+	/// after the first error.
+	pub fn iter(&mut self) -> RemoteSliceIter<'_, T, P> {
+		RemoteSliceIter {
+			process: self.process,
+			maps: self.maps,
+			addr: self.addr,
+			remaining: self.len,
+			buffer: Vec::new(),
+			offset: 0,
+			buffered: 0,
+			failed: false,
+			_target: PhantomData,
+		}
+	}
+}
+
+pub struct RemoteSliceIter<'a, T, P: ?Sized> {
+	process: &'a mut P,
+	maps: Option<&'a ProcMemoryMaps>,
+	/// Address of the first element not yet in `buffer`.
+	addr: u64,
+	/// Elements not yet in `buffer`.
+	remaining: usize,
+	buffer: Vec<u8>,
+	offset: usize,
+	/// Elements left in `buffer`, from `offset` on.
+	buffered: usize,
+	failed: bool,
+	_target: PhantomData<fn() -> T>,
+}
+
+impl<T: RemoteStruct, P: ReadProcessMemory + ?Sized> RemoteSliceIter<'_, T, P> {
+	fn fill(&mut self) -> Result<(), Error> {
+		let count = (ITER_CHUNK / T::SIZE.max(1)).clamp(1, self.remaining);
+		let size = count * T::SIZE;
+		check_read(self.maps, self.addr, size)?;
+
+		self.buffer.resize(size, 0);
+		let read = self.process.read_bytes(self.addr, &mut self.buffer)?;
+		if read < size {
+			return Err(short_read(self.addr, read, size));
+		}
+		self.addr += size as u64;
+		self.remaining -= count;
+		self.offset = 0;
+		self.buffered = count;
+		Ok(())
+	}
+}
+
+impl<T: RemoteStruct, P: ReadProcessMemory + ?Sized> Iterator for RemoteSliceIter<'_, T, P> {
+	type Item = Result<T, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.failed {
+			return None;
+		}
+		if self.buffered == 0 {
+			if self.remaining == 0 {
+				return None;
+			}
+			if let Err(e) = self.fill() {
+				self.failed = true;
+				return Some(Err(e));
+			}
+		}
+
+		let element = &self.buffer[self.offset..self.offset + T::SIZE];
+		self.offset += T::SIZE;
+		self.buffered -= 1;
+		let value = T::from_remote_bytes(element, self.process);
+		self.failed = value.is_err();
+		Some(value)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		if self.failed {
+			return (0, Some(0));
+		}
+		(0, Some(self.buffered + self.remaining))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	fn process() -> MockProcess {
+		let mut process = MockProcess::new(1)
+			.with_region(0x1000, 0x3000, "rw-p", None)
+			.with_region(0x8000, 0x1000, "---p", None);
+		for i in 0..0x800u32 {
+			process.poke(0x1000 + 4 * i as u64, &i.to_ne_bytes());
+		}
+		process
+	}
+
+	#[test]
+	fn pointers_read_index_and_validate() {
+		let mut process = process();
+		let maps = process.get_maps().clone();
+
+		let mut ptr = RemotePtr::<u32, _>::new(&mut process, 0x1000)
+			.with_maps(&maps)
+			.offset(3);
+		assert_eq!(ptr.addr(), 0x100c);
+		assert_eq!(ptr.read().unwrap(), 3);
+
+		let mut ptr = ptr.with_addr(0);
+		assert_eq!(ptr.read_opt().unwrap(), None);
+		assert_eq!(ptr.read().unwrap_err().kind(), ErrorKind::InvalidAddress);
+
+		let ptr = ptr.with_addr(0x8000);
+		assert_eq!(ptr.check().unwrap_err().kind(), ErrorKind::Protected);
+		assert!(!ptr.with_addr(0x3ffe).is_valid());
+	}
+
+	#[test]
+	fn slices_iterate_in_chunks() {
+		let mut process = process();
+		let mut slice = RemoteSlice::<u32, _>::new(&mut process, 0x1000, 0x800);
+		assert_eq!(slice.get(0x7ff).unwrap(), 0x7ff);
+		assert_eq!(slice.get(0x800).unwrap_err().kind(), ErrorKind::OutOfBounds);
+
+		let values = slice.iter().collect::<Result<Vec<_>, _>>().unwrap();
+		assert_eq!(values, (0..0x800).collect::<Vec<u32>>());
+		assert_eq!(slice.to_vec().unwrap(), values);
+
+		let mut slice = RemoteSlice::<u32, _>::new(&mut process, 0x3ff8, 4);
+		let results: Vec<_> = slice.iter().collect();
+		assert_eq!(results.len(), 1);
+		assert_eq!(
+			results[0].as_ref().unwrap_err().kind(),
+			ErrorKind::ReadFailed
+		);
+	}
+
+	#[test]
+	fn writes_through_pointers() {
+		let mut process = process();
+		let mut slice = RemoteSlice::<u16, _>::new(&mut process, 0x2000, 4);
+		slice.at(2).unwrap().write(&0xbeef).unwrap();
+		assert_eq!(slice.get(2).unwrap(), 0xbeef);
+
+		let mut ptr = RemotePtr::<u64, _>::new(&mut process, 0x2000);
+		ptr.write(&0x1004).unwrap();
+		let mut ptr = ptr.cast::<RemotePtrAddr<u32>>().follow().unwrap();
+		assert_eq!(ptr.read().unwrap(), 1);
+	}
+
+	#[test]
+	fn garbage_addresses_fail_instead_of_overflowing() {
+		let mut process = process();
+		let mut ptr = RemotePtr::<u64, _>::new(&mut process, u64::MAX);
+		assert_eq!(ptr.read().unwrap_err().kind(), ErrorKind::OutOfBounds);
+		assert_eq!(ptr.write(&0).unwrap_err().kind(), ErrorKind::OutOfBounds);
+
+		let mut slice = RemoteSlice::<u64, _>::new(&mut process, u64::MAX - 0xf, 4);
+		assert_eq!(slice.get(3).unwrap_err().kind(), ErrorKind::OutOfBounds);
+		assert_eq!(slice.to_vec().unwrap_err().kind(), ErrorKind::OutOfBounds);
+		assert_eq!(
+			slice.iter().next().unwrap().unwrap_err().kind(),
+			ErrorKind::OutOfBounds
+		);
+
+		let mut slice = RemoteSlice::<u64, _>::new(&mut process, 0x1000, usize::MAX);
+		assert_eq!(slice.to_vec().unwrap_err().kind(), ErrorKind::OutOfBounds);
+		assert_eq!(
+			slice.get(usize::MAX - 1).unwrap_err().kind(),
+			ErrorKind::OutOfBounds
+		);
+	}
+}
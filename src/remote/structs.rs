@@ -0,0 +1,291 @@
+//! Typed views of structs in another process' memory.
+//!
+//! `#[derive(RemoteStruct)]` (with the `derive` feature) describes where each field lives:
+//!
+//! ```ignore
+//! use lime::remote::structs::RemoteStruct;
+//!
+//! #[derive(RemoteStruct)]
+//! struct Vec3 {
+//!     x: f32,
+//!     y: f32,
+//!     z: f32,
+//! }
+//!
+//! #[derive(RemoteStruct)]
+//! #[remote(size = 0x80)]
+//! struct Player {
+//!     #[remote(offset = 0x10)]
+//!     health: i32,
+//!     #[remote(padding = 4)]
+//!     position: Vec3,
+//!     #[remote(offset = 0x40, pointer)]
+//!     weapon: Option<Weapon>,
+//! }
+//!
+//! let player = Player::read_remote(&mut process, addr)?;
+//! Player::write_health(&mut process, addr, &100)?;
+//! ```
+//!
+//! Fields without `offset` follow the previous one, after `padding` bytes, without any
+//! alignment. `size` sets the size of the whole struct when it has trailing fields that are
+//! not described. Pointer fields are 8 bytes and are followed when the struct is read, with
+//! `Option` for pointers that can be null; they cannot be written back. Structs without pointer
+//! fields can be nested inline and used in arrays.
+
+use crate::{
+	errors::{Error, ErrorKind},
+	pod::{Be, Endian, Le, Pod},
+	traits::{ReadProcessMemory, WriteProcessMemory},
+};
+
+#[cfg(feature = "derive")]
+pub use lime_derive::RemoteStruct;
+
+/// A value stored inline in the target, with a fixed size and no pointers to follow.
+pub trait RemoteField: Sized {
+	/// Bytes the value takes in the target.
+	const SIZE: usize;
+
+	/// Whether `encode` sets every one of the `SIZE` bytes, so there are no gaps to preserve.
+	const GAPLESS: bool = false;
+
+	/// Decodes the value from exactly `SIZE` bytes.
+	fn decode(bytes: &[u8]) -> Result<Self, Error>;
+
+	/// Encodes the value into exactly `SIZE` bytes. Bytes between fields are not touched.
+	fn encode(&self, bytes: &mut [u8]);
+
+	/// Writes the value to `addr` with a single `write_bytes` call. Unless the value is
+	/// `GAPLESS`, the current bytes are read first, so the bytes between fields keep their
+	/// values in the target.
+	fn write_remote<P: ReadProcessMemory + WriteProcessMemory + ?Sized>(
+		&self,
+		process: &mut P,
+		addr: u64,
+	) -> Result<(), Error> {
+		let mut bytes = vec![0u8; Self::SIZE];
+		if !Self::GAPLESS {
+			let read = process.read_bytes(addr, &mut bytes)?;
+			if read < Self::SIZE {
+				return Err(Error::new(
+					ErrorKind::ReadFailed,
+					format!("only {} of {} bytes readable", read, Self::SIZE),
+				)
+				.with_addr(addr)
+				.with_size(Self::SIZE));
+			}
+		}
+		self.encode(&mut bytes);
+		process.write_bytes(addr, &bytes)
+	}
+}
+
+/// A struct read from the target in one go.
+pub trait RemoteStruct: Sized {
+	/// Bytes the struct takes in the target, padding included.
+	const SIZE: usize;
+
+	/// Decodes the struct from the first `SIZE` bytes of `bytes`, reading what pointer fields
+	/// point to from `process`.
+	///
+	/// Panics if `bytes` is shorter than `SIZE`.
+	fn from_remote_bytes<P: ReadProcessMemory + ?Sized>(
+		bytes: &[u8],
+		process: &mut P,
+	) -> Result<Self, Error>;
+
+	/// Reads the struct at `addr` with a single `read_bytes` call, plus one for every pointer
+	/// field that is followed.
+	fn read_remote<P: ReadProcessMemory + ?Sized>(
+		process: &mut P,
+		addr: u64,
+	) -> Result<Self, Error> {
+		let mut bytes = vec![0u8; Self::SIZE];
+		let read = process.read_bytes(addr, &mut bytes)?;
+		if read < Self::SIZE {
+			return Err(Error::new(
+				ErrorKind::ReadFailed,
+				format!("only {} of {} bytes readable", read, Self::SIZE),
+			)
+			.with_addr(addr)
+			.with_size(Self::SIZE));
+		}
+		Self::from_remote_bytes(&bytes, process)
+	}
+}
+
+impl<T: RemoteField> RemoteStruct for T {
+	const SIZE: usize = <T as RemoteField>::SIZE;
+
+	fn from_remote_bytes<P: ReadProcessMemory + ?Sized>(
+		bytes: &[u8],
+		_process: &mut P,
+	) -> Result<Self, Error> {
+		T::decode(&bytes[..<T as RemoteField>::SIZE])
+	}
+}
+
+/// What a `#[remote(pointer)]` field holds: the value read from where the pointer points.
+pub trait PointerTarget: Sized {
+	fn read_target<P: ReadProcessMemory + ?Sized>(
+		process: &mut P,
+		addr: u64,
+	) -> Result<Self, Error>;
+}
+
+impl<T: RemoteStruct> PointerTarget for T {
+	fn read_target<P: ReadProcessMemory + ?Sized>(
+		process: &mut P,
+		addr: u64,
+	) -> Result<Self, Error> {
+		if addr == 0 {
+			return Err(Error::new(ErrorKind::InvalidAddress, "null pointer").with_addr(addr));
+		}
+		T::read_remote(process, addr)
+	}
+}
+
+/// `None` for null pointers.
+impl<T: PointerTarget> PointerTarget for Option<T> {
+	fn read_target<P: ReadProcessMemory + ?Sized>(
+		process: &mut P,
+		addr: u64,
+	) -> Result<Self, Error> {
+		match addr {
+			0 => Ok(None),
+			addr => T::read_target(process, addr).map(Some),
+		}
+	}
+}
+
+fn expect_len(bytes: &[u8], size: usize) -> Result<(), Error> {
+	if bytes.len() != size {
+		return Err(Error::new(
+			ErrorKind::BadDataType,
+			format!("expected {} bytes, got {}", size, bytes.len()),
+		));
+	}
+	Ok(())
+}
+
+macro_rules! impl_remote_field {
+	($($t:ty),*) => {
+		$(
+			impl RemoteField for $t {
+				const SIZE: usize = size_of::<$t>();
+				const GAPLESS: bool = true;
+
+				fn decode(bytes: &[u8]) -> Result<Self, Error> {
+					expect_len(bytes, <Self as RemoteField>::SIZE)?;
+					Ok(<$t>::from_ne_bytes(bytes.try_into().unwrap()))
+				}
+
+				fn encode(&self, bytes: &mut [u8]) {
+					bytes.copy_from_slice(&self.to_ne_bytes());
+				}
+			}
+		)*
+	};
+}
+
+impl_remote_field!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+macro_rules! impl_byte_order_field {
+	($($wrapper:ident),*) => {
+		$(
+			impl<T: Endian> RemoteField for $wrapper<T> {
+				const SIZE: usize = size_of::<Self>();
+				const GAPLESS: bool = true;
+
+				fn decode(bytes: &[u8]) -> Result<Self, Error> {
+					expect_len(bytes, <Self as RemoteField>::SIZE)?;
+					Ok(Self::from_bytes(bytes).unwrap())
+				}
+
+				fn encode(&self, bytes: &mut [u8]) {
+					bytes.copy_from_slice(self.as_bytes());
+				}
+			}
+		)*
+	};
+}
+
+impl_byte_order_field!(Le, Be);
+
+impl RemoteField for bool {
+	const SIZE: usize = 1;
+	const GAPLESS: bool = true;
+
+	fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		expect_len(bytes, <Self as RemoteField>::SIZE)?;
+		match bytes[0] {
+			0 => Ok(false),
+			1 => Ok(true),
+			b => Err(Error::new(
+				ErrorKind::BadDataType,
+				format!("{:#x} is not a bool", b),
+			)),
+		}
+	}
+
+	fn encode(&self, bytes: &mut [u8]) {
+		bytes[0] = *self as u8;
+	}
+}
+
+impl<T: RemoteField, const N: usize> RemoteField for [T; N] {
+	const SIZE: usize = T::SIZE * N;
+	const GAPLESS: bool = T::GAPLESS;
+
+	fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		expect_len(bytes, <Self as RemoteField>::SIZE)?;
+		let values = (0..N)
+			.map(|i| T::decode(&bytes[i * T::SIZE..(i + 1) * T::SIZE]))
+			.collect::<Result<Vec<T>, Error>>()?;
+		Ok(values
+			.try_into()
+			.unwrap_or_else(|_| unreachable!("decoded {} values", N)))
+	}
+
+	fn encode(&self, bytes: &mut [u8]) {
+		for (i, value) in self.iter().enumerate() {
+			value.encode(&mut bytes[i * T::SIZE..(i + 1) * T::SIZE]);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	#[test]
+	fn primitives_and_arrays_round_trip() {
+		let mut bytes = [0u8; 8];
+		[1u16, 2, 3, 0xffff].encode(&mut bytes);
+		assert_eq!(<[u16; 4]>::decode(&bytes).unwrap(), [1, 2, 3, 0xffff]);
+		assert!(u32::decode(&bytes[..3]).is_err());
+
+		assert_eq!(Be::<u16>::decode(&[1, 2]).unwrap().get(), 0x102);
+		assert!(bool::decode(&[1]).unwrap());
+		assert_eq!(
+			bool::decode(&[2]).unwrap_err().kind(),
+			ErrorKind::BadDataType
+		);
+	}
+
+	#[test]
+	fn pointers_are_followed_unless_null() {
+		let mut process = MockProcess::new(1).with_region(0x1000, 0x1000, "rw-p", None);
+		process.poke(0x1010, &7u32.to_ne_bytes());
+
+		assert_eq!(u32::read_target(&mut process, 0x1010).unwrap(), 7);
+		assert_eq!(Option::<u32>::read_target(&mut process, 0).unwrap(), None);
+		assert!(u32::read_target(&mut process, 0).is_err());
+
+		let err = u64::read_remote(&mut process, 0x1ffc).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::ReadFailed);
+		assert_eq!(err.addr(), Some(0x1ffc));
+	}
+}
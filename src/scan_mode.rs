@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 pub fn bytes_to_hex_pattern(bytes: &[u8]) -> String {
 	bytes
 		.iter()
@@ -6,13 +8,6 @@ pub fn bytes_to_hex_pattern(bytes: &[u8]) -> String {
 		.join(" ")
 }
 
-fn string_to_pattern(s: &str) -> String {
-	s.bytes()
-		.map(|b| format!("{:02X}", b))
-		.collect::<Vec<_>>()
-		.join(" ")
-}
-
 fn parse_integer(input: &str) -> Result<i64, String> {
 	let s = input.trim();
 	let (neg, s) = if let Some(rest) = s.strip_prefix('-') {
@@ -37,47 +32,61 @@ fn parse_unsigned(input: &str) -> Result<u64, String> {
 	}
 }
 
-fn signed_int_to_pattern(input: &str, size: usize) -> Result<String, String> {
+fn signed_int_to_bytes(input: &str, size: usize) -> Result<Vec<u8>, String> {
 	let val = parse_integer(input)?;
-	let bytes: Vec<u8> = match size {
+	Ok(match size {
 		1 => (val as i8).to_le_bytes().to_vec(),
 		2 => (val as i16).to_le_bytes().to_vec(),
 		4 => (val as i32).to_le_bytes().to_vec(),
 		8 => val.to_le_bytes().to_vec(),
 		_ => return Err("Invalid size".to_string()),
-	};
-	Ok(bytes_to_hex_pattern(&bytes))
+	})
 }
 
-fn unsigned_int_to_pattern(input: &str, size: usize) -> Result<String, String> {
+fn unsigned_int_to_bytes(input: &str, size: usize) -> Result<Vec<u8>, String> {
 	let val = parse_unsigned(input)?;
-	let bytes: Vec<u8> = match size {
+	Ok(match size {
 		1 => (val as u8).to_le_bytes().to_vec(),
 		2 => (val as u16).to_le_bytes().to_vec(),
 		4 => (val as u32).to_le_bytes().to_vec(),
 		8 => val.to_le_bytes().to_vec(),
 		_ => return Err("Invalid size".to_string()),
-	};
-	Ok(bytes_to_hex_pattern(&bytes))
+	})
 }
 
-fn float32_to_pattern(input: &str) -> Result<String, String> {
+fn float32_to_bytes(input: &str) -> Result<Vec<u8>, String> {
 	input
 		.trim()
 		.parse::<f32>()
 		.map_err(|e| e.to_string())
-		.map(|f| bytes_to_hex_pattern(&f.to_le_bytes()))
+		.map(|f| f.to_le_bytes().to_vec())
 }
 
-fn float64_to_pattern(input: &str) -> Result<String, String> {
+fn float64_to_bytes(input: &str) -> Result<Vec<u8>, String> {
 	input
 		.trim()
 		.parse::<f64>()
 		.map_err(|e| e.to_string())
-		.map(|f| bytes_to_hex_pattern(&f.to_le_bytes()))
+		.map(|f| f.to_le_bytes().to_vec())
+}
+
+/// Hex bytes separated by whitespace, with optional `0x` prefixes. Wildcards are rejected
+/// since there is nothing to write for them.
+fn pattern_to_bytes(input: &str) -> Result<Vec<u8>, String> {
+	input
+		.split_whitespace()
+		.map(|t| {
+			let t = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")).unwrap_or(t);
+			u8::from_str_radix(t, 16).map_err(|_| format!("not a hex byte: {}", t))
+		})
+		.collect()
+}
+
+fn le<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+	bytes.get(..N)?.try_into().ok()
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanMode {
 	String,
 	Pattern,
@@ -94,9 +103,31 @@ pub enum ScanMode {
 }
 
 impl ScanMode {
+	pub const ALL: [ScanMode; 12] = [
+		Self::String,
+		Self::Pattern,
+		Self::I8,
+		Self::I16,
+		Self::I32,
+		Self::I64,
+		Self::U8,
+		Self::U16,
+		Self::U32,
+		Self::U64,
+		Self::F32,
+		Self::F64,
+	];
+
+	/// Like `from_name`, but treats anything unknown as a pattern.
+	#[allow(clippy::should_implement_trait)]
 	pub fn from_str(s: &str) -> Self {
-		match s.to_lowercase().as_str() {
+		Self::from_name(s).unwrap_or(Self::Pattern)
+	}
+
+	pub fn from_name(s: &str) -> Option<Self> {
+		Some(match s.to_lowercase().as_str() {
 			"string" => Self::String,
+			"pattern" => Self::Pattern,
 			"i8" => Self::I8,
 			"i16" => Self::I16,
 			"i32" => Self::I32,
@@ -107,8 +138,8 @@ impl ScanMode {
 			"u64" => Self::U64,
 			"f32" | "float" => Self::F32,
 			"f64" | "double" => Self::F64,
-			_ => Self::Pattern,
-		}
+			_ => return None,
+		})
 	}
 
 	pub fn name(self) -> &'static str {
@@ -128,20 +159,145 @@ impl ScanMode {
 		}
 	}
 
+	/// Size of a value in bytes, `None` for strings and patterns.
+	pub fn size(self) -> Option<usize> {
+		match self {
+			Self::String | Self::Pattern => None,
+			Self::I8 | Self::U8 => Some(1),
+			Self::I16 | Self::U16 => Some(2),
+			Self::I32 | Self::U32 | Self::F32 => Some(4),
+			Self::I64 | Self::U64 | Self::F64 => Some(8),
+		}
+	}
+
+	/// The bytes `input` stands for, little endian for numbers.
+	pub fn to_bytes(self, input: &str) -> Result<Vec<u8>, String> {
+		match self {
+			Self::String => Ok(input.as_bytes().to_vec()),
+			Self::Pattern => pattern_to_bytes(input),
+			Self::I8 => signed_int_to_bytes(input, 1),
+			Self::I16 => signed_int_to_bytes(input, 2),
+			Self::I32 => signed_int_to_bytes(input, 4),
+			Self::I64 => signed_int_to_bytes(input, 8),
+			Self::U8 => unsigned_int_to_bytes(input, 1),
+			Self::U16 => unsigned_int_to_bytes(input, 2),
+			Self::U32 => unsigned_int_to_bytes(input, 4),
+			Self::U64 => unsigned_int_to_bytes(input, 8),
+			Self::F32 => float32_to_bytes(input),
+			Self::F64 => float64_to_bytes(input),
+		}
+	}
+
 	pub fn to_pattern(self, input: &str) -> Result<String, String> {
 		match self {
-			Self::String => Ok(string_to_pattern(input)),
 			Self::Pattern => Ok(input.to_string()),
-			Self::I8 => signed_int_to_pattern(input, 1),
-			Self::I16 => signed_int_to_pattern(input, 2),
-			Self::I32 => signed_int_to_pattern(input, 4),
-			Self::I64 => signed_int_to_pattern(input, 8),
-			Self::U8 => unsigned_int_to_pattern(input, 1),
-			Self::U16 => unsigned_int_to_pattern(input, 2),
-			Self::U32 => unsigned_int_to_pattern(input, 4),
-			Self::U64 => unsigned_int_to_pattern(input, 8),
-			Self::F32 => float32_to_pattern(input),
-			Self::F64 => float64_to_pattern(input),
+			_ => self.to_bytes(input).map(|b| bytes_to_hex_pattern(&b)),
 		}
 	}
+
+	/// Decodes the value at the start of `bytes`: decimal for integers, as is for floats.
+	/// Strings stop at the first NUL or non-printable byte. `None` for patterns and too short
+	/// input.
+	pub fn decode_value(self, bytes: &[u8]) -> Option<String> {
+		match self {
+			Self::U8 => le::<1>(bytes).map(|b| u8::from_le_bytes(b).to_string()),
+			Self::U16 => le::<2>(bytes).map(|b| u16::from_le_bytes(b).to_string()),
+			Self::U32 => le::<4>(bytes).map(|b| u32::from_le_bytes(b).to_string()),
+			Self::U64 => le::<8>(bytes).map(|b| u64::from_le_bytes(b).to_string()),
+			Self::I8 => le::<1>(bytes).map(|b| i8::from_le_bytes(b).to_string()),
+			Self::I16 => le::<2>(bytes).map(|b| i16::from_le_bytes(b).to_string()),
+			Self::I32 => le::<4>(bytes).map(|b| i32::from_le_bytes(b).to_string()),
+			Self::I64 => le::<8>(bytes).map(|b| i64::from_le_bytes(b).to_string()),
+			Self::F32 => le::<4>(bytes).map(|b| f32::from_le_bytes(b).to_string()),
+			Self::F64 => le::<8>(bytes).map(|b| f64::from_le_bytes(b).to_string()),
+			Self::String => {
+				let len = bytes
+					.iter()
+					.position(|b| *b == 0 || !b.is_ascii() || b.is_ascii_control())
+					.unwrap_or(bytes.len());
+				Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+			}
+			Self::Pattern => None,
+		}
+	}
+
+	/// Orders the values at the start of `a` and `b`. `None` for strings, patterns, too short
+	/// input and NaN.
+	pub fn compare(self, a: &[u8], b: &[u8]) -> Option<Ordering> {
+		match self {
+			Self::U8 => Some(le::<1>(a)?[0].cmp(&le::<1>(b)?[0])),
+			Self::U16 => Some(u16::from_le_bytes(le(a)?).cmp(&u16::from_le_bytes(le(b)?))),
+			Self::U32 => Some(u32::from_le_bytes(le(a)?).cmp(&u32::from_le_bytes(le(b)?))),
+			Self::U64 => Some(u64::from_le_bytes(le(a)?).cmp(&u64::from_le_bytes(le(b)?))),
+			Self::I8 => Some(i8::from_le_bytes(le(a)?).cmp(&i8::from_le_bytes(le(b)?))),
+			Self::I16 => Some(i16::from_le_bytes(le(a)?).cmp(&i16::from_le_bytes(le(b)?))),
+			Self::I32 => Some(i32::from_le_bytes(le(a)?).cmp(&i32::from_le_bytes(le(b)?))),
+			Self::I64 => Some(i64::from_le_bytes(le(a)?).cmp(&i64::from_le_bytes(le(b)?))),
+			Self::F32 => f32::from_le_bytes(le(a)?).partial_cmp(&f32::from_le_bytes(le(b)?)),
+			Self::F64 => f64::from_le_bytes(le(a)?).partial_cmp(&f64::from_le_bytes(le(b)?)),
+			Self::String | Self::Pattern => None,
+		}
+	}
+
+	/// Like `decode_value`, with unsigned integers also shown in hex and an empty string
+	/// instead of `None`.
+	pub fn format_value(self, bytes: &[u8]) -> String {
+		let Some(value) = self.decode_value(bytes) else {
+			return String::new();
+		};
+
+		match self {
+			Self::U8 | Self::U16 | Self::U32 | Self::U64 => {
+				let v: u64 = value.parse().unwrap_or_default();
+				format!("{} ({:#x})", v, v)
+			}
+			_ => value,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn from_name_rejects_unknown_types() {
+		assert!(matches!(ScanMode::from_name("U32"), Some(ScanMode::U32)));
+		assert!(matches!(ScanMode::from_name("double"), Some(ScanMode::F64)));
+		assert!(ScanMode::from_name("u128").is_none());
+		assert!(matches!(ScanMode::from_str("u128"), ScanMode::Pattern));
+	}
+
+	#[test]
+	fn to_bytes_is_little_endian() {
+		assert_eq!(ScanMode::U32.to_bytes("0x64").unwrap(), [0x64, 0, 0, 0]);
+		assert_eq!(ScanMode::I16.to_bytes("-2").unwrap(), [0xfe, 0xff]);
+		assert_eq!(ScanMode::String.to_bytes("hi").unwrap(), b"hi");
+		assert_eq!(ScanMode::Pattern.to_bytes("0xDE ad").unwrap(), [0xde, 0xad]);
+		assert!(ScanMode::Pattern.to_bytes("de ??").is_err());
+		assert!(ScanMode::U8.to_bytes("x").is_err());
+	}
+
+	#[test]
+	fn compare_uses_the_type() {
+		let (a, b) = (ScanMode::I32.to_bytes("-1").unwrap(), ScanMode::I32.to_bytes("1").unwrap());
+		assert_eq!(ScanMode::I32.compare(&a, &b), Some(Ordering::Less));
+		assert_eq!(ScanMode::U32.compare(&a, &b), Some(Ordering::Greater));
+		assert_eq!(ScanMode::String.compare(b"a", b"b"), None);
+	}
+
+	#[test]
+	fn format_value_roundtrips() {
+		for (mode, input, expected) in [
+			(ScanMode::U16, "513", "513 (0x201)"),
+			(ScanMode::I32, "-7", "-7"),
+			(ScanMode::F64, "1.5", "1.5"),
+		] {
+			let bytes = mode.to_bytes(input).unwrap();
+			assert_eq!(mode.format_value(&bytes), expected);
+		}
+
+		assert_eq!(ScanMode::String.format_value(b"name\0rest"), "name");
+		assert_eq!(ScanMode::U64.format_value(&[1, 2]), "");
+	}
 }
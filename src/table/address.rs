@@ -0,0 +1,294 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+	errors::{Error, ErrorKind},
+	maps::ProcMemoryMaps,
+	traits::ReadProcessMemory,
+};
+
+/// Where a table entry lives: a fixed address, a module's load base, or a pointer path built
+/// from them.
+///
+/// Written like Cheat Engine's address column, with `0x` for hex: `libgame.so+0x1234`,
+/// `[[libgame.so+0x1234]+0x10]-8` or `0x7ffd1234`. Module names containing `+`, `-`, brackets
+/// or spaces that would be ambiguous can be quoted: `"libstdc++.so.6"+0x10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressExpr {
+	Address(u64),
+	/// The probable load base of the first module whose path contains the name.
+	Module(String),
+	/// The pointer stored at the inner address.
+	Deref(Box<AddressExpr>),
+	Offset(Box<AddressExpr>, i64),
+}
+
+impl AddressExpr {
+	/// Wraps `base` in a pointer dereference for every offset, like Cheat Engine's pointer
+	/// lists: `[[base]+offsets[0]]+offsets[1]`.
+	pub fn pointer_path(base: AddressExpr, offsets: &[i64]) -> Self {
+		offsets.iter().fold(base, |expr, offset| {
+			AddressExpr::Deref(Box::new(expr)).offset(*offset)
+		})
+	}
+
+	pub fn offset(self, offset: i64) -> Self {
+		if offset == 0 {
+			self
+		} else {
+			AddressExpr::Offset(Box::new(self), offset)
+		}
+	}
+
+	/// Whether resolving reads pointers from the process.
+	pub fn is_pointer(&self) -> bool {
+		match self {
+			AddressExpr::Address(_) | AddressExpr::Module(_) => false,
+			AddressExpr::Deref(_) => true,
+			AddressExpr::Offset(inner, _) => inner.is_pointer(),
+		}
+	}
+
+	/// Computes the address, following pointers with 8 byte reads from `process`.
+	pub fn resolve<P: ReadProcessMemory + ?Sized>(
+		&self,
+		process: &mut P,
+		maps: &ProcMemoryMaps,
+	) -> Result<u64, Error> {
+		match self {
+			AddressExpr::Address(addr) => Ok(*addr),
+			AddressExpr::Module(name) => {
+				maps.get_module_probable_load_base(name).ok_or_else(|| {
+					Error::new(
+						ErrorKind::InvalidAddress,
+						format!("module {} is not loaded", name),
+					)
+				})
+			}
+			AddressExpr::Deref(inner) => {
+				let addr = inner.resolve(process, maps)?;
+				process.read_value::<u64>(addr)
+			}
+			AddressExpr::Offset(inner, offset) => {
+				Ok(inner.resolve(process, maps)?.wrapping_add_signed(*offset))
+			}
+		}
+	}
+}
+
+/// Whether the name would not parse back as itself without quotes.
+fn needs_quotes(name: &str) -> bool {
+	!matches!(name.parse(), Ok(AddressExpr::Module(parsed)) if parsed == name)
+}
+
+impl fmt::Display for AddressExpr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			AddressExpr::Address(addr) => write!(f, "{:#x}", addr),
+			AddressExpr::Module(name) if needs_quotes(name) => write!(f, "\"{}\"", name),
+			AddressExpr::Module(name) => write!(f, "{}", name),
+			AddressExpr::Deref(inner) => write!(f, "[{}]", inner),
+			AddressExpr::Offset(inner, offset) if *offset < 0 => {
+				write!(f, "{}-{:#x}", inner, offset.unsigned_abs())
+			}
+			AddressExpr::Offset(inner, offset) => write!(f, "{}+{:#x}", inner, offset),
+		}
+	}
+}
+
+impl FromStr for AddressExpr {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parser = Parser { s, pos: 0 };
+		let expr = parser.expr()?;
+		parser.skip_whitespace();
+		if parser.pos != s.len() {
+			return Err(parser.error("unexpected character"));
+		}
+		Ok(expr)
+	}
+}
+
+struct Parser<'a> {
+	s: &'a str,
+	pos: usize,
+}
+
+/// A `0x` hex or decimal number at the start of `s`, and its length, if it is followed by the
+/// end, an operator, a closing bracket or whitespace.
+fn number_at(s: &str) -> Option<(u64, usize)> {
+	let (digits, radix, prefix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+		Some(hex) => (hex, 16, 2),
+		None => (s, 10, 0),
+	};
+	let len = digits
+		.find(|c: char| !c.is_digit(radix))
+		.unwrap_or(digits.len());
+	let rest = &digits[len..];
+	if len == 0
+		|| !(rest.is_empty() || rest.starts_with(|c: char| "+-]".contains(c) || c.is_whitespace()))
+	{
+		return None;
+	}
+	let value = u64::from_str_radix(&digits[..len], radix).ok()?;
+	Some((value, prefix + len))
+}
+
+impl<'a> Parser<'a> {
+	fn rest(&self) -> &'a str {
+		&self.s[self.pos..]
+	}
+
+	fn skip_whitespace(&mut self) {
+		self.pos = self.s.len() - self.rest().trim_start().len();
+	}
+
+	fn error(&self, message: &str) -> Error {
+		Error::new(
+			ErrorKind::Parse,
+			format!("{} at {} in address {:?}", message, self.pos, self.s),
+		)
+	}
+
+	fn expr(&mut self) -> Result<AddressExpr, Error> {
+		let mut expr = self.term()?;
+		loop {
+			self.skip_whitespace();
+			let sign = match self.rest().chars().next() {
+				Some('+') => 1,
+				Some('-') => -1,
+				_ => return Ok(expr),
+			};
+			self.pos += 1;
+			self.skip_whitespace();
+
+			let (offset, len) =
+				number_at(self.rest()).ok_or_else(|| self.error("expected an offset"))?;
+			self.pos += len;
+			let offset = i64::try_from(offset).map_err(|_| self.error("offset too large"))?;
+			expr = AddressExpr::Offset(Box::new(expr), sign * offset);
+		}
+	}
+
+	fn term(&mut self) -> Result<AddressExpr, Error> {
+		self.skip_whitespace();
+		let rest = self.rest();
+
+		if rest.starts_with('[') {
+			self.pos += 1;
+			let inner = self.expr()?;
+			self.skip_whitespace();
+			if !self.rest().starts_with(']') {
+				return Err(self.error("expected ]"));
+			}
+			self.pos += 1;
+			return Ok(AddressExpr::Deref(Box::new(inner)));
+		}
+
+		if let Some(quoted) = rest.strip_prefix('"') {
+			let len = quoted
+				.find('"')
+				.ok_or_else(|| self.error("unterminated quote"))?;
+			self.pos += len + 2;
+			return Ok(AddressExpr::Module(quoted[..len].to_string()));
+		}
+
+		if let Some((addr, len)) = number_at(rest) {
+			self.pos += len;
+			return Ok(AddressExpr::Address(addr));
+		}
+
+		// A module name runs until an operator that is followed by a number, so names like
+		// ld-linux-x86-64.so.2 and libstdc++.so.6 need no quotes.
+		let mut len = rest.len();
+		for (i, c) in rest.char_indices() {
+			if c == ']' || c.is_whitespace() {
+				len = i;
+				break;
+			}
+			if (c == '+' || c == '-') && number_at(rest[i + 1..].trim_start()).is_some() {
+				len = i;
+				break;
+			}
+		}
+		if len == 0 {
+			return Err(self.error("expected an address, module or ["));
+		}
+		if rest[..len].ends_with(['+', '-']) {
+			return Err(self.error("expected an offset"));
+		}
+		self.pos += len;
+		Ok(AddressExpr::Module(rest[..len].to_string()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	fn parse(s: &str) -> AddressExpr {
+		s.parse().unwrap()
+	}
+
+	#[test]
+	fn parses_pointer_paths() {
+		let expr = parse("[[libgame.so + 0x10] + 0x8] - 4");
+		assert_eq!(
+			expr,
+			AddressExpr::pointer_path(
+				AddressExpr::Module("libgame.so".to_string()).offset(0x10),
+				&[8, -4]
+			)
+		);
+		assert_eq!(expr.to_string(), "[[libgame.so+0x10]+0x8]-0x4");
+		assert!(expr.is_pointer());
+
+		assert_eq!(parse("0x7ffd1234"), AddressExpr::Address(0x7ffd1234));
+		assert_eq!(parse("4096"), AddressExpr::Address(4096));
+		assert!(!parse("libc.so.6+0x10").is_pointer());
+	}
+
+	#[test]
+	fn module_names_keep_their_dashes_and_pluses() {
+		assert_eq!(
+			parse("ld-linux-x86-64.so.2+0x20"),
+			AddressExpr::Module("ld-linux-x86-64.so.2".to_string()).offset(0x20)
+		);
+		assert_eq!(
+			parse("libstdc++.so.6+8"),
+			AddressExpr::Module("libstdc++.so.6".to_string()).offset(8)
+		);
+
+		let quoted = parse(r#""game 2"+0x1"#);
+		assert_eq!(quoted.to_string(), r#""game 2"+0x1"#);
+		assert_eq!(parse(&quoted.to_string()), quoted);
+	}
+
+	#[test]
+	fn rejects_malformed_expressions() {
+		for bad in ["", "[libc.so.6+0x10", "libc.so.6+", "0x10 0x20", r#""game"#] {
+			assert!(bad.parse::<AddressExpr>().is_err(), "{:?}", bad);
+		}
+	}
+
+	#[test]
+	fn resolves_against_a_process() {
+		let mut process = MockProcess::new(1)
+			.with_region(0x1000, 0x1000, "r-xp", Some("/usr/lib/libgame.so"))
+			.with_region(0x5000, 0x1000, "rw-p", None);
+		process.poke(0x1010, &0x5000u64.to_le_bytes());
+		process.poke(0x5008, &0x5100u64.to_le_bytes());
+		let maps = process.get_maps().clone();
+
+		let expr = parse("[[libgame.so+0x10]+0x8]+0x20");
+		assert_eq!(expr.resolve(&mut process, &maps).unwrap(), 0x5120);
+
+		let err = parse("[libother.so]")
+			.resolve(&mut process, &maps)
+			.unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidAddress);
+		let err = parse("[0x9000]").resolve(&mut process, &maps).unwrap_err();
+		assert_eq!(err.addr(), Some(0x9000));
+	}
+}
@@ -0,0 +1,309 @@
+use roxmltree::{Document, Node};
+
+use super::{
+	address::AddressExpr,
+	cheat_table::{TableEntry, TableImport, TableScript},
+};
+use crate::{
+	errors::{Error, ErrorKind},
+	scan_mode::ScanMode,
+};
+
+/// Reads the address list of a Cheat Engine `.CT` file.
+///
+/// Groups are flattened into `Group/Entry` descriptions, auto assembler scripts are kept as
+/// text, and activated entries are imported frozen at their last value. Entries of types lime
+/// has no equivalent for (binary, custom types) and symbolic addresses are skipped.
+pub fn import_cheat_engine(xml: &str) -> Result<TableImport, Error> {
+	let document = Document::parse(xml).map_err(|e| {
+		Error::new(
+			ErrorKind::Parse,
+			format!("invalid Cheat Engine table: {}", e),
+		)
+	})?;
+	let root = document.root_element();
+	if !root.has_tag_name("CheatTable") {
+		return Err(Error::new(
+			ErrorKind::Parse,
+			format!("expected a CheatTable, found {}", root.tag_name().name()),
+		));
+	}
+
+	let mut import = TableImport::default();
+	if let Some(entries) = child(root, "CheatEntries") {
+		import.entries(entries, "");
+	}
+	Ok(import)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+	node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+	child(node, name).and_then(|n| n.text()).map(str::trim)
+}
+
+impl TableImport {
+	fn entries(&mut self, entries: Node, group: &str) {
+		for node in entries.children().filter(|n| n.has_tag_name("CheatEntry")) {
+			let name = child_text(node, "Description")
+				.unwrap_or_default()
+				.trim_matches('"');
+			let description = if group.is_empty() {
+				name.to_string()
+			} else {
+				format!("{}/{}", group, name)
+			};
+
+			if child_text(node, "VariableType").is_some()
+				&& let Err(reason) = self.entry(node, &description)
+			{
+				self.skipped.push(format!("{}: {}", description, reason));
+			}
+			if let Some(children) = child(node, "CheatEntries") {
+				self.entries(children, &description);
+			}
+		}
+	}
+
+	fn entry(&mut self, node: Node, description: &str) -> Result<(), String> {
+		let variable_type = child_text(node, "VariableType").unwrap_or_default();
+		if variable_type == "Auto Assembler Script" {
+			self.table.scripts.push(TableScript {
+				description: description.to_string(),
+				script: child_text(node, "AssemblerScript")
+					.unwrap_or_default()
+					.to_string(),
+			});
+			return Ok(());
+		}
+
+		let signed = child_text(node, "ShowAsSigned") == Some("1");
+		let (value_type, length) = match variable_type {
+			"Byte" if signed => (ScanMode::I8, None),
+			"Byte" => (ScanMode::U8, None),
+			"2 Bytes" if signed => (ScanMode::I16, None),
+			"2 Bytes" => (ScanMode::U16, None),
+			"4 Bytes" if signed => (ScanMode::I32, None),
+			"4 Bytes" => (ScanMode::U32, None),
+			"8 Bytes" if signed => (ScanMode::I64, None),
+			"8 Bytes" => (ScanMode::U64, None),
+			"Float" => (ScanMode::F32, None),
+			"Double" => (ScanMode::F64, None),
+			"String" => (ScanMode::String, length(node, "Length")),
+			"Array of byte" => (
+				ScanMode::Pattern,
+				length(node, "ByteLength").or(length(node, "Length")),
+			),
+			other => return Err(format!("unsupported type {}", other)),
+		};
+
+		let address = child_text(node, "Address").ok_or("no address")?;
+		let base =
+			parse_address(address).ok_or_else(|| format!("unsupported address {}", address))?;
+		// Cheat Engine lists the last offset applied first.
+		let mut offsets = Vec::new();
+		if let Some(list) = child(node, "Offsets") {
+			for offset in list.children().filter(|n| n.has_tag_name("Offset")) {
+				let text = offset.text().unwrap_or_default().trim();
+				offsets
+					.push(parse_hex(text).ok_or_else(|| format!("unsupported offset {}", text))?);
+			}
+		}
+		offsets.reverse();
+
+		let last_state = child(node, "LastState");
+		let frozen = last_state.and_then(|n| n.attribute("Activated")) == Some("1");
+		let value = last_state
+			.and_then(|n| n.attribute("Value"))
+			.filter(|_| frozen)
+			.map(str::to_string);
+
+		self.table.entries.push(TableEntry {
+			description: description.to_string(),
+			address: AddressExpr::pointer_path(base, &offsets),
+			value_type,
+			length,
+			frozen,
+			value,
+		});
+		Ok(())
+	}
+}
+
+fn length(node: Node, name: &str) -> Option<usize> {
+	child_text(node, name)?.parse().ok()
+}
+
+/// A Cheat Engine number: hex, with or without a `0x` prefix, optionally negative.
+fn parse_hex(s: &str) -> Option<i64> {
+	let (negative, s) = match s.strip_prefix('-') {
+		Some(s) => (true, s),
+		None => (false, s),
+	};
+	let s = s
+		.strip_prefix("0x")
+		.or_else(|| s.strip_prefix("0X"))
+		.unwrap_or(s);
+	let value = i64::from_str_radix(s, 16).ok()?;
+	Some(if negative { -value } else { value })
+}
+
+/// Converts a Cheat Engine address column, `"game.exe"+0012F0A8`, `game.exe+10-4` or
+/// `0012F0A8`, where numbers are hex. Brackets and symbols other than module names are not
+/// supported.
+fn parse_address(s: &str) -> Option<AddressExpr> {
+	let s = s.trim();
+	let (base, mut rest) = if let Some(quoted) = s.strip_prefix('"') {
+		let len = quoted.find('"')?;
+		(
+			AddressExpr::Module(quoted[..len].to_string()),
+			&quoted[len + 1..],
+		)
+	} else {
+		// The base ends at the first operator followed only by offsets.
+		let len = s
+			.char_indices()
+			.find(|&(i, c)| (c == '+' || c == '-') && offsets_only(&s[i..]))
+			.map_or(s.len(), |(i, _)| i);
+		let name = s[..len].trim();
+		if name.is_empty() || name.contains(['[', ']']) {
+			return None;
+		}
+		let base = match parse_hex(name) {
+			Some(addr) if !name.starts_with('-') => AddressExpr::Address(addr as u64),
+			Some(_) => return None,
+			None if name.contains('.') => AddressExpr::Module(name.to_string()),
+			None => return None,
+		};
+		(base, &s[len..])
+	};
+
+	let mut expr = base;
+	while let Some(sign) = rest.chars().next() {
+		let term = &rest[1..];
+		let len = term.find(['+', '-']).unwrap_or(term.len());
+		let offset = parse_hex(term[..len].trim())?;
+		expr = match sign {
+			'+' => expr.offset(offset),
+			'-' => expr.offset(-offset),
+			_ => return None,
+		};
+		rest = &term[len..];
+	}
+	Some(expr)
+}
+
+/// Whether `s` is a run of `+hex` and `-hex` terms.
+fn offsets_only(s: &str) -> bool {
+	let mut rest = s;
+	while let Some(term) = rest.strip_prefix(['+', '-']) {
+		let len = term.find(['+', '-']).unwrap_or(term.len());
+		if parse_hex(term[..len].trim()).is_none() {
+			return false;
+		}
+		rest = &term[len..];
+	}
+	rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TABLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<CheatTable CheatEngineTableVersion="45">
+  <CheatEntries>
+    <CheatEntry>
+      <ID>1</ID>
+      <Description>"Player"</Description>
+      <GroupHeader>1</GroupHeader>
+      <CheatEntries>
+        <CheatEntry>
+          <ID>2</ID>
+          <Description>"Health"</Description>
+          <LastState Value="100" Activated="1" RealAddress="0x5008"/>
+          <ShowAsSigned>1</ShowAsSigned>
+          <VariableType>4 Bytes</VariableType>
+          <Address>"libgame.so"+0012F0A8</Address>
+          <Offsets>
+            <Offset>8</Offset>
+            <Offset>1C</Offset>
+          </Offsets>
+        </CheatEntry>
+        <CheatEntry>
+          <ID>3</ID>
+          <Description>"Name"</Description>
+          <VariableType>String</VariableType>
+          <Length>16</Length>
+          <Unicode>0</Unicode>
+          <Address>7FFD1000</Address>
+        </CheatEntry>
+      </CheatEntries>
+    </CheatEntry>
+    <CheatEntry>
+      <ID>4</ID>
+      <Description>"Infinite ammo"</Description>
+      <VariableType>Auto Assembler Script</VariableType>
+      <AssemblerScript>[ENABLE]
+nop
+[DISABLE]
+</AssemblerScript>
+    </CheatEntry>
+    <CheatEntry>
+      <ID>5</ID>
+      <Description>"Flags"</Description>
+      <VariableType>Binary</VariableType>
+      <Address>ld-linux-x86-64.so.2+10</Address>
+    </CheatEntry>
+  </CheatEntries>
+</CheatTable>
+"#;
+
+	#[test]
+	fn imports_entries_groups_and_scripts() {
+		let import = import_cheat_engine(TABLE).unwrap();
+		let entries = &import.table.entries;
+		assert_eq!(entries.len(), 2);
+
+		assert_eq!(entries[0].description, "Player/Health");
+		assert_eq!(
+			entries[0].address.to_string(),
+			"[[libgame.so+0x12f0a8]+0x1c]+0x8"
+		);
+		assert!(matches!(entries[0].value_type, ScanMode::I32));
+		assert!(entries[0].frozen);
+		assert_eq!(entries[0].value.as_deref(), Some("100"));
+
+		assert_eq!(entries[1].address, AddressExpr::Address(0x7ffd1000));
+		assert_eq!(entries[1].size(), 16);
+		assert!(!entries[1].frozen);
+
+		assert_eq!(import.table.scripts[0].description, "Infinite ammo");
+		assert!(import.table.scripts[0].script.contains("nop"));
+		assert_eq!(import.skipped, ["Flags: unsupported type Binary"]);
+	}
+
+	#[test]
+	fn converts_addresses() {
+		let parse = |s| parse_address(s).map(|e| e.to_string());
+		assert_eq!(
+			parse("ld-linux-x86-64.so.2+10-4").as_deref(),
+			Some("ld-linux-x86-64.so.2+0x10-0x4")
+		);
+		assert_eq!(
+			parse(r#""game 2.exe"+0x20"#).as_deref(),
+			Some("\"game 2.exe\"+0x20")
+		);
+		assert_eq!(parse("00400000").as_deref(), Some("0x400000"));
+		assert_eq!(parse("[game.exe+10]+4"), None);
+		assert_eq!(parse("playerbase+4"), None);
+	}
+
+	#[test]
+	fn rejects_other_documents() {
+		assert!(import_cheat_engine("<Other/>").is_err());
+		assert!(import_cheat_engine("not xml").is_err());
+	}
+}
@@ -0,0 +1,283 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{address::AddressExpr, cheat_engine::import_cheat_engine};
+use crate::{
+	errors::{Error, ErrorKind},
+	maps::ProcMemoryMaps,
+	scan_mode::ScanMode,
+	traits::ReadProcessMemory,
+};
+
+/// Bytes read for string entries without a length.
+pub const DEFAULT_STRING_LEN: usize = 64;
+
+/// A list of findings: addresses with a description and type, and scripts that go with them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheatTable {
+	#[serde(default, rename = "entry", skip_serializing_if = "Vec::is_empty")]
+	pub entries: Vec<TableEntry>,
+	#[serde(default, rename = "script", skip_serializing_if = "Vec::is_empty")]
+	pub scripts: Vec<TableScript>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableEntry {
+	pub description: String,
+	#[serde(with = "address_text")]
+	pub address: AddressExpr,
+	#[serde(rename = "type", with = "mode_name")]
+	pub value_type: ScanMode,
+	/// Bytes to read for strings and patterns, ignored for numbers.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub length: Option<usize>,
+	/// Whether the value should be kept at `value`, or at what it is when loaded.
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub frozen: bool,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub value: Option<String>,
+}
+
+/// A script stored with the table, e.g. a Cheat Engine auto assembler script. Kept as text,
+/// lime never runs them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableScript {
+	pub description: String,
+	pub script: String,
+}
+
+/// A table read from a file, with the entries that had no equivalent when it was imported.
+#[derive(Debug, Clone, Default)]
+pub struct TableImport {
+	pub table: CheatTable,
+	/// Descriptions of the entries left out, with the reason.
+	pub skipped: Vec<String>,
+}
+
+/// On disk formats, picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+	Toml,
+	Json,
+	/// Cheat Engine's `.CT` XML, which can only be imported.
+	CheatEngine,
+}
+
+impl TableFormat {
+	pub fn from_path(path: &Path) -> Option<Self> {
+		let extension = path.extension()?.to_str()?.to_lowercase();
+		match extension.as_str() {
+			"toml" => Some(Self::Toml),
+			"json" => Some(Self::Json),
+			"ct" => Some(Self::CheatEngine),
+			_ => None,
+		}
+	}
+}
+
+impl TableEntry {
+	/// Bytes that make up the value.
+	pub fn size(&self) -> usize {
+		self.value_type
+			.size()
+			.unwrap_or(self.length.unwrap_or(DEFAULT_STRING_LEN))
+	}
+}
+
+fn parse_error(format: TableFormat, e: impl std::fmt::Display) -> Error {
+	Error::new(
+		ErrorKind::Parse,
+		format!("invalid {:?} table: {}", format, e),
+	)
+}
+
+impl CheatTable {
+	pub fn parse(text: &str, format: TableFormat) -> Result<Self, Error> {
+		match format {
+			TableFormat::Toml => toml::from_str(text).map_err(|e| parse_error(format, e)),
+			TableFormat::Json => serde_json::from_str(text).map_err(|e| parse_error(format, e)),
+			TableFormat::CheatEngine => import_cheat_engine(text).map(|import| import.table),
+		}
+	}
+
+	pub fn to_text(&self, format: TableFormat) -> Result<String, Error> {
+		match format {
+			TableFormat::Toml => {
+				toml::to_string_pretty(self).map_err(|e| Error::new(ErrorKind::Io, e.to_string()))
+			}
+			TableFormat::Json => serde_json::to_string_pretty(self)
+				.map_err(|e| Error::new(ErrorKind::Io, e.to_string())),
+			TableFormat::CheatEngine => Err(Error::new(
+				ErrorKind::Unsupported,
+				"Cheat Engine tables can only be imported, save as .toml or .json",
+			)),
+		}
+	}
+
+	fn format_of(path: &Path) -> Result<TableFormat, Error> {
+		TableFormat::from_path(path).ok_or_else(|| {
+			Error::new(
+				ErrorKind::Unsupported,
+				format!("{}: expected a .toml, .json or .ct file", path.display()),
+			)
+		})
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+		Self::import(path).map(|import| import.table)
+	}
+
+	/// Loads a table of any format, keeping track of the Cheat Engine entries left out.
+	pub fn import(path: impl AsRef<Path>) -> Result<TableImport, Error> {
+		let path = path.as_ref();
+		let text = std::fs::read_to_string(path)?;
+		match Self::format_of(path)? {
+			TableFormat::CheatEngine => import_cheat_engine(&text),
+			format => Ok(TableImport {
+				table: Self::parse(&text, format)?,
+				skipped: Vec::new(),
+			}),
+		}
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+		let path = path.as_ref();
+		let text = self.to_text(Self::format_of(path)?)?;
+		std::fs::write(path, text)?;
+		Ok(())
+	}
+
+	/// The address of every entry, in order. Entries whose module is not loaded or whose
+	/// pointers cannot be read get their error.
+	pub fn resolve_all<P: ReadProcessMemory + ?Sized>(
+		&self,
+		process: &mut P,
+		maps: &ProcMemoryMaps,
+	) -> Vec<Result<u64, Error>> {
+		self.entries
+			.iter()
+			.map(|entry| entry.address.resolve(process, maps))
+			.collect()
+	}
+}
+
+/// Addresses as the text `AddressExpr` parses.
+mod address_text {
+	use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+	use super::AddressExpr;
+
+	pub fn serialize<S: Serializer>(expr: &AddressExpr, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(expr)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<AddressExpr, D::Error> {
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(D::Error::custom)
+	}
+}
+
+/// Value types by `ScanMode::name`.
+mod mode_name {
+	use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+	use super::ScanMode;
+
+	pub fn serialize<S: Serializer>(mode: &ScanMode, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(mode.name())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ScanMode, D::Error> {
+		let name = String::deserialize(deserializer)?;
+		ScanMode::from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown type {}", name)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::process::MockProcess;
+
+	const TOML: &str = r#"
+[[entry]]
+description = "Health"
+address = "[libgame.so+0x10]+0x8"
+type = "i32"
+frozen = true
+value = "100"
+
+[[entry]]
+description = "Name"
+address = "0x5100"
+type = "string"
+length = 16
+
+[[script]]
+description = "Infinite ammo"
+script = "[ENABLE]\n[DISABLE]\n"
+"#;
+
+	#[test]
+	fn toml_and_json_round_trip() {
+		let table = CheatTable::parse(TOML, TableFormat::Toml).unwrap();
+		assert_eq!(table.entries.len(), 2);
+		assert_eq!(table.scripts.len(), 1);
+		assert!(matches!(table.entries[0].value_type, ScanMode::I32));
+		assert!(table.entries[0].frozen);
+		assert_eq!(table.entries[1].size(), 16);
+
+		for format in [TableFormat::Toml, TableFormat::Json] {
+			let text = table.to_text(format).unwrap();
+			assert_eq!(CheatTable::parse(&text, format).unwrap(), table, "{}", text);
+		}
+		assert!(table.to_text(TableFormat::CheatEngine).is_err());
+	}
+
+	#[test]
+	fn rejects_bad_addresses_and_types() {
+		let bad_type = TOML.replace("\"i32\"", "\"i33\"");
+		let err = CheatTable::parse(&bad_type, TableFormat::Toml).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::Parse);
+		assert!(err.to_string().contains("unknown type i33"));
+
+		let bad_address = TOML.replace("0x5100", "[0x5100");
+		assert!(CheatTable::parse(&bad_address, TableFormat::Toml).is_err());
+	}
+
+	#[test]
+	fn format_follows_the_extension() {
+		assert_eq!(
+			TableFormat::from_path(Path::new("a/game.TOML")),
+			Some(TableFormat::Toml)
+		);
+		assert_eq!(
+			TableFormat::from_path(Path::new("game.CT")),
+			Some(TableFormat::CheatEngine)
+		);
+		assert_eq!(TableFormat::from_path(Path::new("game.txt")), None);
+	}
+
+	#[test]
+	fn resolves_every_entry() {
+		let mut process = MockProcess::new(1)
+			.with_region(0x1000, 0x1000, "r-xp", Some("/opt/game/libgame.so"))
+			.with_region(0x5000, 0x1000, "rw-p", None);
+		process.poke(0x1010, &0x5000u64.to_le_bytes());
+		let maps = process.get_maps().clone();
+
+		let mut table = CheatTable::parse(TOML, TableFormat::Toml).unwrap();
+		table.entries.push(TableEntry {
+			address: "libmissing.so+0x10".parse().unwrap(),
+			..table.entries[1].clone()
+		});
+
+		let addrs = table.resolve_all(&mut process, &maps);
+		assert_eq!(addrs[0].as_ref().unwrap(), &0x5008);
+		assert_eq!(addrs[1].as_ref().unwrap(), &0x5100);
+		assert!(addrs[2].is_err());
+	}
+}
@@ -1,16 +1,14 @@
-use std::fmt::{Debug, Display};
-
-use crate::errors::{MemAddrError, RPMError, WPMError};
+use crate::{errors::Error, maps::SmapsInfo, pod::Pod};
 
 pub trait ReadProcessMemory {
-	fn read_value<T: Copy>(&mut self, addr: u64) -> Result<T, Box<dyn InternalLimeError>>;
+	fn read_value<T: Pod>(&mut self, addr: u64) -> Result<T, Error>;
 
-	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Box<dyn InternalLimeError>> {
+	fn read_bytes(&mut self, addr: u64, buf: &mut [u8]) -> Result<usize, Error> {
 		let mut n = 0;
-		for i in 0..buf.len() {
+		for (i, byte) in buf.iter_mut().enumerate() {
 			match self.read_value::<u8>(addr + i as u64) {
 				Ok(b) => {
-					buf[i] = b;
+					*byte = b;
 					n += 1;
 				}
 				Err(_) => break,
@@ -21,11 +19,14 @@ pub trait ReadProcessMemory {
 }
 
 pub trait WriteProcessMemory {
-	fn write_value<T: Copy>(
-		&mut self,
-		addr: u64,
-		value: &T,
-	) -> Result<(), Box<dyn InternalLimeError>>;
+	fn write_value<T: Pod>(&mut self, addr: u64, value: &T) -> Result<(), Error>;
+
+	fn write_bytes(&mut self, addr: u64, buf: &[u8]) -> Result<(), Error> {
+		for (i, byte) in buf.iter().enumerate() {
+			self.write_value::<u8>(addr + i as u64, byte)?;
+		}
+		Ok(())
+	}
 }
 
 pub enum ScanTarget<'a> {
@@ -34,43 +35,39 @@ pub enum ScanTarget<'a> {
 	AnonymousNonHeapAndStack,
 	Module(&'a str),
 	Range(u64, u64),
+	ThreadStack(u32),
+	/// Regions of the inner target that pass the filter. Filters need smaps data, which is
+	/// loaded on demand.
+	Filtered(&'a ScanTarget<'a>, RegionFilter),
 }
 
-pub trait ProcessMemoryPatternScan {
-	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>>;
-	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>>;
-}
-
-pub trait InternalLimeError {
-	fn string(&self) -> String;
-}
-
-impl Debug for dyn InternalLimeError {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{}", self.string())
+impl ScanTarget<'_> {
+	pub fn needs_smaps(&self) -> bool {
+		matches!(self, ScanTarget::Filtered(..))
 	}
 }
 
-impl Display for dyn InternalLimeError {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{}", self.string())
-	}
-}
-
-impl From<WPMError> for Box<dyn InternalLimeError> {
-	fn from(value: WPMError) -> Self {
-		Box::new(value)
-	}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionFilter {
+	/// Skip regions without any resident pages (Rss of 0).
+	Resident,
+	/// Only regions with privately modified pages (Private_Dirty above 0).
+	PrivateDirty,
+	/// Skip regions that were never touched, with neither resident nor swapped out pages.
+	ResidentOrSwapped,
 }
 
-impl From<RPMError> for Box<dyn InternalLimeError> {
-	fn from(value: RPMError) -> Self {
-		Box::new(value)
+impl RegionFilter {
+	pub fn matches(self, smaps: &SmapsInfo) -> bool {
+		match self {
+			RegionFilter::Resident => smaps.rss > 0,
+			RegionFilter::PrivateDirty => smaps.private_dirty > 0,
+			RegionFilter::ResidentOrSwapped => smaps.rss > 0 || smaps.swap > 0,
+		}
 	}
 }
 
-impl From<MemAddrError> for Box<dyn InternalLimeError> {
-	fn from(value: MemAddrError) -> Self {
-		Box::new(value)
-	}
+pub trait ProcessMemoryPatternScan {
+	fn scan_for_pattern(&mut self, pattern: &str) -> Option<Vec<u64>>;
+	fn scan_for_pattern_in(&mut self, pattern: &str, target: ScanTarget) -> Option<Vec<u64>>;
 }
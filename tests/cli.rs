@@ -0,0 +1,335 @@
+//! The `lime` binary against a `lime-fixture` process.
+
+#![cfg(all(feature = "procmem", feature = "cli"))]
+
+mod common;
+
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	process::{Command, Output, Stdio},
+};
+
+use common::{ANON_VALUE, Fixture, HEAP_VALUE, READONLY_VALUE};
+
+fn lime(args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_lime"))
+		.args(args)
+		.output()
+		.expect("failed to run lime")
+}
+
+fn stdout(output: &Output) -> String {
+	String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn scan_prints_matches() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let value = format!("{:#x}", HEAP_VALUE);
+
+	let output = lime(&["scan", "-p", &pid, "-t", "u64", "-v", &value]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).contains(&format!("{:#x}  [heap]", fixture.addr("heap"))));
+}
+
+#[test]
+fn scan_without_matches_exits_with_1() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+
+	let output = lime(&["scan", "-p", &pid, "-v", &fixture.marker_pattern(b'Z')]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn scan_prints_ndjson() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let value = format!("{:#x}", ANON_VALUE);
+
+	let output = lime(&[
+		"scan", "-p", &pid, "-t", "u64", "-v", &value, "--target", "anon", "--ndjson",
+	]);
+	assert_eq!(output.status.code(), Some(0));
+
+	let lines: Vec<serde_json::Value> = stdout(&output)
+		.lines()
+		.map(|l| serde_json::from_str(l).unwrap())
+		.collect();
+	assert_eq!(lines.len(), 1);
+	assert_eq!(lines[0]["address"], format!("{:#x}", fixture.addr("anon")));
+	assert_eq!(lines[0]["value"], ANON_VALUE.to_string());
+	assert_eq!(lines[0]["bytes"], "ccccbbbbaaaa9999");
+	assert!(lines[0]["region"].is_null());
+}
+
+#[test]
+fn matches_at_a_region_start_keep_their_bytes() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let maps = std::fs::read_to_string(format!("/proc/{}/maps", fixture.pid)).unwrap();
+	let base = u64::from_str_radix(maps.split('-').next().unwrap(), 16).unwrap();
+	let target = format!("range:{:#x}-{:#x}", base, base + 0x1000);
+
+	let output = lime(&[
+		"scan", "-p", &pid, "-v", "7f 45 4c 46", "--target", &target, "--ndjson",
+	]);
+	assert_eq!(output.status.code(), Some(0));
+
+	let record: serde_json::Value =
+		serde_json::from_str(stdout(&output).lines().next().unwrap()).unwrap();
+	assert_eq!(record["address"], format!("{:#x}", base));
+	assert_eq!(record["bytes"], "7f454c46");
+	assert_eq!(record["context_start"], format!("{:#x}", base));
+	assert!(record["context"].as_str().unwrap().starts_with("7f454c46"));
+}
+
+#[test]
+fn maps_and_ps_print_json() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+
+	let output = lime(&["maps", &pid, "--json"]);
+	let regions: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+	assert!(regions.iter().any(|r| r["pathname"] == "[heap]"));
+
+	let output = lime(&["--json", "ps", "lime-fixture"]);
+	let processes: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+	assert!(processes.iter().any(|p| p["pid"] == fixture.pid));
+}
+
+#[test]
+fn read_decodes_values() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let addr = format!("{:#x}", fixture.addr("readonly"));
+
+	let output = lime(&["read", "-p", &pid, &addr, "-t", "u64"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert!(stdout(&output).contains(&format!("({:#x})", READONLY_VALUE)));
+
+	let output = lime(&["read", "-p", &pid, &addr, "-l", "8"]);
+	assert!(stdout(&output).contains("00 00 ff ff ee ee dd dd"));
+}
+
+#[test]
+fn write_changes_the_target() {
+	let mut fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let addr = format!("{:#x}", fixture.addr("anon"));
+	assert_eq!(fixture.values()["anon"], ANON_VALUE);
+
+	let output = lime(&["write", "-p", &pid, &addr, "-t", "u64", "-v", "42"]);
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(fixture.values()["anon"], 42);
+}
+
+#[test]
+fn errors_exit_with_2() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let addr = format!("{:#x}", fixture.addr("readonly"));
+
+	let output = lime(&["write", "-p", &pid, &addr, "-t", "u64", "-v", "1"]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("write protected"));
+
+	let output = lime(&["scan", "-p", &pid, "-t", "nonsense", "-v", "1"]);
+	assert_eq!(output.status.code(), Some(2));
+
+	let output = lime(&[
+		"scan", "-p", &pid, "-v", "41 42", "--target", "range:0x1000-0x2000",
+	]);
+	assert_eq!(output.status.code(), Some(2));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read"));
+}
+
+#[test]
+fn repl_keeps_results_between_commands() {
+	let mut fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let value = format!("{:#x}", ANON_VALUE);
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_lime"))
+		.args(["repl", "-p", &pid])
+		.env("HOME", env!("CARGO_TARGET_TMPDIR"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to run lime");
+	let script = format!("scan u64 {} anon\nlist\nwrite #0 42\nundo\nwrite #0 7\nquit\n", value);
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(script.as_bytes())
+		.unwrap();
+
+	let output = child.wait_with_output().unwrap();
+	assert_eq!(output.status.code(), Some(0));
+	assert!(!String::from_utf8_lossy(&output.stderr).contains("error:"));
+	assert!(stdout(&output).contains(&format!("{:#x}", fixture.addr("anon"))));
+	assert_eq!(fixture.values()["anon"], 7);
+}
+
+#[test]
+fn repl_next_sees_writes() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let value = format!("{:#x}", ANON_VALUE);
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_lime"))
+		.args(["repl", "-p", &pid])
+		.env("HOME", env!("CARGO_TARGET_TMPDIR"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to run lime");
+	let script = format!(
+		"scan u64 {} anon\nnext unchanged\nwrite #0 42\nnext changed\nnext 42\nnext changed\nquit\n",
+		value
+	);
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(script.as_bytes())
+		.unwrap();
+
+	let output = child.wait_with_output().unwrap();
+	assert_eq!(output.status.code(), Some(0));
+	let counts: Vec<String> = stdout(&output)
+		.lines()
+		.filter_map(|l| l.strip_suffix(" result(s)"))
+		.map(|c| c.rsplit(' ').next().unwrap().to_string())
+		.collect();
+	assert_eq!(counts, ["1", "1", "1", "1", "0"]);
+}
+
+#[test]
+fn repl_reads_through_pointer_paths() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let (guarded, anon) = (fixture.addr("guarded"), fixture.addr("anon"));
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_lime"))
+		.args(["repl", "-p", &pid])
+		.env("HOME", env!("CARGO_TARGET_TMPDIR"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to run lime");
+	let script = format!(
+		"write {0:#x} {1:#x} u64\nread [{0:#x}] u64\nread [{0:#x}]-0x10 4\nread lime-fixture+0x10 4\nquit\n",
+		guarded, anon
+	);
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(script.as_bytes())
+		.unwrap();
+
+	let output = child.wait_with_output().unwrap();
+	assert_eq!(output.status.code(), Some(0));
+	assert!(!String::from_utf8_lossy(&output.stderr).contains("error:"));
+	let lines: Vec<String> = stdout(&output).lines().map(String::from).collect();
+	assert!(lines[2].starts_with(&format!("{:#x} ", anon)));
+	assert!(lines[2].contains(&ANON_VALUE.to_string()));
+	assert!(lines[3].starts_with(&format!("{:#x} ", anon - 0x10)));
+}
+
+#[test]
+fn table_resolves_entries() {
+	let fixture = Fixture::spawn();
+	let pid = fixture.pid.to_string();
+	let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixture.toml");
+	let table = format!(
+		"[[entry]]\ndescription = \"anon\"\naddress = \"{:#x}\"\ntype = \"u64\"\n\n\
+		 [[entry]]\ndescription = \"missing\"\naddress = \"[libmissing.so+0x10]\"\ntype = \"u32\"\n",
+		fixture.addr("anon")
+	);
+	std::fs::write(&path, table).unwrap();
+
+	let output = lime(&["--json", "table", path.to_str().unwrap(), "-p", &pid]);
+	assert_eq!(output.status.code(), Some(0));
+	let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(entries[0]["value"], ANON_VALUE.to_string());
+	assert!(entries[1]["resolved"].is_null());
+	assert!(entries[1]["error"].as_str().unwrap().contains("libmissing.so"));
+}
+
+#[test]
+fn repl_follows_a_restarted_process() {
+	// A name of its own, so fixtures of other tests are not mistaken for the restart.
+	let exe = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("lime-restarted");
+	std::fs::copy(env!("CARGO_BIN_EXE_lime-fixture"), &exe).unwrap();
+	let table = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("restarted.toml");
+	std::fs::write(
+		&table,
+		"[[entry]]\ndescription = \"magic\"\naddress = \"lime-restarted+0x4\"\ntype = \"u32\"\n",
+	)
+	.unwrap();
+
+	let fixture = Fixture::spawn_from(&exe);
+	let mut child = Command::new(env!("CARGO_BIN_EXE_lime"))
+		.args(["repl", "-p", &fixture.pid.to_string()])
+		.env("HOME", env!("CARGO_TARGET_TMPDIR"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.expect("failed to run lime");
+	let mut stdin = child.stdin.take().unwrap();
+	let mut stdout = BufReader::new(child.stdout.take().unwrap());
+	writeln!(
+		stdin,
+		"watch lime-restarted+0x10 u32 header\nload {}\nwatch",
+		table.display()
+	)
+	.unwrap();
+	let mut before = Vec::new();
+	while before.len() < 2 {
+		let mut line = String::new();
+		assert!(stdout.read_line(&mut line).unwrap() > 0, "lime exited early");
+		if line.contains("lime-restarted") {
+			before.push(line);
+		}
+	}
+
+	drop(fixture);
+	let restarted = Fixture::spawn_from(&exe);
+	writeln!(stdin, "watch\nquit").unwrap();
+	drop(stdin);
+
+	let mut rest = String::new();
+	stdout.read_to_string(&mut rest).unwrap();
+	assert_eq!(child.wait().unwrap().code(), Some(0));
+	assert!(rest.contains(&format!("attached to {}", restarted.pid)), "{}", rest);
+
+	// Same module offsets and values, at the addresses of the new process.
+	let maps = std::fs::read_to_string(format!("/proc/{}/maps", restarted.pid)).unwrap();
+	let after: Vec<&str> = rest.lines().filter(|l| l.contains("lime-restarted")).collect();
+	assert_eq!(after.len(), 2, "{}", rest);
+	for (old, new) in before.iter().zip(after) {
+		let (old_addr, old_rest) = old.trim_end()[4..].split_once(' ').unwrap();
+		let (new_addr, new_rest) = new[4..].split_once(' ').unwrap();
+		assert_eq!(old_rest, new_rest);
+		assert_ne!(old_addr, new_addr);
+		let addr = u64::from_str_radix(&new_addr[2..], 16).unwrap();
+		assert!(
+			maps.lines().any(|l| {
+				let (start, end) = l.split(' ').next().unwrap().split_once('-').unwrap();
+				let start = u64::from_str_radix(start, 16).unwrap();
+				(start..u64::from_str_radix(end, 16).unwrap()).contains(&addr)
+			}),
+			"{:#x} is not mapped",
+			addr
+		);
+	}
+}
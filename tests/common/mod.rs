@@ -0,0 +1,119 @@
+//! Spawns `lime-fixture` (`src/bin/lime-fixture.rs`, behind the `fixture` feature) and parses what it reports.
+
+// Every test file includes this module but none uses all of it.
+#![allow(dead_code)]
+
+use std::{
+	collections::HashMap,
+	io::{BufRead, BufReader, Write},
+	path::Path,
+	process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+pub const HEAP_VALUE: u64 = 0x1111_2222_3333_4444;
+pub const STACK_VALUE: u64 = 0x5555_6666_7777_8888;
+pub const ANON_VALUE: u64 = 0x9999_aaaa_bbbb_cccc;
+pub const READONLY_VALUE: u64 = 0xdddd_eeee_ffff_0000;
+
+pub struct Fixture {
+	child: Child,
+	stdin: ChildStdin,
+	stdout: BufReader<ChildStdout>,
+	pub pid: u32,
+	addresses: HashMap<String, u64>,
+}
+
+impl Fixture {
+	pub fn spawn() -> Self {
+		Self::spawn_from(Path::new(env!("CARGO_BIN_EXE_lime-fixture")))
+	}
+
+	/// Runs a copy of the fixture, e.g. to give it a name no other test uses.
+	pub fn spawn_from(path: &Path) -> Self {
+		let mut child = Command::new(path)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.spawn()
+			.expect("failed to spawn lime-fixture");
+
+		let stdin = child.stdin.take().unwrap();
+		let stdout = BufReader::new(child.stdout.take().unwrap());
+		let pid = child.id();
+
+		let mut fixture = Self {
+			child,
+			stdin,
+			stdout,
+			pid,
+			addresses: HashMap::new(),
+		};
+		fixture.addresses = fixture
+			.read_block()
+			.into_iter()
+			.map(|(k, v)| {
+				let addr = u64::from_str_radix(v.trim_start_matches("0x"), 16).unwrap();
+				(k, addr)
+			})
+			.collect();
+		fixture
+	}
+
+	pub fn addr(&self, name: &str) -> u64 {
+		self.addresses[name]
+	}
+
+	/// The values the fixture itself sees in its areas.
+	pub fn values(&mut self) -> HashMap<String, u64> {
+		writeln!(self.stdin, "values").unwrap();
+		self.stdin.flush().unwrap();
+
+		self.read_block()
+			.into_iter()
+			.map(|(k, v)| {
+				(
+					k,
+					u64::from_str_radix(v.trim_start_matches("0x"), 16).unwrap(),
+				)
+			})
+			.collect()
+	}
+
+	/// The 16-byte marker in front of the value tagged `tag`, as a scan pattern.
+	pub fn marker_pattern(&self, tag: u8) -> String {
+		let mut m = [0u8; 16];
+		m[0..3].copy_from_slice(b"LIM");
+		m[3] = tag;
+		m[4..8].copy_from_slice(&self.pid.to_le_bytes());
+		m[8] = !tag;
+		m[12..16].copy_from_slice(&(!self.pid).to_le_bytes());
+
+		m.iter()
+			.map(|b| format!("{:02X}", b))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+
+	/// Reads `name value` lines up to `ready`.
+	fn read_block(&mut self) -> Vec<(String, String)> {
+		let mut block = Vec::new();
+		loop {
+			let mut line = String::new();
+			let n = self.stdout.read_line(&mut line).unwrap();
+			assert!(n > 0, "lime-fixture exited early");
+
+			let line = line.trim();
+			if line == "ready" {
+				return block;
+			}
+			let (name, value) = line.split_once(' ').expect("malformed fixture output");
+			block.push((name.to_string(), value.to_string()));
+		}
+	}
+}
+
+impl Drop for Fixture {
+	fn drop(&mut self) {
+		let _ = self.child.kill();
+		let _ = self.child.wait();
+	}
+}
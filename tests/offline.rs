@@ -0,0 +1,236 @@
+//! Dumps read back through the offline backends.
+
+#![cfg(all(feature = "procmem", feature = "ptrace", feature = "offline"))]
+
+mod common;
+
+use std::{fs::File, path::PathBuf};
+
+use common::{Fixture, HEAP_VALUE, STACK_VALUE};
+use lime::{
+	dump::{
+		elfcore::{dump_process_core, write_core},
+		lime::write_lime,
+	},
+	errors::ErrorKind,
+	maps::ProcMemoryMaps,
+	mock::process::MockProcess,
+	offline::{
+		elfcore::CoreFile,
+		lime::{LimeImage, maps_sidecar_path},
+	},
+	process::regs::ThreadRegisters,
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget},
+};
+
+fn tmp(name: &str) -> PathBuf {
+	PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+fn mock() -> MockProcess {
+	let mut process = MockProcess::new(1)
+		.with_region(0x10000, 0x2000, "r-xp", Some("/usr/bin/game"))
+		.with_region(0x20000, 0x3000, "rw-p", Some("[heap]"))
+		.with_region(0x30000, 0x1000, "---p", None)
+		.with_region(0x7ff00000, 0x2000, "rw-p", Some("[stack]"));
+	process.poke(0x20010, b"HEAPMARK");
+	process.poke(0x7ff01ff0, b"STCKMARK");
+	process
+}
+
+#[test]
+fn written_cores_keep_region_names() {
+	let mut process = mock();
+	let maps = process.get_maps().clone();
+	let path = tmp("mock.core");
+	let mut out = File::create(&path).unwrap();
+	write_core(&mut process, std::process::id(), &maps, &[], &mut out).unwrap();
+	drop(out);
+
+	let mut core = CoreFile::open(&path).unwrap();
+	assert_eq!(core.get_maps().to_maps_string(), maps.to_maps_string());
+	assert_eq!(core.get_maps().get_heap_regions().len(), 1);
+
+	let found = core.scan_for_pattern("48 45 41 50 4D 41 52 4B").unwrap();
+	assert_eq!(found, vec![0x20010]);
+	let found = core
+		.scan_for_pattern_in("53 54 43 4B", ScanTarget::HeapAndStack)
+		.unwrap();
+	assert_eq!(found, vec![0x7ff01ff0]);
+	assert!(core.read_value::<u8>(0x30000).is_err());
+}
+
+fn registers(tid: u32, sp: u64) -> ThreadRegisters {
+	let mut regs =
+		ThreadRegisters::from_bytes(tid, &[0; size_of::<libc::user_regs_struct>()]).unwrap();
+	#[cfg(target_arch = "x86_64")]
+	{
+		regs.regs.rsp = sp;
+	}
+	#[cfg(target_arch = "aarch64")]
+	{
+		regs.regs.sp = sp;
+	}
+	regs
+}
+
+/// A core of `mock()` with one thread, made to look like a kernel core if `kernel`.
+fn write_mock_core(kernel: bool) -> Vec<u8> {
+	let mut process = mock();
+	let maps = process.get_maps().clone();
+	let threads = [registers(7, 0x7ff01f00)];
+	let mut out = std::io::Cursor::new(Vec::new());
+	write_core(&mut process, std::process::id(), &maps, &threads, &mut out).unwrap();
+	let mut bytes = out.into_inner();
+	if kernel {
+		let at = bytes.windows(5).position(|w| w == b"LIME\0").unwrap();
+		bytes[at..at + 4].copy_from_slice(b"XXXX");
+	}
+	bytes
+}
+
+#[test]
+fn kernel_cores_find_the_stack_from_registers() {
+	let path = tmp("kernel.core");
+	std::fs::write(&path, write_mock_core(true)).unwrap();
+
+	let mut core = CoreFile::open(&path).unwrap();
+	let stack = core.get_maps().get_stack_regions();
+	assert_eq!(stack.len(), 1);
+	assert_eq!(stack[0].start, 0x7ff00000);
+	assert!(core.get_maps().get_heap_regions().is_empty());
+	assert_eq!(
+		core.scan_for_pattern("53 54 43 4B 4D 41 52 4B").unwrap(),
+		vec![0x7ff01ff0]
+	);
+}
+
+#[test]
+fn oversized_notes_are_rejected() {
+	let path = tmp("oversized.core");
+	let mut bytes = write_mock_core(false);
+	// `p_filesz` of the PT_NOTE header, the first after the 64 byte ELF header.
+	bytes[64 + 32..64 + 40].copy_from_slice(&(1u64 << 40).to_le_bytes());
+	std::fs::write(&path, bytes).unwrap();
+
+	let err = CoreFile::open(&path).err().unwrap();
+	assert_eq!(err.kind(), ErrorKind::InvalidElf);
+}
+
+/// Offset of the first program header of type `p_type` in a core written by `write_core`.
+fn phdr_offset(bytes: &[u8], p_type: u32) -> usize {
+	(0..)
+		.map(|i| 64 + i * 56)
+		.find(|o| bytes[*o..*o + 4] == p_type.to_le_bytes())
+		.unwrap()
+}
+
+#[test]
+fn garbage_headers_are_rejected() {
+	let path = tmp("garbage.core");
+	let bytes = write_mock_core(false);
+
+	let check = |bytes: &[u8]| {
+		std::fs::write(&path, bytes).unwrap();
+		let err = CoreFile::open(&path).err().unwrap();
+		assert!(
+			matches!(err.kind(), ErrorKind::InvalidElf | ErrorKind::ReadFailed),
+			"{}",
+			err
+		);
+	};
+
+	check(&bytes[..100]);
+
+	let mut phoff = bytes.clone();
+	phoff[32..40].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+	check(&phoff);
+
+	let mut wrapping = bytes.clone();
+	let load = phdr_offset(&bytes, 1);
+	wrapping[load + 16..load + 24].copy_from_slice(&(u64::MAX - 0xfff).to_le_bytes());
+	check(&wrapping);
+
+	// The NT_FILE note: its type, the name "CORE" padded to 8 bytes, then the entry count.
+	let mut files = bytes.clone();
+	let note = files.windows(8).position(|w| w == b"ELIFCORE").unwrap();
+	files[note + 12..note + 20].copy_from_slice(&(u64::MAX / 8).to_le_bytes());
+	check(&files);
+}
+
+#[test]
+fn dumped_fixture_scans_like_the_live_process() {
+	let fixture = Fixture::spawn();
+	let path = tmp("fixture.core");
+	let stats = dump_process_core(fixture.pid, &path).unwrap();
+	assert!(stats.threads >= 1);
+
+	let mut core = CoreFile::open(&path).unwrap();
+	assert_eq!(core.pid(), Some(fixture.pid));
+	assert_eq!(
+		core.read_value::<u64>(fixture.addr("heap")).unwrap(),
+		HEAP_VALUE
+	);
+	assert_eq!(
+		core.read_value::<u64>(fixture.addr("stack")).unwrap(),
+		STACK_VALUE
+	);
+
+	let found = core
+		.scan_for_pattern(&fixture.marker_pattern(b'H'))
+		.unwrap();
+	assert_eq!(found, vec![fixture.addr("heap") - 16]);
+	let found = core
+		.scan_for_pattern_in(&fixture.marker_pattern(b'S'), ScanTarget::HeapAndStack)
+		.unwrap();
+	assert_eq!(found, vec![fixture.addr("stack") - 16]);
+}
+
+#[test]
+fn written_lime_images_read_back() {
+	let mut process = mock();
+	let maps = process.get_maps().clone();
+	let path = tmp("mock.lime");
+	let mut out = File::create(&path).unwrap();
+	let (stats, written) = write_lime(&mut process, &maps, &mut out).unwrap();
+	drop(out);
+	std::fs::write(maps_sidecar_path(&path), written.to_maps_string()).unwrap();
+	assert_eq!(stats.ranges, 3);
+
+	let mut image = LimeImage::open(&path).unwrap();
+	assert_eq!(image.get_maps().to_maps_string(), written.to_maps_string());
+	assert_eq!(
+		image.scan_for_pattern("48 45 41 50 4D 41 52 4B").unwrap(),
+		vec![0x20010]
+	);
+	assert_eq!(
+		image.read_value::<[u8; 8]>(0x7ff01ff0).unwrap(),
+		*b"STCKMARK"
+	);
+
+	let err = image.read_value::<u8>(0x30000).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::OutOfBounds);
+
+	// Reads are checked against the maps even where the image has the bytes.
+	let mut regions = written.get_regions().to_vec();
+	regions[1].perm = "---p".to_string();
+	let maps = ProcMemoryMaps::from_regions(regions);
+	let mut image = LimeImage::open_with_maps(&path, Some(maps)).unwrap();
+	let err = image.read_value::<u64>(0x20010).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::Protected);
+}
+
+#[test]
+fn lime_ranges_overflowing_the_address_space_are_rejected() {
+	let path = tmp("overflow.lime");
+	let mut bytes = Vec::new();
+	bytes.extend_from_slice(&0x4c69_4d45u32.to_le_bytes());
+	bytes.extend_from_slice(&1u32.to_le_bytes());
+	bytes.extend_from_slice(&0xffff_ffff_ffff_f000u64.to_le_bytes());
+	bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+	bytes.resize(32 + 0x1000, 0);
+	std::fs::write(&path, bytes).unwrap();
+
+	let err = LimeImage::open_with_maps(&path, None).err().unwrap();
+	assert_eq!(err.kind(), ErrorKind::InvalidLime);
+}
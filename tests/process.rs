@@ -0,0 +1,112 @@
+//! `Process` and every live backend against a `lime-fixture` process.
+
+#![cfg(all(feature = "procvm", feature = "procmem", feature = "ptrace"))]
+
+mod common;
+
+use common::{ANON_VALUE, Fixture, HEAP_VALUE, READONLY_VALUE, STACK_VALUE};
+use lime::{
+	Backend, Process,
+	process::{
+		access::check_access,
+		auxv::{AT_ENTRY, get_auxv_value},
+	},
+	ptrace::{
+		launch::{LaunchOptions, launch_stopped},
+		regs::get_stopped_thread_registers,
+	},
+	traits::{ProcessMemoryPatternScan, ReadProcessMemory, ScanTarget, WriteProcessMemory},
+};
+
+#[test]
+fn open_prefers_process_vm() {
+	let fixture = Fixture::spawn();
+	assert!(check_access(fixture.pid).is_ok());
+
+	let process = Process::open(fixture.pid).unwrap();
+	assert_eq!(process.pid(), fixture.pid);
+	assert_eq!(process.backend(), Backend::ProcVm);
+}
+
+#[test]
+fn open_missing_process_fails() {
+	assert!(Process::open(u32::MAX / 2).is_err());
+}
+
+#[test]
+fn every_backend_reads_writes_and_scans() {
+	for backend in Backend::PREFERENCE {
+		let mut fixture = Fixture::spawn();
+
+		{
+			let mut process = Process::open_with(fixture.pid, backend).unwrap();
+			assert_eq!(process.backend(), backend);
+
+			assert_eq!(
+				process.read_value::<u64>(fixture.addr("heap")).unwrap(),
+				HEAP_VALUE,
+				"{:?}",
+				backend
+			);
+			assert_eq!(
+				process.read_value::<u64>(fixture.addr("stack")).unwrap(),
+				STACK_VALUE
+			);
+
+			// Unaligned and crossing a word boundary for ptrace.
+			let mut buf = [0u8; 11];
+			assert_eq!(
+				process
+					.read_bytes(fixture.addr("anon") - 3, &mut buf)
+					.unwrap(),
+				11
+			);
+			assert_eq!(&buf[3..11], &ANON_VALUE.to_le_bytes());
+
+			process
+				.write_value(fixture.addr("heap") + 3, &0xabu8)
+				.unwrap();
+			process
+				.write_value(fixture.addr("anon") + 6, &0u32)
+				.unwrap();
+			assert!(
+				process
+					.write_value(fixture.addr("readonly"), &0u64)
+					.is_err()
+			);
+
+			let found = process
+				.scan_for_pattern_in(&fixture.marker_pattern(b'A'), ScanTarget::Anonymous)
+				.unwrap();
+			assert_eq!(found, vec![fixture.addr("anon") - 16], "{:?}", backend);
+
+			let scan = process
+				.scan_touched_for_pattern_in(
+					&fixture.marker_pattern(b'H'),
+					ScanTarget::HeapAndStack,
+				)
+				.unwrap();
+			assert!(scan.matches.contains(&(fixture.addr("heap") - 16)));
+		}
+
+		// Dropping a ptrace backend detaches, so the fixture answers again.
+		let values = fixture.values();
+		assert_eq!(values["heap"], HEAP_VALUE & !0xff00_0000 | 0xab00_0000);
+		assert_eq!(values["anon"], ANON_VALUE & 0x0000_ffff_ffff_ffff);
+		assert_eq!(values["readonly"], READONLY_VALUE);
+	}
+}
+
+#[test]
+fn launched_processes_stop_at_the_entry_point() {
+	let options = LaunchOptions::new(env!("CARGO_BIN_EXE_lime-fixture"));
+	let mut launched = launch_stopped(&options).unwrap();
+
+	let entry = get_auxv_value(launched.pid, AT_ENTRY).unwrap().unwrap();
+	assert_eq!(launched.entry, entry);
+	let regs = get_stopped_thread_registers(launched.pid).unwrap();
+	assert_eq!(regs.pc(), entry);
+
+	launched.kill().unwrap();
+	assert!(!launched.wait().unwrap().success());
+}
@@ -0,0 +1,262 @@
+//! `ProcMem` against a live `lime-fixture` process.
+
+#![cfg(feature = "procmem")]
+
+mod common;
+
+use std::os::fd::AsRawFd;
+
+use common::{ANON_VALUE, Fixture, HEAP_VALUE, READONLY_VALUE, STACK_VALUE};
+use lime::{
+	procmem::{
+		procmem::ProcMem,
+		softdirty::{SoftDirtyTracker, soft_dirty_supported},
+	},
+	traits::{
+		ProcessMemoryPatternScan, ReadProcessMemory, RegionFilter, ScanTarget, WriteProcessMemory,
+	},
+};
+
+fn open(fixture: &Fixture) -> ProcMem {
+	ProcMem::new(fixture.pid, true).expect("failed to open fixture memory")
+}
+
+#[test]
+fn reads_known_values() {
+	let fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+
+	assert_eq!(
+		procmem.read_value::<u64>(fixture.addr("heap")).unwrap(),
+		HEAP_VALUE
+	);
+	assert_eq!(
+		procmem.read_value::<u64>(fixture.addr("stack")).unwrap(),
+		STACK_VALUE
+	);
+	assert_eq!(
+		procmem.read_value::<u64>(fixture.addr("anon")).unwrap(),
+		ANON_VALUE
+	);
+	assert_eq!(
+		procmem.read_value::<u64>(fixture.addr("readonly")).unwrap(),
+		READONLY_VALUE
+	);
+
+	let mut buf = [0u8; 8];
+	assert_eq!(
+		procmem.read_bytes(fixture.addr("anon"), &mut buf).unwrap(),
+		8
+	);
+	assert_eq!(u64::from_le_bytes(buf), ANON_VALUE);
+}
+
+#[test]
+fn writes_are_visible_to_the_target() {
+	let mut fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+
+	procmem.write_value(fixture.addr("heap"), &1u64).unwrap();
+	procmem.write_value(fixture.addr("stack"), &2u64).unwrap();
+	procmem.write_value(fixture.addr("anon"), &3u64).unwrap();
+
+	let values = fixture.values();
+	assert_eq!(values["heap"], 1);
+	assert_eq!(values["stack"], 2);
+	assert_eq!(values["anon"], 3);
+}
+
+#[test]
+fn read_only_handles_reopen_for_the_first_write() {
+	let mut fixture = Fixture::spawn();
+	let mut procmem = ProcMem::new(fixture.pid, false).unwrap();
+	let access_mode =
+		|p: &ProcMem| unsafe { libc::fcntl(p.mem_file.as_raw_fd(), libc::F_GETFL) } & libc::O_ACCMODE;
+
+	assert_eq!(
+		procmem.read_value::<u64>(fixture.addr("anon")).unwrap(),
+		ANON_VALUE
+	);
+	assert_eq!(access_mode(&procmem), libc::O_RDONLY);
+
+	procmem.write_value(fixture.addr("anon"), &7u64).unwrap();
+	assert_eq!(access_mode(&procmem), libc::O_RDWR);
+	assert_eq!(fixture.values()["anon"], 7);
+}
+
+#[test]
+fn write_to_read_only_mapping_is_refused() {
+	let mut fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+
+	assert!(
+		procmem
+			.write_value(fixture.addr("readonly"), &0u64)
+			.is_err()
+	);
+	assert_eq!(fixture.values()["readonly"], READONLY_VALUE);
+}
+
+#[test]
+fn region_lookups() {
+	let fixture = Fixture::spawn();
+	let procmem = open(&fixture);
+	let maps = procmem.get_maps();
+
+	let heap = maps.find_region_by_addr(fixture.addr("heap")).unwrap();
+	assert_eq!(heap.pathname.as_deref(), Some("[heap]"));
+
+	let stack = maps.find_region_by_addr(fixture.addr("stack")).unwrap();
+	assert_eq!(stack.pathname.as_deref(), Some("[stack]"));
+
+	let anon = maps.find_region_by_addr(fixture.addr("anon")).unwrap();
+	assert!(anon.is_anonymous() && anon.is_writeable());
+
+	let readonly = maps.find_region_by_addr(fixture.addr("readonly")).unwrap();
+	assert_eq!(readonly.perm, "r--p");
+
+	assert_eq!(
+		maps.get_module_base("lime-fixture").map(|b| b & 0xfff),
+		Some(0)
+	);
+}
+
+#[test]
+fn region_end_is_exclusive() {
+	let fixture = Fixture::spawn();
+	let procmem = open(&fixture);
+	let maps = procmem.get_maps();
+	let guard = fixture.addr("guard");
+
+	// The writable page ends where the inaccessible one starts.
+	let writable = maps.find_region_by_addr(guard - 1).unwrap();
+	assert_eq!(writable.end, guard);
+	assert!(!writable.contains(guard));
+
+	let inaccessible = maps.find_region_by_addr(guard).unwrap();
+	assert_eq!(inaccessible.perm, "---p");
+
+	assert!(maps.can_write(guard - 8, 8).is_ok());
+	assert!(maps.can_write(guard - 4, 8).is_err());
+	assert!(maps.can_read(guard, 1).is_err());
+	assert!(maps.can_write(guard, 1).is_err());
+}
+
+#[test]
+fn scans_find_markers() {
+	let fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+
+	let heap_marker = fixture.addr("heap") - 16;
+	let stack_marker = fixture.addr("stack") - 16;
+	let anon_marker = fixture.addr("anon") - 16;
+
+	let found = procmem
+		.scan_for_pattern_in(&fixture.marker_pattern(b'H'), ScanTarget::HeapAndStack)
+		.unwrap();
+	assert!(found.contains(&heap_marker));
+
+	let found = procmem
+		.scan_for_pattern_in(
+			&fixture.marker_pattern(b'S'),
+			ScanTarget::ThreadStack(fixture.pid),
+		)
+		.unwrap();
+	assert!(found.contains(&stack_marker));
+
+	let found = procmem
+		.scan_for_pattern_in(&fixture.marker_pattern(b'A'), ScanTarget::Anonymous)
+		.unwrap();
+	assert_eq!(found, vec![anon_marker]);
+
+	let found = procmem
+		.scan_for_pattern_in(
+			&fixture.marker_pattern(b'A'),
+			ScanTarget::Range(anon_marker - 3, anon_marker + 16),
+		)
+		.unwrap();
+	assert_eq!(found, vec![anon_marker]);
+
+	assert!(
+		procmem
+			.scan_for_pattern_in(&fixture.marker_pattern(b'A'), ScanTarget::HeapAndStack)
+			.is_none()
+	);
+}
+
+#[test]
+fn touched_scan_finds_written_pages() {
+	let fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+
+	let scan = procmem
+		.scan_touched_for_pattern_in(&fixture.marker_pattern(b'H'), ScanTarget::HeapAndStack)
+		.unwrap();
+	assert!(scan.matches.contains(&(fixture.addr("heap") - 16)));
+	assert!(scan.inspected_bytes() > 0);
+	assert!(scan.inspected_bytes() <= scan.selected_bytes());
+}
+
+#[test]
+fn filtered_scans_see_pages_touched_since_the_last_scan() {
+	let fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+	let guarded = fixture.addr("guarded");
+	let range = ScanTarget::Range(guarded, guarded + 0x1000);
+	let resident = ScanTarget::Filtered(&range, RegionFilter::Resident);
+	let pattern = "46 52 45 53 48 4D 41 52 4B";
+
+	// The page was never touched, so the region has no Rss yet.
+	assert_eq!(procmem.scan_for_pattern_in(pattern, resident), None);
+
+	procmem.write_bytes(guarded + 8, b"FRESHMARK").unwrap();
+	assert_eq!(
+		procmem.scan_for_pattern_in(
+			pattern,
+			ScanTarget::Filtered(&range, RegionFilter::Resident)
+		),
+		Some(vec![guarded + 8])
+	);
+
+	procmem.refresh_maps().unwrap();
+	assert_eq!(
+		procmem.scan_for_pattern_in(
+			pattern,
+			ScanTarget::Filtered(&range, RegionFilter::Resident)
+		),
+		Some(vec![guarded + 8])
+	);
+}
+
+#[test]
+fn soft_dirty_tracker_reports_written_addresses() {
+	if !soft_dirty_supported() {
+		eprintln!("skipped: kernel without CONFIG_MEM_SOFT_DIRTY");
+		return;
+	}
+	let fixture = Fixture::spawn();
+	let mut procmem = open(&fixture);
+	let (anon, readonly) = (fixture.addr("anon"), fixture.addr("readonly"));
+
+	let tracker = SoftDirtyTracker::start(fixture.pid).unwrap();
+	assert!(
+		tracker
+			.dirty_addresses(&[anon, readonly], 8)
+			.unwrap()
+			.is_empty()
+	);
+
+	procmem.write_value(anon, &42u64).unwrap();
+	assert_eq!(
+		tracker.dirty_addresses(&[readonly, anon], 8).unwrap(),
+		vec![anon]
+	);
+
+	tracker.reset().unwrap();
+	assert!(
+		tracker
+			.dirty_addresses(&[anon, readonly], 8)
+			.unwrap()
+			.is_empty()
+	);
+}
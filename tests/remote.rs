@@ -0,0 +1,215 @@
+//! `#[derive(RemoteStruct)]` against a `MockProcess`.
+
+#![cfg(feature = "derive")]
+
+use lime::{
+	errors::ErrorKind,
+	mock::process::MockProcess,
+	remote::{
+		cstr::RemoteCStr,
+		ptr::{RemotePtr, RemoteSlice},
+		structs::{RemoteField, RemoteStruct},
+	},
+};
+
+#[derive(RemoteStruct, Debug, PartialEq)]
+struct Vec3 {
+	x: f32,
+	y: f32,
+	z: f32,
+}
+
+#[derive(RemoteStruct, Debug, PartialEq)]
+struct Weapon {
+	damage: u32,
+	#[remote(padding = 2)]
+	ammo: u16,
+}
+
+#[derive(RemoteStruct, Debug, PartialEq)]
+#[remote(size = 0x60)]
+struct Player {
+	#[remote(offset = 0x10)]
+	health: i32,
+	alive: bool,
+	#[remote(padding = 3)]
+	position: Vec3,
+	#[remote(offset = 0x30, pointer)]
+	weapon: Option<Weapon>,
+	#[remote(pointer)]
+	offhand: Option<Weapon>,
+	#[remote(offset = 0x40)]
+	slots: [u16; 4],
+}
+
+#[derive(RemoteStruct, Debug, PartialEq)]
+struct Node {
+	value: u32,
+	#[remote(padding = 4)]
+	next: u64,
+	name: u64,
+}
+
+const PLAYER: u64 = 0x1000;
+const WEAPON: u64 = 0x1800;
+
+fn process() -> MockProcess {
+	let mut process = MockProcess::new(1).with_region(0x1000, 0x1000, "rw-p", None);
+	process.poke(PLAYER + 0x10, &(-5i32).to_ne_bytes());
+	process.poke(PLAYER + 0x14, &[1]);
+	for (i, v) in [1.0f32, 2.0, 3.0].iter().enumerate() {
+		process.poke(PLAYER + 0x18 + 4 * i as u64, &v.to_ne_bytes());
+	}
+	process.poke(PLAYER + 0x30, &WEAPON.to_ne_bytes());
+	process.poke(PLAYER + 0x42, &7u16.to_ne_bytes());
+	process.poke(WEAPON, &50u32.to_ne_bytes());
+	process.poke(WEAPON + 6, &30u16.to_ne_bytes());
+	process
+}
+
+#[test]
+fn layout_follows_offsets_and_padding() {
+	assert_eq!(<Vec3 as RemoteField>::SIZE, 12);
+	assert_eq!(<Weapon as RemoteField>::SIZE, 8);
+	assert_eq!(Player::SIZE, 0x60);
+}
+
+#[test]
+fn reads_whole_structs_and_follows_pointers() {
+	let mut process = process();
+	let player = Player::read_remote(&mut process, PLAYER).unwrap();
+
+	assert_eq!(
+		player,
+		Player {
+			health: -5,
+			alive: true,
+			position: Vec3 {
+				x: 1.0,
+				y: 2.0,
+				z: 3.0
+			},
+			weapon: Some(Weapon {
+				damage: 50,
+				ammo: 30
+			}),
+			offhand: None,
+			slots: [0, 7, 0, 0],
+		}
+	);
+}
+
+#[test]
+fn writes_single_fields() {
+	let mut process = process();
+	Player::write_health(&mut process, PLAYER, &100).unwrap();
+	Player::write_position(
+		&mut process,
+		PLAYER,
+		&Vec3 {
+			x: 0.0,
+			y: 0.5,
+			z: 0.0,
+		},
+	)
+	.unwrap();
+	Weapon::write_ammo(&mut process, WEAPON, &29).unwrap();
+
+	let player = Player::read_remote(&mut process, PLAYER).unwrap();
+	assert_eq!(player.health, 100);
+	assert_eq!(player.position.y, 0.5);
+	assert_eq!(player.weapon.unwrap().ammo, 29);
+	assert!(player.alive);
+}
+
+#[derive(RemoteStruct, Debug, PartialEq)]
+#[remote(size = 0x18)]
+struct Holster {
+	#[remote(offset = 4)]
+	weapon: Weapon,
+	spare: [Weapon; 1],
+}
+
+#[test]
+fn writing_nested_structs_keeps_the_gaps() {
+	let mut process = process();
+	let holster = 0x1a00;
+	process.poke(holster, &[0xaa; 0x18]);
+
+	let weapon = Weapon { damage: 1, ammo: 2 };
+	Holster::write_weapon(&mut process, holster, &weapon).unwrap();
+	Holster::write_spare(&mut process, holster, &[Weapon { damage: 3, ammo: 4 }]).unwrap();
+
+	let bytes = process.peek(holster, 0x18).unwrap();
+	assert_eq!(bytes[..4], [0xaa; 4]);
+	assert_eq!(bytes[8..10], [0xaa; 2]);
+	assert_eq!(bytes[16..18], [0xaa; 2]);
+	assert_eq!(bytes[20..], [0xaa; 4]);
+	assert_eq!(
+		Holster::read_remote(&mut process, holster).unwrap(),
+		Holster {
+			weapon,
+			spare: [Weapon { damage: 3, ammo: 4 }],
+		}
+	);
+}
+
+#[test]
+fn invalid_fields_and_short_reads_fail() {
+	let mut process = process();
+	process.poke(PLAYER + 0x14, &[2]);
+	let err = Player::read_remote(&mut process, PLAYER).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::BadDataType);
+
+	let err = Player::read_remote(&mut process, 0x1fc0).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::ReadFailed);
+}
+
+#[test]
+fn walks_linked_lists_and_arrays() {
+	let mut process = process();
+	let nodes = [
+		(0x1900, 1u32, 0x1940u64),
+		(0x1940, 2, 0x1980),
+		(0x1980, 3, 0),
+	];
+	for (addr, value, next) in nodes {
+		process.poke(addr, &value.to_ne_bytes());
+		process.poke(addr + 8, &next.to_ne_bytes());
+		process.poke(addr + 16, &(addr + 0x20).to_ne_bytes());
+		process.poke(addr + 0x20, format!("node {}\0", value).as_bytes());
+	}
+	let maps = process.get_maps().clone();
+
+	let mut values = Vec::new();
+	let mut names = Vec::new();
+	let mut node = RemotePtr::<Node, _>::new(&mut process, 0x1900).with_maps(&maps);
+	while let Some(n) = node.read_opt().unwrap() {
+		values.push(n.value);
+		names.push(n.name);
+		node = node.with_addr(n.next);
+	}
+	assert_eq!(values, [1, 2, 3]);
+	let names: Vec<String> = names
+		.into_iter()
+		.map(|addr| RemoteCStr::new(&mut process, addr).read().unwrap())
+		.collect();
+	assert_eq!(names, ["node 1", "node 2", "node 3"]);
+
+	let mut weapons = RemoteSlice::<Weapon, _>::new(&mut process, WEAPON, 2).with_maps(&maps);
+	let weapons = weapons.iter().collect::<Result<Vec<_>, _>>().unwrap();
+	assert_eq!(
+		weapons[0],
+		Weapon {
+			damage: 50,
+			ammo: 30
+		}
+	);
+	assert_eq!(weapons[1], Weapon { damage: 0, ammo: 0 });
+
+	let err = RemoteSlice::<Weapon, _>::new(&mut process, 0x1ff8, 2)
+		.with_maps(&maps)
+		.to_vec()
+		.unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::OutOfBounds);
+}